@@ -1,31 +1,66 @@
 /**
- * 微博Cookies验证脚本
+ * 微博Cookies验证Worker
  *
- * 使用Playwright启动headless浏览器,设置cookies后访问微博个人资料API,
- * 验证cookies是否有效,并提取UID和用户昵称。
+ * 常驻进程,由桌面端启动并复用: 所有请求共享同一个headless浏览器,
+ * 每个请求使用独立的BrowserContext (互不共享cookies),同时最多 MAX_CONTEXTS 个,其余排队。
+ * 浏览器崩溃后下一个请求自动重新启动浏览器。
  *
- * 输入: JSON格式的cookies对象 (通过命令行参数或stdin)
- * 输出: JSON格式的验证结果
+ * 协议: stdin/stdout,每行一个JSON (NDJSON)
+ * 请求: {"id": 1, "cookies": {"SUB": "xxx", "SUBP": "yyy"}}
+ * 响应: {"id": 1, "valid": true, "uid": "123", "screen_name": "昵称"}
+ *       {"id": 1, "valid": false, "error": "...", "status": 200, "url": "https://..."}
+ *       {"id": 1, "valid": false, "error": "...", "outcome": "network_error"}
+ *
+ * 失败原因 (过期、封禁、验证码等) 由桌面端根据 status/url/error 分类;
+ * 脚本只对请求未得到响应的情况给出 outcome: network_error / timeout / validator_error。
+ *
+ * cookies只经stdin传递,不出现在命令行参数中 (其他本地用户可通过ps看到参数)。
+ * stdout只输出响应,日志写到stderr。stdin关闭后处理完已收到的请求再退出。
  *
  * 示例:
  * ```bash
- * node dist/validate-cookies.js '{"SUB":"xxx","SUBP":"yyy"}'
+ * echo '{"id":1,"cookies":{"SUB":"xxx"}}' | node dist/validate-cookies.js
  * ```
  */
 
-import { chromium, Cookie } from 'playwright';
+import { chromium, APIResponse, Browser, Cookie } from 'playwright';
+import * as readline from 'readline';
+
+/// 同时存在的BrowserContext上限
+const MAX_CONTEXTS = Math.max(1, Number(process.env.VALIDATION_MAX_CONTEXTS) || 4);
 
 /// 输入的cookies格式
 interface InputCookies {
   [key: string]: string;
 }
 
+/// 验证请求
+interface ValidationRequest {
+  id: number;
+  cookies: InputCookies;
+}
+
+/// 请求未得到响应时的失败类型
+type FailureOutcome = 'network_error' | 'timeout' | 'validator_error';
+
 /// 验证结果
 interface ValidationResult {
   valid: boolean;
   uid?: string;
   screen_name?: string;
   error?: string;
+  /// HTTP状态码
+  status?: number;
+  /// 跟随重定向后的最终URL (被重定向到登录页、安全验证页时可据此判断原因)
+  url?: string;
+  outcome?: FailureOutcome;
+}
+
+/**
+ * 错误信息
+ */
+function errorMessage(error: unknown): string {
+  return error instanceof Error ? error.message : String(error);
 }
 
 /**
@@ -62,6 +97,56 @@ interface VipCenterResponse {
   msg?: string;
 }
 
+let browserPromise: Promise<Browser> | null = null;
+
+/**
+ * 获取共享浏览器,未启动或已断开时重新启动
+ */
+function getBrowser(): Promise<Browser> {
+  if (!browserPromise) {
+    browserPromise = chromium
+      .launch({
+        headless: true,
+        args: ['--no-sandbox', '--disable-setuid-sandbox'],
+      })
+      .then((browser) => {
+        browser.on('disconnected', () => {
+          console.error('[validate-cookies] 浏览器已断开,下次请求时重新启动');
+          browserPromise = null;
+        });
+        return browser;
+      })
+      .catch((error) => {
+        browserPromise = null;
+        throw error;
+      });
+  }
+  return browserPromise;
+}
+
+/**
+ * 限制同时存在的context数量
+ */
+let activeContexts = 0;
+const waiting: Array<() => void> = [];
+
+async function acquireSlot(): Promise<void> {
+  if (activeContexts < MAX_CONTEXTS) {
+    activeContexts++;
+    return;
+  }
+  await new Promise<void>((resolve) => waiting.push(resolve));
+}
+
+function releaseSlot(): void {
+  const next = waiting.shift();
+  if (next) {
+    next();
+  } else {
+    activeContexts--;
+  }
+}
+
 /**
  * 验证cookies有效性
  *
@@ -69,42 +154,74 @@ interface VipCenterResponse {
  * 成功标准: code === 100000 且存在 data.uid
  */
 async function validateCookies(inputCookies: InputCookies): Promise<ValidationResult> {
-  const browser = await chromium.launch({
-    headless: true,
-    args: ['--no-sandbox', '--disable-setuid-sandbox'],
-  });
+  await acquireSlot();
 
-  try {
-    const context = await browser.newContext({
-      userAgent: 'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36',
-    });
+  const context = await getBrowser()
+    .then((browser) =>
+      browser.newContext({
+        userAgent: 'Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36',
+      }),
+    )
+    .catch((error: unknown) => new Error(errorMessage(error)));
+  if (context instanceof Error) {
+    releaseSlot();
+    return { valid: false, error: context.message, outcome: 'validator_error' };
+  }
 
-    const cookies = convertToCookies(inputCookies);
-    await context.addCookies(cookies);
+  try {
+    await context.addCookies(convertToCookies(inputCookies));
 
     // 使用context.request直接调用VIP中心API,优雅且高效
-    const response = await context.request.get('https://vip.weibo.com/aj/vipcenter/user', {
-      headers: {
-        'accept': 'application/json, text/plain, */*',
-        'referer': 'https://vip.weibo.com/home',
-      },
-      timeout: 10000,
-    });
+    let response: APIResponse;
+    try {
+      response = await context.request.get('https://vip.weibo.com/aj/vipcenter/user', {
+        headers: {
+          'accept': 'application/json, text/plain, */*',
+          'referer': 'https://vip.weibo.com/home',
+        },
+        timeout: 10000,
+      });
+    } catch (error) {
+      const timedOut = error instanceof Error && error.name === 'TimeoutError';
+      return {
+        valid: false,
+        error: errorMessage(error),
+        outcome: timedOut ? 'timeout' : 'network_error',
+      };
+    }
+
+    const status = response.status();
+    const url = response.url();
 
     if (!response.ok()) {
       return {
         valid: false,
-        error: `HTTP ${response.status()}: Failed to access VIP center`,
+        error: `HTTP ${status}: Failed to access VIP center`,
+        status,
+        url,
       };
     }
 
-    const vipData: VipCenterResponse = await response.json();
+    let vipData: VipCenterResponse;
+    try {
+      vipData = await response.json();
+    } catch {
+      // 被重定向到登录页、安全验证页时返回的是HTML
+      return {
+        valid: false,
+        error: 'VIP center returned non-JSON response',
+        status,
+        url,
+      };
+    }
 
     // 验证响应格式和必要字段
     if (vipData.code !== 100000 || !vipData.data?.uid) {
       return {
         valid: false,
         error: vipData.msg || 'Invalid cookies or missing uid',
+        status,
+        url,
       };
     }
 
@@ -113,59 +230,74 @@ async function validateCookies(inputCookies: InputCookies): Promise<ValidationRe
       uid: String(vipData.data.uid),
       screen_name: vipData.data.nickname || 'Unknown',
     };
-
   } catch (error) {
-    return {
-      valid: false,
-      error: error instanceof Error ? error.message : String(error),
-    };
+    return { valid: false, error: errorMessage(error), outcome: 'validator_error' };
   } finally {
-    await browser.close();
+    await context.close().catch(() => undefined);
+    releaseSlot();
   }
 }
 
 /**
- * 主函数
+ * 输出一行响应
  */
-async function main() {
+function respond(id: number | null, result: ValidationResult): void {
+  process.stdout.write(JSON.stringify({ id, ...result }) + '\n');
+}
+
+/**
+ * 处理一行请求
+ */
+async function handleLine(line: string): Promise<void> {
+  let request: ValidationRequest;
   try {
-    // 从命令行参数读取cookies JSON
-    const cookiesJson = process.argv[2];
+    request = JSON.parse(line);
+  } catch (error) {
+    // 无法解析出id,桌面端按协议错误处理
+    respond(null, { valid: false, error: `无效的请求: ${errorMessage(error)}` });
+    return;
+  }
 
-    if (!cookiesJson) {
-      console.error(JSON.stringify({
-        valid: false,
-        error: '缺少cookies参数。使用方法: node validate-cookies.js \'{"SUB":"xxx"}\'',
-      }));
-      process.exit(1);
-    }
+  if (typeof request.id !== 'number' || typeof request.cookies !== 'object' || request.cookies === null) {
+    respond(typeof request.id === 'number' ? request.id : null, {
+      valid: false,
+      error: '请求缺少id或cookies字段',
+    });
+    return;
+  }
 
-    // 解析JSON
-    const inputCookies: InputCookies = JSON.parse(cookiesJson);
+  respond(request.id, await validateCookies(request.cookies));
+}
 
-    // 验证cookies
-    const result = await validateCookies(inputCookies);
+/**
+ * 主函数
+ */
+async function main() {
+  const pending = new Set<Promise<void>>();
+  const input = readline.createInterface({ input: process.stdin, terminal: false });
 
-    // 输出结果到stdout
-    console.log(JSON.stringify(result));
+  input.on('line', (line) => {
+    if (!line.trim()) {
+      return;
+    }
+    const task = handleLine(line).finally(() => pending.delete(task));
+    pending.add(task);
+  });
 
-    // 退出码: 0=成功, 1=失败
-    process.exit(result.valid ? 0 : 1);
+  // stdin关闭: 桌面端已退出或不再需要worker
+  await new Promise<void>((resolve) => input.once('close', resolve));
+  await Promise.all(pending);
 
-  } catch (error) {
-    console.error(JSON.stringify({
-      valid: false,
-      error: error instanceof Error ? error.message : String(error),
-    }));
-    process.exit(1);
+  if (browserPromise) {
+    const browser = await browserPromise.catch(() => null);
+    await browser?.close().catch(() => undefined);
   }
 }
 
 // 运行主函数
-main().catch((error) => {
-  console.error(JSON.stringify({
-    valid: false,
-    error: error.message,
-  }));
-  process.exit(1);
-});
+main()
+  .then(() => process.exit(0))
+  .catch((error) => {
+    console.error('[validate-cookies] worker异常退出:', error);
+    process.exit(1);
+  });
@@ -0,0 +1,175 @@
+//! 账户生命周期命令
+//!
+//! 按状态列出账户,标记失效或归档,以及回收站的恢复与清除。
+//! 账户变化监听的缓存与状态也从这里读取。
+//! 删除账户见 `cookies_commands::delete_cookies` (移入回收站)。
+
+use crate::models::{AccountState, AccountSummary, AuditEvent, AuditOperation};
+use crate::services::WatchStatus;
+use crate::state::AppState;
+use tauri::State;
+
+/// 按状态列出账户
+///
+/// # 参数
+/// - `states`: 要包含的状态,缺省或为空时返回所有账户 (含回收站)
+///
+/// # 返回
+/// 账户摘要 (UID、昵称、状态、原因、清除期限),不含cookies值
+#[tauri::command]
+pub async fn list_accounts(
+    states: Option<Vec<AccountState>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AccountSummary>, String> {
+    tracing::debug!(状态 = ?states, "调用list_accounts命令");
+
+    state
+        .redis
+        .list_accounts(&states.unwrap_or_default())
+        .await
+        .map_err(|e| format!("List accounts failed: {}", e))
+}
+
+/// 修改账户状态
+///
+/// 标记失效 (`invalid`)、归档 (`archived`) 或重新启用 (`active`)。
+/// 失效和归档的账户不会自动过期,也不参与租约分配。
+///
+/// # 参数
+/// - `uid`: 微博用户ID
+/// - `account_state`: 目标状态
+/// - `reason`: 原因说明,缺省时清除原有原因
+///
+/// # 返回
+/// 修改前的状态
+#[tauri::command]
+pub async fn set_account_state(
+    uid: String,
+    account_state: AccountState,
+    reason: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<AccountState, String> {
+    tracing::info!(用户ID = %uid, 目标状态 = ?account_state, 原因 = ?reason, "调用set_account_state命令");
+
+    let result = state
+        .redis
+        .set_account_state(&uid, account_state, reason.as_deref())
+        .await;
+
+    state
+        .audit
+        .record(
+            AuditEvent::new(AuditOperation::StateChange, "set_account_state")
+                .with_uid(&uid)
+                .with_detail(format!("目标状态 {}", account_state.as_str()))
+                .with_result(&result),
+        )
+        .await;
+
+    result.map_err(|e| format!("Set account state failed: {}", e))
+}
+
+/// 从回收站恢复账户
+///
+/// # 返回
+/// 恢复后的状态 (删除前的状态;若Cookies在回收站期间已过有效期则为 `invalid`)
+#[tauri::command]
+pub async fn restore_account(
+    uid: String,
+    state: State<'_, AppState>,
+) -> Result<AccountState, String> {
+    tracing::info!(用户ID = %uid, "调用restore_account命令");
+
+    let result = state.redis.restore_account(&uid).await;
+
+    state
+        .audit
+        .record(
+            AuditEvent::new(AuditOperation::Restore, "restore_account")
+                .with_uid(&uid)
+                .with_result(&result),
+        )
+        .await;
+
+    result.map_err(|e| format!("Restore account failed: {}", e))
+}
+
+/// 立即清除账户
+///
+/// 不等待清除期限,永久删除Cookies、历史快照、标签和租约记录。
+/// 任何状态的账户都可以清除,不可恢复。
+#[tauri::command]
+pub async fn purge_account(uid: String, state: State<'_, AppState>) -> Result<(), String> {
+    tracing::info!(用户ID = %uid, "调用purge_account命令");
+
+    let result = state.redis.delete_cookies(&uid).await;
+
+    state
+        .audit
+        .record(
+            AuditEvent::new(AuditOperation::Purge, "purge_account")
+                .with_uid(&uid)
+                .with_result(&result),
+        )
+        .await;
+
+    result.map_err(|e| format!("Purge account failed: {}", e))
+}
+
+/// 清空回收站
+///
+/// # 返回
+/// 被清除的UID
+#[tauri::command]
+pub async fn empty_trash(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    tracing::info!("调用empty_trash命令");
+
+    let trashed = state
+        .redis
+        .list_accounts(&[AccountState::DeletedPendingPurge])
+        .await
+        .map_err(|e| format!("Empty trash failed: {}", e))?;
+
+    let mut purged = Vec::with_capacity(trashed.len());
+    for account in trashed {
+        let result = state.redis.delete_cookies(&account.uid).await;
+
+        state
+            .audit
+            .record(
+                AuditEvent::new(AuditOperation::Purge, "empty_trash")
+                    .with_uid(&account.uid)
+                    .with_result(&result),
+            )
+            .await;
+
+        result.map_err(|e| format!("Empty trash failed: {}", e))?;
+        purged.push(account.uid);
+    }
+
+    Ok(purged)
+}
+
+/// 读取缓存的账户列表
+///
+/// 由账户变化监听器维护,不访问Redis。前端启动时调用一次,
+/// 之后根据 `account_changed` / `account_expired` / `account_deleted` 事件增量更新。
+#[tauri::command]
+pub async fn list_cached_accounts(
+    state: State<'_, AppState>,
+) -> Result<Vec<AccountSummary>, String> {
+    tracing::debug!("调用list_cached_accounts命令");
+
+    Ok(state.watcher.cached_accounts().await)
+}
+
+/// 查询账户变化监听状态
+///
+/// # 返回
+/// 监听方式 (keyspace通知/轮询)、缓存账户数、上次同步时间和最近错误
+#[tauri::command]
+pub async fn get_account_watch_status(state: State<'_, AppState>) -> Result<WatchStatus, String> {
+    tracing::debug!("调用get_account_watch_status命令");
+
+    Ok(state.watcher.status().await)
+}
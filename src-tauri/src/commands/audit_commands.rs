@@ -0,0 +1,24 @@
+//! 审计日志命令
+//!
+//! 分页查看cookies读写与租约操作的审计记录,可按UID或操作类型过滤。
+
+use crate::models::{AuditPage, AuditQuery};
+use crate::state::AppState;
+use tauri::State;
+
+/// 分页查询审计日志
+///
+/// 按时间倒序返回。翻页时把上一页的 `next_cursor` 作为 `before` 传入。
+#[tauri::command]
+pub async fn query_audit_log(
+    query: AuditQuery,
+    state: State<'_, AppState>,
+) -> Result<AuditPage, String> {
+    tracing::debug!(用户ID = ?query.uid, 操作 = ?query.operations, 游标 = ?query.before, "调用query_audit_log命令");
+
+    state
+        .audit
+        .page(&query)
+        .await
+        .map_err(|e| format!("Query audit log failed: {}", e))
+}
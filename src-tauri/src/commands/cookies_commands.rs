@@ -1,4 +1,8 @@
-use crate::models::{CookiesData, StorageError, ValidationError};
+use crate::models::{
+    AuditEvent, AuditOperation, CookiesData, SnapshotSource, SnapshotValidation, StorageError,
+    ValidationError, ValidationOutcome,
+};
+use crate::services::{RefreshReport, SaveMode, SaveOutcome, SaveWinner};
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -19,6 +23,39 @@ pub enum SaveCookiesError {
         message: String,
     },
 
+    /// Cookies已过期或已登出,需要重新扫码登录
+    #[error("Cookies已过期: {message}")]
+    CookiesExpired {
+        message: String,
+    },
+
+    /// 账户被封禁,重新登录无效
+    #[error("账户已被封禁: {message}")]
+    AccountBanned {
+        message: String,
+    },
+
+    /// 账户被锁定,需在微博完成安全验证
+    #[error("账户已被锁定: {message}")]
+    AccountLocked {
+        message: String,
+    },
+
+    /// 验证时遇到验证码
+    #[error("需要完成验证码: {message}")]
+    CaptchaRequired {
+        message: String,
+    },
+
+    /// 请求过于频繁,稍后重试
+    ///
+    /// `retry_after`: 建议的重试等待秒数 (本地限速器排队超限时给出,微博限流时为None)
+    #[error("请求过于频繁: {message}")]
+    RateLimited {
+        retry_after: Option<u64>,
+        message: String,
+    },
+
     /// 缺少必需的cookie字段
     #[error("缺少必需的cookie字段: {cookie_name}")]
     MissingCookie {
@@ -43,6 +80,22 @@ pub enum SaveCookiesError {
         message: String,
     },
 
+    /// 验证请求失败 (网络错误)
+    #[error("验证请求失败: {message}")]
+    ValidationRequestFailed {
+        message: String,
+    },
+
+    /// 验证超时
+    #[error("验证超时 ({seconds}秒)")]
+    ValidationTimeout {
+        seconds: u64,
+    },
+
+    /// 验证被前端取消
+    #[error("验证已取消")]
+    ValidationCancelled,
+
     /// Redis连接失败
     #[error("Redis连接失败: {message}")]
     RedisConnectionFailed {
@@ -73,6 +126,12 @@ pub enum SaveCookiesError {
         message: String,
     },
 
+    /// 账户状态不允许此操作
+    #[error("账户状态不允许此操作: {message}")]
+    InvalidState {
+        message: String,
+    },
+
     /// UID不匹配
     #[error("UID不匹配: 期望 {expected}, 实际 {actual}")]
     UidMismatch {
@@ -88,18 +147,51 @@ impl From<ValidationError> for SaveCookiesError {
             ValidationError::ProfileApiFailed { status, message } => {
                 SaveCookiesError::ProfileApiFailed { status, message }
             }
+            ValidationError::Rejected { outcome, message } => match outcome {
+                ValidationOutcome::Expired => SaveCookiesError::CookiesExpired { message },
+                ValidationOutcome::Banned => SaveCookiesError::AccountBanned { message },
+                ValidationOutcome::Locked => SaveCookiesError::AccountLocked { message },
+                ValidationOutcome::CaptchaRequired => SaveCookiesError::CaptchaRequired { message },
+                ValidationOutcome::RateLimited => SaveCookiesError::RateLimited {
+                    retry_after: None,
+                    message,
+                },
+                ValidationOutcome::NetworkError => {
+                    SaveCookiesError::ValidationRequestFailed { message }
+                }
+                ValidationOutcome::ValidatorError => SaveCookiesError::PlaywrightFailed { message },
+                ValidationOutcome::Valid
+                | ValidationOutcome::Timeout
+                | ValidationOutcome::Unknown => {
+                    SaveCookiesError::ProfileApiFailed { status: 401, message }
+                }
+            },
             ValidationError::MissingCookie(cookie_name) => {
                 SaveCookiesError::MissingCookie { cookie_name }
             }
             ValidationError::PlaywrightFailed(message) => {
                 SaveCookiesError::PlaywrightFailed { message }
             }
+            err @ ValidationError::WorkerCrashed(_) => SaveCookiesError::PlaywrightFailed {
+                message: err.to_string(),
+            },
             ValidationError::InvalidFormat(message) => {
                 SaveCookiesError::InvalidFormat { message }
             }
             ValidationError::UidExtractionFailed(message) => {
                 SaveCookiesError::UidExtractionFailed { message }
             }
+            ValidationError::RequestFailed(message) => {
+                SaveCookiesError::ValidationRequestFailed { message }
+            }
+            ValidationError::Timeout { seconds } => SaveCookiesError::ValidationTimeout { seconds },
+            ValidationError::Cancelled => SaveCookiesError::ValidationCancelled,
+            err @ ValidationError::RateLimitExceeded { retry_after } => {
+                SaveCookiesError::RateLimited {
+                    retry_after,
+                    message: err.to_string(),
+                }
+            }
         }
     }
 }
@@ -121,6 +213,9 @@ impl From<StorageError> for SaveCookiesError {
             StorageError::CommandFailed(message) => {
                 SaveCookiesError::CommandFailed { message }
             }
+            StorageError::InvalidState(message) => {
+                SaveCookiesError::InvalidState { message }
+            }
         }
     }
 }
@@ -132,23 +227,27 @@ impl From<StorageError> for SaveCookiesError {
 /// - redis_key: 存储位置,可用于调试
 /// - validation_duration_ms: 性能指标,优化依据
 /// - is_overwrite: 行为说明,UI展示差异
+/// - winner: 并发写入时哪一方胜出 (existing 表示Redis中已有更新的登录)
 #[derive(Debug, Serialize)]
 pub struct SaveCookiesResponse {
     pub success: bool,
     pub redis_key: String,
     pub validation_duration_ms: u64,
     pub is_overwrite: bool,
+    pub winner: SaveWinner,
 }
 
 /// 保存Cookies命令
 ///
 /// 契约定义: specs/001-cookies/contracts/save_cookies.md:31
 /// 参数扁平化,直接接收 uid, cookies, screen_name。
+/// 可选的 validation_id 由前端生成,验证期间可用 `cancel_validation` 取消。
 ///
 /// 完整的验证-保存流程:
-/// 1. 验证cookies有效性 (Playwright调用微博API)
+/// 1. 验证cookies有效性 (调用微博API,超过时限返回 ValidationTimeout)
 /// 2. 确保UID匹配 (安全检查)
 /// 3. 保存到Redis (持久化)
+/// 4. 记录审计日志 (无论成功失败);微博拒绝了cookies时,同时把结果记录到已保存的账户
 ///
 /// 返回:
 /// - 成功: SaveCookiesResponse
@@ -158,54 +257,170 @@ pub async fn save_cookies(
     uid: String,
     cookies: HashMap<String, String>,
     screen_name: Option<String>,
+    validation_id: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<SaveCookiesResponse, SaveCookiesError> {
     tracing::info!(
         用户ID = %uid,
         Cookies数量 = %cookies.len(),
+        验证ID = ?validation_id,
         "调用save_cookies命令"
     );
 
     let start = std::time::Instant::now();
 
-    // 验证cookies
-    let (validated_uid, validated_screen_name) =
-        state.validator.validate_cookies(&cookies).await?;
+    let result = save_validated_cookies(
+        &uid,
+        cookies,
+        screen_name,
+        validation_id.as_deref(),
+        &state,
+    )
+    .await;
+
+    state
+        .audit
+        .record(
+            AuditEvent::new(AuditOperation::Save, "save_cookies")
+                .with_uid(&uid)
+                .with_result(&result),
+        )
+        .await;
+
+    let outcome = result?;
+    let validation_duration = start.elapsed();
+
+    tracing::info!(
+        用户ID = %uid,
+        Redis键 = %outcome.redis_key,
+        验证耗时毫秒 = %validation_duration.as_millis(),
+        是否覆盖 = %outcome.is_overwrite,
+        胜出方 = ?outcome.winner,
+        "Cookies保存成功"
+    );
+
+    Ok(SaveCookiesResponse {
+        success: true,
+        redis_key: outcome.redis_key,
+        validation_duration_ms: validation_duration.as_millis() as u64,
+        is_overwrite: outcome.is_overwrite,
+        winner: outcome.winner,
+    })
+}
+
+/// 验证并保存Cookies (save_cookies的主体流程)
+async fn save_validated_cookies(
+    uid: &str,
+    cookies: HashMap<String, String>,
+    screen_name: Option<String>,
+    validation_id: Option<&str>,
+    state: &AppState,
+) -> Result<SaveOutcome, SaveCookiesError> {
+    // 验证cookies,提供了validation_id时可被前端取消
+    let validation = state.validator.validate_cookies(&cookies);
+    let validated = match validation_id {
+        Some(id) => state.validations.run(id, validation).await,
+        None => validation.await,
+    };
+    let (validated_uid, validated_screen_name) = match validated {
+        Ok(validated) => validated,
+        Err(e) => {
+            record_rejection(uid, &e, state).await;
+            return Err(e.into());
+        }
+    };
 
     // 确保UID匹配 - 安全性的基石
     if validated_uid != uid {
         return Err(SaveCookiesError::UidMismatch {
-            expected: uid,
+            expected: uid.to_string(),
             actual: validated_uid,
         });
     }
 
     // 创建CookiesData
-    let mut cookies_data = CookiesData::new(validated_uid, cookies);
+    let mut cookies_data =
+        CookiesData::new(validated_uid, cookies).with_namespace(state.redis.namespace());
     cookies_data = cookies_data.with_screen_name(screen_name.unwrap_or(validated_screen_name));
 
     // 验证CookiesData结构
     cookies_data.validate()?;
 
     // 保存到Redis
-    let is_overwrite = state.redis.save_cookies(&cookies_data).await?;
+    // 比较并设置: 较旧的登录不会覆盖较新的数据
+    let outcome = state
+        .redis
+        .save_cookies(
+            &cookies_data,
+            SnapshotSource::Manual,
+            SnapshotValidation::Verified,
+            SaveMode::IfNewer,
+        )
+        .await?;
 
-    let validation_duration = start.elapsed();
+    Ok(outcome)
+}
 
-    tracing::info!(
-        用户ID = %cookies_data.uid,
-        Redis键 = %cookies_data.redis_key,
-        验证耗时毫秒 = %validation_duration.as_millis(),
-        是否覆盖 = %is_overwrite,
-        "Cookies保存成功"
-    );
+/// 记录被微博拒绝的验证结果
+///
+/// 只记录有明确结论的拒绝 (过期、封禁等);网络错误、超时、取消等说明不了账户状态,不记录。
+/// 账户尚未保存过时没有可记录的对象,直接忽略。
+async fn record_rejection(uid: &str, error: &ValidationError, state: &AppState) {
+    let ValidationError::Rejected { outcome, .. } = error else {
+        return;
+    };
+    if *outcome == ValidationOutcome::Valid || !outcome.is_conclusive() {
+        return;
+    }
 
-    Ok(SaveCookiesResponse {
-        success: true,
-        redis_key: cookies_data.redis_key,
-        validation_duration_ms: validation_duration.as_millis() as u64,
-        is_overwrite,
-    })
+    match state
+        .redis
+        .record_validation(uid, *outcome, chrono::Utc::now())
+        .await
+    {
+        Ok(()) => {}
+        Err(StorageError::NotFound(_)) => {
+            tracing::debug!(用户ID = %uid, "账户尚未保存,不记录验证结果");
+        }
+        Err(e) => tracing::warn!(用户ID = %uid, 错误 = %e, "验证结果记录失败"),
+    }
+}
+
+/// 取消验证命令
+///
+/// 取消 `save_cookies` 中带有该 validation_id 的验证,
+/// 对应的 `save_cookies` 返回 ValidationCancelled;
+/// 也可取消进行中的批量验证 (`validate_accounts`),其返回已完成部分的汇总。
+///
+/// 返回验证是否仍在进行 (已结束或ID不存在时为false)。
+#[tauri::command]
+pub async fn cancel_validation(
+    validation_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let cancelled = state.validations.cancel(&validation_id);
+    tracing::info!(验证ID = %validation_id, 已取消 = %cancelled, "调用cancel_validation命令");
+    Ok(cancelled)
+}
+
+/// 刷新Cookies命令
+///
+/// 带着已保存的cookies访问微博,收下服务端轮换的cookies,
+/// 有变化时保存为新版本 (来源 `refresh`),账户无需重新扫码即可保持活跃。
+///
+/// 返回轮换与删除的cookie名称以及新版本号;会话已失效时返回错误并记录验证结果。
+#[tauri::command]
+pub async fn refresh_cookies(
+    uid: String,
+    state: State<'_, AppState>,
+) -> Result<RefreshReport, String> {
+    tracing::info!(用户ID = %uid, "调用refresh_cookies命令");
+
+    state
+        .refresh
+        .refresh(&uid)
+        .await
+        .map_err(|e| format!("Refresh failed: {}", e))
 }
 
 /// 查询Cookies命令
@@ -224,17 +439,25 @@ pub async fn query_cookies(
 ) -> Result<CookiesData, String> {
     tracing::debug!(用户ID = %uid, "调用query_cookies命令");
 
+    let result = state.redis.query_cookies(&uid).await;
+
     state
-        .redis
-        .query_cookies(&uid)
-        .await
-        .map_err(|e| format!("Query failed: {}", e))
+        .audit
+        .record(
+            AuditEvent::new(AuditOperation::Query, "query_cookies")
+                .with_uid(&uid)
+                .with_result(&result),
+        )
+        .await;
+
+    result.map_err(|e| format!("Query failed: {}", e))
 }
 
 /// 删除Cookies命令
 ///
 /// 用户登出或cookies过期时调用。
-/// 彻底清除Redis中的数据,不留痕迹。
+/// 账户移入回收站,保留期内可通过 `restore_account` 恢复,
+/// 到期后自动清除;需要立即清除时使用 `purge_account`。
 ///
 /// 幂等性保证: 删除不存在的UID不会报错,
 /// 因为结果一致 - "该UID的cookies不存在"。
@@ -245,17 +468,25 @@ pub async fn delete_cookies(
 ) -> Result<(), String> {
     tracing::info!(用户ID = %uid, "调用delete_cookies命令");
 
-    state
-        .redis
-        .delete_cookies(&uid)
-        .await
+    let result = state.redis.trash_account(&uid).await;
+
+    let mut event = AuditEvent::new(AuditOperation::Delete, "delete_cookies")
+        .with_uid(&uid)
+        .with_result(&result);
+    if let Ok(Some(purge_at)) = &result {
+        event = event.with_detail(format!("移入回收站,清除期限 {}", purge_at.to_rfc3339()));
+    }
+    state.audit.record(event).await;
+
+    result
+        .map(|_| ())
         .map_err(|e| format!("Delete failed: {}", e))
 }
 
 /// 列出所有已保存的UIDs
 ///
 /// 用于前端展示账号列表,支持多账号管理。
-/// 返回所有存储的UID,前端可据此:
+/// 返回所有未删除的UID (回收站中的账户见 `list_accounts`),前端可据此:
 /// - 显示账号选择界面
 /// - 批量查询每个UID的详细信息
 /// - 统计已登录账号数量
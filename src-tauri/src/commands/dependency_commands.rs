@@ -11,6 +11,9 @@ use crate::models::errors::DependencyError;
 use crate::services::{ConfigService, DependencyChecker, InstallerService};
 use tracing::{info, error, warn};
 
+/// Redis版本要求,连接测试的健康报告使用同一个值
+pub const REDIS_VERSION_REQUIREMENT: &str = ">=7.0.0";
+
 /// 获取预定义的依赖项列表
 fn get_predefined_dependencies() -> Vec<Dependency> {
     // 从 .env 读取 Redis 配置,用于环境检测
@@ -62,7 +65,7 @@ fn get_predefined_dependencies() -> Vec<Dependency> {
         Dependency::new(
             "redis".to_string(),
             "Redis".to_string(),
-            ">=7.0.0".to_string(),
+            REDIS_VERSION_REQUIREMENT.to_string(),
             "内存数据库,用于存储会话和缓存".to_string(),
             DependencyLevel::Required,
             false,
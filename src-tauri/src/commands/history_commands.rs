@@ -0,0 +1,103 @@
+//! Cookies历史版本命令
+//!
+//! 每次保存都会产生一个快照,这里提供查看、比较和回滚能力。
+//! 返回给前端的数据只包含cookie名称,cookie值始终留在后端。
+
+use crate::models::{AuditEvent, AuditOperation, CookiesDiff, CookiesSnapshotSummary};
+use crate::state::AppState;
+use tauri::State;
+
+/// 列出某个UID的历史快照
+///
+/// 按版本号降序返回 (最新在前),每项只包含cookie名称。
+#[tauri::command]
+pub async fn list_cookies_history(
+    uid: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CookiesSnapshotSummary>, String> {
+    tracing::debug!(用户ID = %uid, "调用list_cookies_history命令");
+
+    let history = state
+        .redis
+        .list_history(&uid)
+        .await
+        .map_err(|e| format!("List history failed: {}", e))?;
+
+    Ok(history.iter().map(|snapshot| snapshot.summary()).collect())
+}
+
+/// 比较两个历史版本
+///
+/// 按cookie名称给出新增/移除/变化/未变化四类,不返回任何cookie值。
+#[tauri::command]
+pub async fn diff_cookies_versions(
+    uid: String,
+    from_version: u64,
+    to_version: u64,
+    state: State<'_, AppState>,
+) -> Result<CookiesDiff, String> {
+    tracing::debug!(
+        用户ID = %uid,
+        起始版本 = %from_version,
+        目标版本 = %to_version,
+        "调用diff_cookies_versions命令"
+    );
+
+    let from = state
+        .redis
+        .get_snapshot(&uid, from_version)
+        .await
+        .map_err(|e| format!("Diff failed: {}", e))?;
+    let to = state
+        .redis
+        .get_snapshot(&uid, to_version)
+        .await
+        .map_err(|e| format!("Diff failed: {}", e))?;
+
+    Ok(CookiesDiff::between(
+        from.version,
+        &from.cookies,
+        to.version,
+        &to.cookies,
+    ))
+}
+
+/// 回滚到指定历史版本
+///
+/// 回滚会生成一个新版本 (来源为 rollback),原有历史保持不变。
+///
+/// # 返回
+/// 回滚后的最新快照摘要
+#[tauri::command]
+pub async fn rollback_cookies(
+    uid: String,
+    version: u64,
+    state: State<'_, AppState>,
+) -> Result<CookiesSnapshotSummary, String> {
+    tracing::info!(用户ID = %uid, 目标版本 = %version, "调用rollback_cookies命令");
+
+    let result = state.redis.rollback_to(&uid, version).await;
+
+    state
+        .audit
+        .record(
+            AuditEvent::new(AuditOperation::Rollback, "rollback_cookies")
+                .with_uid(&uid)
+                .with_detail(format!("目标版本 v{}", version))
+                .with_result(&result),
+        )
+        .await;
+
+    result.map_err(|e| format!("Rollback failed: {}", e))?;
+
+    let history = state
+        .redis
+        .list_history(&uid)
+        .await
+        .map_err(|e| format!("Rollback failed: {}", e))?;
+
+    history
+        .first()
+        .map(|snapshot| snapshot.summary())
+        .ok_or_else(|| format!("Rollback failed: history of {} is empty", uid))
+}
@@ -0,0 +1,92 @@
+//! 账户租约命令
+//!
+//! 下游worker通过租约独占使用账户,前端可查看当前持有者并强制释放。
+
+use crate::models::{LeaseHolder, LeaseStrategy, LeasedAccount};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tauri::State;
+
+/// 获取一个无人持有的账户
+///
+/// # 参数
+/// - `tag`: 只在拥有该标签的账户中选择
+/// - `ttl_seconds`: 租约时长,到期未续租自动释放
+/// - `strategy`: `round_robin` 或 `least_recently_used` (缺省)
+/// - `holder`: 持有者标识
+///
+/// # 返回
+/// 租约 (含释放凭证) 与账户Cookies
+#[tauri::command]
+pub async fn acquire_account_lease(
+    tag: Option<String>,
+    ttl_seconds: u64,
+    strategy: Option<LeaseStrategy>,
+    holder: String,
+    state: State<'_, AppState>,
+) -> Result<LeasedAccount, String> {
+    let strategy = strategy.unwrap_or_default();
+    tracing::info!(标签 = ?tag, 租约时长 = %ttl_seconds, 策略 = ?strategy, 持有者 = %holder, "调用acquire_account_lease命令");
+
+    state
+        .leases
+        .acquire(
+            tag.as_deref(),
+            Duration::from_secs(ttl_seconds),
+            strategy,
+            &holder,
+        )
+        .await
+        .map_err(|e| format!("Acquire lease failed: {}", e))
+}
+
+/// 续租
+///
+/// # 返回
+/// 新的到期时间
+#[tauri::command]
+pub async fn renew_account_lease(
+    uid: String,
+    token: String,
+    ttl_seconds: u64,
+    state: State<'_, AppState>,
+) -> Result<DateTime<Utc>, String> {
+    tracing::debug!(用户ID = %uid, 租约时长 = %ttl_seconds, "调用renew_account_lease命令");
+
+    state
+        .leases
+        .renew(&uid, &token, Duration::from_secs(ttl_seconds))
+        .await
+        .map_err(|e| format!("Renew lease failed: {}", e))
+}
+
+/// 释放租约
+///
+/// 不提供 `token` 时强制释放 (管理界面使用)。
+#[tauri::command]
+pub async fn release_account_lease(
+    uid: String,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    tracing::info!(用户ID = %uid, 强制 = %token.is_none(), "调用release_account_lease命令");
+
+    let result = match token {
+        Some(token) => state.leases.release(&uid, &token).await,
+        None => state.leases.force_release(&uid, "release_account_lease").await,
+    };
+    result.map_err(|e| format!("Release lease failed: {}", e))
+}
+
+/// 列出当前租约持有者 (不含凭证)
+#[tauri::command]
+pub async fn list_account_leases(state: State<'_, AppState>) -> Result<Vec<LeaseHolder>, String> {
+    tracing::debug!("调用list_account_leases命令");
+
+    state
+        .leases
+        .list_holders()
+        .await
+        .map_err(|e| format!("List leases failed: {}", e))
+}
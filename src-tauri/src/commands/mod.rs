@@ -3,13 +3,29 @@
 /// 包含所有前端可调用的命令:
 /// - qrcode_commands: 二维码生成和轮询
 /// - cookies_commands: Cookies保存/查询/删除
+/// - account_commands: 账户状态与回收站
+/// - history_commands: Cookies历史版本查看/比较/回滚
+/// - tag_commands: 账户标签与分组
+/// - transfer_commands: Cookies导入导出
+/// - lease_commands: 下游worker的账户租约
+/// - audit_commands: 审计日志查询
+/// - revalidation_commands: 已保存账户的重新验证
+/// - spool_commands: Redis故障期间的本地暂存队列
 /// - dependency_commands: 依赖检测和安装
 /// - playwright_commands: Playwright服务管理
-/// - redis_commands: Redis连接测试
+/// - redis_commands: Redis连接测试、配置与key前缀迁移
 
+pub mod account_commands;
+pub mod audit_commands;
 pub mod cookies_commands;
 pub mod dependency_commands;
+pub mod history_commands;
+pub mod lease_commands;
 pub mod log_commands;
 pub mod playwright_commands;
 pub mod qrcode_commands;
 pub mod redis_commands;
+pub mod revalidation_commands;
+pub mod spool_commands;
+pub mod tag_commands;
+pub mod transfer_commands;
@@ -1,5 +1,6 @@
-use crate::models::{ApiError, QrCodeStatus, CookiesData, parse_qr_status};
-use crate::models::events::{LoginErrorEvent, LoginStatusEvent};
+use crate::models::{ApiError, AuditEvent, AuditOperation, QrCodeStatus, CookiesData, SnapshotSource, SnapshotValidation, SpoolError, parse_qr_status};
+use crate::models::events::{LoginErrorEvent, LoginStatusEvent, LoginValidationMismatchEvent};
+use crate::services::{CookieValidator, LoginCheck, LoginValidationPolicy, SaveMode, SaveWinner};
 use crate::state::AppState;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -64,6 +65,10 @@ pub async fn generate_qrcode(
 
     // 克隆services用于后台任务 (Arc已在内部,无需重复包装)
     let redis = state.redis.clone();
+    let audit = state.audit.clone();
+    let spool = state.spool.clone();
+    let validator = state.validator.clone();
+    let policy = state.login_policy;
     let session_manager = state.session_manager.clone();
 
     // 克隆qr_id用于后续操作
@@ -72,7 +77,7 @@ pub async fn generate_qrcode(
 
     // 启动后台监控任务 (可取消)
     let monitor_task = tokio::spawn(async move {
-        monitor_login(qr_id_for_task, ws_stream, app, redis).await;
+        monitor_login(qr_id_for_task, ws_stream, app, redis, audit, spool, validator, policy).await;
     });
 
     // 注册到会话管理器 (自动取消旧任务)
@@ -93,12 +98,22 @@ pub async fn generate_qrcode(
 /// 支持任务取消 - 当SessionManager取消旧会话时,此任务会自动终止
 /// 支持断线重连 - WebSocket断开时自动重连,最多重试5次
 ///
-/// 注: WebSocket服务已通过VIP API验证UID,无需二次验证
+/// 保存Redis失败时Cookies转入本地暂存队列,仍向前端推送confirmed事件
+///
+/// 保存前是否验证由 `QR_LOGIN_VALIDATION` 决定:
+/// - `trust`: WebSocket服务已通过VIP API验证UID,直接保存 (默认)
+/// - `sync`: 保存前验证,Cookies被拒绝或UID不符时不保存并推送login_error
+/// - `async`: 立即保存为 `pending`,后台验证后写回结论
+#[allow(clippy::too_many_arguments)]
 async fn monitor_login(
     qr_id: String,
     mut ws_stream: crate::services::weibo_api::WsStream,
     app: AppHandle,
     redis: Arc<crate::services::RedisService>,
+    audit: Arc<crate::services::AuditLog>,
+    spool: Option<Arc<crate::services::SpoolService>>,
+    validator: Arc<dyn CookieValidator>,
+    policy: LoginValidationPolicy,
 ) {
     use crate::services::weibo_api::WsEvent;
     use tokio_tungstenite::tungstenite::Message;
@@ -160,22 +175,97 @@ async fn monitor_login(
                         QrCodeStatus::Confirmed => {
                             tracing::debug!(二维码ID = %qr_id, "处理Confirmed状态");
                             if let (Some(uid), Some(cookies), Some(screen_name)) = (uid_opt, cookies_opt, screen_name_opt) {
-                                // WebSocket已经通过VIP API验证,直接使用返回的UID
-                                // 不需要二次验证 - VIP API是唯一可信的数据源
                                 let cookies_data = CookiesData::new(uid.clone(), cookies)
-                                    .with_screen_name(screen_name);
-
-                                if let Err(e) = redis.save_cookies(&cookies_data).await {
-                                    tracing::error!(二维码ID = %qr_id, 错误 = ?e, "保存cookies失败");
-                                    emit_error(&app, &qr_id, "StorageError", format!("保存Cookies失败: {}", e));
-                                    should_exit = true;
-                                    break;
+                                    .with_screen_name(screen_name)
+                                    .with_namespace(redis.namespace());
+                                let audit_event = AuditEvent::new(AuditOperation::Save, "monitor_login").with_uid(&uid);
+
+                                // WebSocket服务已通过VIP API确认UID,默认直接信任;按策略可在保存前或保存后再验证
+                                let validation = match policy {
+                                    LoginValidationPolicy::Trust => SnapshotValidation::TrustedServer,
+                                    LoginValidationPolicy::Async => SnapshotValidation::Pending,
+                                    LoginValidationPolicy::Sync => {
+                                        match LoginCheck::run(validator.as_ref(), &uid, &cookies_data.cookies).await {
+                                            LoginCheck::Mismatch { message, .. } => {
+                                                tracing::warn!(二维码ID = %qr_id, uid = %uid, 原因 = %message, "扫码登录的Cookies验证未通过,未保存");
+                                                audit.record(audit_event.failed(format!("验证未通过,未保存: {}", message))).await;
+                                                emit_error(&app, &qr_id, "ValidationFailed", format!("Cookies验证未通过: {}", message));
+                                                should_exit = true;
+                                                break;
+                                            }
+                                            check => {
+                                                // 无法判断 (网络错误、超时) 不丢弃扫码结果,按未验证保存
+                                                if let LoginCheck::Inconclusive { message } = &check {
+                                                    tracing::warn!(二维码ID = %qr_id, uid = %uid, 原因 = %message, "无法验证扫码登录的Cookies,按未验证保存");
+                                                }
+                                                check.validation()
+                                            }
+                                        }
+                                    }
+                                };
+
+                                // 比较并设置: 同一UID的并发确认中,较旧的登录不会覆盖较新的
+                                let saved = redis
+                                    .save_cookies(&cookies_data, SnapshotSource::QrLogin, validation, SaveMode::IfNewer)
+                                    .await;
+                                // 推送给前端的是Redis中实际生效的Cookies
+                                let mut confirmed_cookies = Some(cookies_data.clone());
+                                match saved {
+                                    Ok(outcome) if outcome.winner == SaveWinner::Existing => {
+                                        tracing::warn!(二维码ID = %qr_id, uid = %uid, "Redis中已有更新的Cookies,本次登录未覆盖");
+                                        audit.record(audit_event.with_detail("Redis中已有更新的Cookies,未覆盖")).await;
+                                        confirmed_cookies = match redis.query_cookies(&uid).await {
+                                            Ok(stored) => Some(stored),
+                                            Err(e) => {
+                                                tracing::warn!(二维码ID = %qr_id, uid = %uid, 错误 = %e, "读取已保存的Cookies失败");
+                                                None
+                                            }
+                                        };
+                                    }
+                                    Ok(_) => {
+                                        tracing::info!(二维码ID = %qr_id, uid = %uid, 验证方式 = %validation.as_str(), "Cookies已保存");
+                                        audit.record(audit_event.with_detail(format!("验证方式: {}", validation.as_str()))).await;
+                                        if validation == SnapshotValidation::Pending {
+                                            tokio::spawn(validate_after_save(
+                                                qr_id.clone(),
+                                                cookies_data.clone(),
+                                                app.clone(),
+                                                redis.clone(),
+                                                audit.clone(),
+                                                validator.clone(),
+                                            ));
+                                        }
+                                    }
+                                    Err(e) => {
+                                        tracing::error!(二维码ID = %qr_id, 错误 = ?e, "保存cookies失败");
+                                        // 扫码结果来之不易: 先暂存到本地,Redis恢复后自动写回
+                                        // 暂存期间不做后台验证,写回时按未验证保存
+                                        let spooled_validation = match validation {
+                                            SnapshotValidation::Pending => SnapshotValidation::Unverified,
+                                            validation => validation,
+                                        };
+                                        let spooled = match &spool {
+                                            Some(spool) => spool.enqueue(cookies_data.clone(), SnapshotSource::QrLogin, spooled_validation, &e.to_string()),
+                                            None => Err(SpoolError::Unavailable),
+                                        };
+                                        match spooled {
+                                            Ok(entry) => {
+                                                audit.record(audit_event.failed(format!("{}; 已暂存到本地队列", e))).await;
+                                                let _ = app.emit_all("spool_updated", entry);
+                                            }
+                                            Err(spool_error) => {
+                                                tracing::error!(二维码ID = %qr_id, 错误 = %spool_error, "暂存cookies失败");
+                                                audit.record(audit_event.failed(&e)).await;
+                                                emit_error(&app, &qr_id, "StorageError", format!("保存Cookies失败: {}", e));
+                                                should_exit = true;
+                                                break;
+                                            }
+                                        }
+                                    }
                                 }
 
-                                tracing::info!(二维码ID = %qr_id, uid = %uid, "Cookies已保存");
-
                                 // 推送confirmed事件
-                                let event = LoginStatusEvent::new(qr_id.clone(), QrCodeStatus::Confirmed, Some(cookies_data));
+                                let event = LoginStatusEvent::new(qr_id.clone(), QrCodeStatus::Confirmed, confirmed_cookies);
                                 let _ = app.emit_all("login_status_update", event);
                                 tracing::debug!(二维码ID = %qr_id, "Confirmed事件已发送至前端");
                             }
@@ -274,6 +364,57 @@ async fn monitor_login(
     tracing::info!(二维码ID = %qr_id, "登录监控已停止");
 }
 
+/// 保存后验证 (异步验证策略的后台任务)
+///
+/// 结论写回账户的验证方式;Cookies被拒绝或属于另一个账户时
+/// 记录审计并推送 `login_validation_mismatch` 事件。
+/// 期间账户已被新的登录替换时,结论不再写入。
+async fn validate_after_save(
+    qr_id: String,
+    cookies_data: CookiesData,
+    app: AppHandle,
+    redis: Arc<crate::services::RedisService>,
+    audit: Arc<crate::services::AuditLog>,
+    validator: Arc<dyn CookieValidator>,
+) {
+    let uid = &cookies_data.uid;
+    let check = LoginCheck::run(validator.as_ref(), uid, &cookies_data.cookies).await;
+
+    let applied = match redis
+        .finish_pending_validation(uid, cookies_data.fetched_at, check.validation(), check.outcome(), Utc::now())
+        .await
+    {
+        Ok(applied) => applied,
+        Err(e) => {
+            tracing::error!(二维码ID = %qr_id, uid = %uid, 错误 = %e, "保存后验证结论写入失败");
+            false
+        }
+    };
+    if !applied {
+        tracing::info!(二维码ID = %qr_id, uid = %uid, 验证方式 = %check.validation().as_str(), "账户已被替换或无法写入,保存后验证结论未记录");
+    }
+
+    match check {
+        LoginCheck::Verified => {
+            tracing::info!(二维码ID = %qr_id, uid = %uid, "保存后验证通过");
+        }
+        LoginCheck::Inconclusive { message } => {
+            tracing::warn!(二维码ID = %qr_id, uid = %uid, 原因 = %message, "保存后验证无法判断,账户按未验证记录");
+        }
+        LoginCheck::Mismatch { outcome, message } => {
+            tracing::warn!(二维码ID = %qr_id, uid = %uid, 验证结果 = ?outcome, 原因 = %message, "保存后验证未通过");
+            let audit_event = AuditEvent::new(AuditOperation::Save, "monitor_login")
+                .with_uid(uid)
+                .failed(format!("保存后验证未通过: {}", message));
+            audit.record(audit_event).await;
+            if applied {
+                let event = LoginValidationMismatchEvent::new(qr_id, uid.clone(), outcome, message);
+                let _ = app.emit_all("login_validation_mismatch", event);
+            }
+        }
+    }
+}
+
 /// 重新连接 WebSocket
 ///
 /// 使用与 `generate_qrcode` 相同的连接逻辑
@@ -1,7 +1,11 @@
+use crate::commands::dependency_commands::REDIS_VERSION_REQUIREMENT;
 use crate::models::redis_config::{RedisConfig, RedisConfigError};
+use crate::models::{HealthSeverity, KeyNamespace, RedisHealthReport};
+use crate::services::redis_service::KeyMigrationReport;
 use crate::services::ConfigService;
+use crate::state::AppState;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use tauri::State;
 use thiserror::Error;
 
 /// Redis连接测试错误
@@ -17,6 +21,12 @@ pub enum RedisTestError {
     #[error("认证失败: {message}")]
     AuthenticationFailed { message: String },
 
+    #[error("权限不足: {message}")]
+    PermissionDenied { message: String },
+
+    #[error("TLS握手失败: {message}")]
+    TlsFailed { message: String },
+
     #[error("PING命令执行失败: {message}")]
     PingFailed { message: String },
 
@@ -35,6 +45,7 @@ pub enum RedisTestError {
 /// - latency_ms: 性能基准,评估网络和Redis响应速度
 /// - message: 人类可读的结果描述
 /// - error: 失败时的具体错误信息
+/// - health: 版本、内存、持久化与账户数量,以及可能导致账户丢失的配置
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RedisConnectionTestResult {
     /// 测试是否成功
@@ -51,6 +62,10 @@ pub struct RedisConnectionTestResult {
 
     /// 错误详情 (仅在失败时)
     pub error: Option<String>,
+
+    /// 健康报告 (仅在成功时)
+    #[serde(default)]
+    pub health: Option<RedisHealthReport>,
 }
 
 impl RedisConnectionTestResult {
@@ -61,7 +76,30 @@ impl RedisConnectionTestResult {
             latency_ms: Some(latency_ms),
             message: format!("连接成功 (延迟: {}ms)", latency_ms),
             error: None,
+            health: None,
+        }
+    }
+
+    /// 附加健康报告 (构建器模式)
+    ///
+    /// 存在严重问题时写入结果描述,避免只看到"连接成功"而忽略风险。
+    fn with_health(mut self, health: RedisHealthReport) -> Self {
+        let critical: Vec<&str> = health
+            .warnings
+            .iter()
+            .filter(|warning| warning.severity == HealthSeverity::Critical)
+            .map(|warning| warning.message.as_str())
+            .collect();
+        if !critical.is_empty() {
+            self.message = format!(
+                "{},但存在{}个严重问题: {}",
+                self.message,
+                critical.len(),
+                critical.join("; ")
+            );
         }
+        self.health = Some(health);
+        self
     }
 
     /// 创建失败结果
@@ -71,6 +109,7 @@ impl RedisConnectionTestResult {
             latency_ms: None,
             message: "连接测试失败".to_string(),
             error: Some(error.to_string()),
+            health: None,
         }
     }
 }
@@ -78,18 +117,26 @@ impl RedisConnectionTestResult {
 /// 测试Redis连接
 ///
 /// # 功能
-/// 1. 建立连接: 根据提供的配置创建Redis客户端
+/// 1. 建立连接: 根据提供的配置创建Redis客户端 (单机、哨兵或集群)
 /// 2. 执行PING: 发送PING命令验证连接可用性
 /// 3. 测量延迟: 记录从连接到响应的总耗时
+/// 4. 健康报告: 读取 `INFO` 与 `CONFIG GET save`,统计账户key数量,
+///    检查版本要求 (与依赖检测相同)、持久化与内存淘汰策略
 ///
 /// # 验证层次
 /// - 网络连通性: 能否到达Redis服务器
-/// - 认证正确性: 密码是否正确 (如配置了密码)
+/// - TLS: 证书能否读取、服务端证书能否通过校验 (如启用了TLS)
+/// - 认证正确性: 用户名与密码是否正确 (如配置了密码)
+/// - ACL权限: 用户是否有权执行PING
 /// - 服务可用性: Redis是否正常响应命令
 ///
 /// # 超时保护
 /// - 总超时: 5秒 (防止长时间阻塞UI)
-/// - 包含: 连接建立 + 认证 + PING执行
+/// - 包含: 连接建立 + 认证 + PING执行 + 健康信息收集
+///
+/// # 健康警告
+/// 持久化关闭 (重启丢失全部账户) 或淘汰策略可能删除账户时,
+/// 以error级别记录日志并写入结果描述,连接测试本身仍视为成功。
 ///
 /// # 使用场景
 /// - 配置验证: 用户填写配置后立即测试
@@ -111,24 +158,19 @@ pub async fn test_redis_connection(
         "开始Redis连接测试"
     );
 
-    let start = Instant::now();
-
-    // 建立连接
-    let client = redis::Client::open(config.to_connection_url()).map_err(|e| {
-        tracing::error!(error = %e, "创建Redis客户端失败");
-        RedisTestError::InvalidConfig {
-            message: e.to_string(),
-        }
+    // 部署方式参数不完整时不必发起连接
+    config.validate().map_err(|e| RedisTestError::InvalidConfig {
+        message: e.to_string(),
     })?;
 
-    // 获取异步连接
-    let mut conn = match tokio::time::timeout(
+    // 建立连接并执行PING (哨兵模式先向哨兵解析主节点,集群模式先发现节点)
+    let probe = match tokio::time::timeout(
         std::time::Duration::from_secs(5),
-        client.get_multiplexed_async_connection(),
+        crate::services::redis_pool::probe(&config),
     )
     .await
     {
-        Ok(Ok(conn)) => conn,
+        Ok(Ok(probe)) => probe,
         Ok(Err(e)) => {
             tracing::error!(error = %e, "连接Redis失败");
             return Err(classify_connection_error(e));
@@ -139,49 +181,85 @@ pub async fn test_redis_connection(
         }
     };
 
-    // 执行PING命令
-    let pong: String = redis::cmd("PING")
-        .query_async(&mut conn)
-        .await
-        .map_err(|e| {
-            tracing::error!(error = %e, "PING命令失败");
-            RedisTestError::PingFailed {
-                message: e.to_string(),
-            }
-        })?;
-
-    let latency = start.elapsed().as_millis() as u64;
+    let latency = probe.latency.as_millis() as u64;
 
     // 验证响应
-    if pong != "PONG" {
-        tracing::error!(response = %pong, "PING响应异常");
+    if probe.pong != "PONG" {
+        tracing::error!(response = %probe.pong, "PING响应异常");
         return Err(RedisTestError::PingFailed {
-            message: format!("期望 'PONG', 收到 '{}'", pong),
+            message: format!("期望 'PONG', 收到 '{}'", probe.pong),
         });
     }
 
+    let health = RedisHealthReport::from_info(
+        probe.info.as_deref(),
+        probe.save_config.as_deref(),
+        probe.cookies_keys,
+        REDIS_VERSION_REQUIREMENT,
+    );
+    for warning in &health.warnings {
+        match warning.severity {
+            HealthSeverity::Critical => {
+                tracing::error!(警告 = ?warning.code, "Redis存在账户丢失风险: {}", warning.message)
+            }
+            HealthSeverity::Warning => {
+                tracing::warn!(警告 = ?warning.code, "Redis健康检查: {}", warning.message)
+            }
+        }
+    }
+
     tracing::info!(
         latency_ms = %latency,
+        版本 = ?health.server_version,
+        持久化 = ?health.persistence,
+        账户数量 = ?health.cookies_key_count,
+        警告数量 = %health.warnings.len(),
         "Redis连接测试成功"
     );
 
-    Ok(RedisConnectionTestResult::success(latency))
+    Ok(RedisConnectionTestResult::success(latency).with_health(health))
 }
 
 /// 分类连接错误
 ///
 /// 将Redis底层错误映射到业务语义错误。
 /// 帮助用户快速定位问题根因:
-/// - 认证失败 → 检查密码配置
+/// - TLS失败 → 检查证书、CA与服务端是否启用TLS
+/// - 权限不足 → 检查ACL用户的命令与key权限
+/// - 认证失败 → 检查用户名和密码配置
 /// - 连接拒绝 → 检查服务器状态和防火墙
 /// - 超时 → 检查网络连通性
 fn classify_connection_error(err: redis::RedisError) -> RedisTestError {
     let err_msg = err.to_string().to_lowercase();
 
+    // TLS错误: 证书无法读取或校验失败、握手失败、对端未启用TLS (收到明文响应)
+    if ["tls", "certificate", "handshake", "corrupt message", "证书"]
+        .iter()
+        .any(|marker| err_msg.contains(marker))
+    {
+        return RedisTestError::TlsFailed {
+            message: err.to_string(),
+        };
+    }
+
+    // URL或节点地址无法解析
+    if err.kind() == redis::ErrorKind::InvalidClientConfig {
+        return RedisTestError::InvalidConfig {
+            message: err.to_string(),
+        };
+    }
+
+    // ACL权限错误: 认证成功但用户无权执行命令 (需在认证检查之前,错误信息可能提及auth命令)
+    if err_msg.contains("noperm") {
+        return RedisTestError::PermissionDenied {
+            message: "ACL用户无权执行该命令,请检查用户的命令与key权限".to_string(),
+        };
+    }
+
     // 认证错误
     if err_msg.contains("auth") || err_msg.contains("noauth") || err_msg.contains("wrongpass") {
         return RedisTestError::AuthenticationFailed {
-            message: "密码错误或未提供密码".to_string(),
+            message: "用户名或密码错误,或未提供密码".to_string(),
         };
     }
 
@@ -253,8 +331,11 @@ pub async fn save_redis_config(config: RedisConfig) -> Result<(), RedisConfigErr
 /// # 环境变量
 /// - REDIS_HOST: Redis服务器地址 (默认: localhost)
 /// - REDIS_PORT: Redis端口 (默认: 6379)
-/// - REDIS_PASSWORD: 认证密码 (可选)
+/// - REDIS_USERNAME / REDIS_PASSWORD: ACL用户名与认证密码 (可选)
 /// - REDIS_DATABASE: 数据库索引 (可选, 0-15)
+/// - REDIS_TLS 及 REDIS_TLS_CA_CERT 等证书路径、REDIS_UNIX_SOCKET (可选)
+/// - REDIS_MODE: 部署方式 (standalone / sentinel / cluster),
+///   及对应的 REDIS_SENTINEL_MASTER、REDIS_SENTINELS、REDIS_CLUSTER_NODES 等
 ///
 /// # 错误处理
 /// - 文件读取失败时返回 IoError
@@ -284,6 +365,44 @@ pub async fn load_redis_config() -> Result<RedisConfig, RedisConfigError> {
     Ok(config)
 }
 
+/// 迁移Redis key前缀
+///
+/// # 功能
+/// 将 `from_prefix` 命名空间下的所有key复制到 `to_prefix`,保留数据类型与TTL。
+/// 目标key已存在时不覆盖,可重复执行。
+///
+/// # 零停机流程
+/// 1. `delete_source = false`: 复制,旧前缀继续服务
+/// 2. 用 `save_redis_config` 保存新前缀并重启应用
+/// 3. `delete_source = true`: 补齐新写入的key并清理旧前缀
+///
+/// # 错误处理
+/// - 前缀非法或互相嵌套 (如 `weibo` 与 `weibo:staging`) 时拒绝执行
+/// - Redis命令失败时返回错误描述
+#[tauri::command]
+pub async fn migrate_key_prefix(
+    from_prefix: String,
+    to_prefix: String,
+    delete_source: bool,
+    state: State<'_, AppState>,
+) -> Result<KeyMigrationReport, String> {
+    tracing::info!(
+        源前缀 = %from_prefix,
+        目标前缀 = %to_prefix,
+        删除源key = %delete_source,
+        "调用migrate_key_prefix命令"
+    );
+
+    let from = KeyNamespace::new(&from_prefix).map_err(|e| e.to_string())?;
+    let to = KeyNamespace::new(&to_prefix).map_err(|e| e.to_string())?;
+
+    state
+        .redis
+        .migrate_namespace(&from, &to, delete_source)
+        .await
+        .map_err(|e| format!("Migration failed: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,6 +416,22 @@ mod tests {
         assert!(result.error.is_none());
     }
 
+    #[test]
+    fn test_connection_result_surfaces_critical_health_warnings() {
+        let info = "redis_version:7.2.4\r\naof_enabled:0\r\nmaxmemory:0\r\nmaxmemory_policy:noeviction\r\n";
+        let health = RedisHealthReport::from_info(Some(info), Some(""), Some(2), REDIS_VERSION_REQUIREMENT);
+        let result = RedisConnectionTestResult::success(3).with_health(health);
+        assert!(result.success);
+        assert!(result.message.contains("1个严重问题"));
+        assert!(result.message.contains("持久化"));
+        assert_eq!(result.health.unwrap().cookies_key_count, Some(2));
+
+        let info = "redis_version:7.2.4\r\naof_enabled:1\r\nmaxmemory:0\r\nmaxmemory_policy:noeviction\r\n";
+        let health = RedisHealthReport::from_info(Some(info), None, Some(0), REDIS_VERSION_REQUIREMENT);
+        let result = RedisConnectionTestResult::success(3).with_health(health);
+        assert_eq!(result.message, "连接成功 (延迟: 3ms)");
+    }
+
     #[test]
     fn test_connection_result_failure() {
         let error = RedisTestError::ConnectionFailed {
@@ -321,6 +456,71 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_classify_invalid_client_config() {
+        let redis_err = redis::RedisError::from((
+            redis::ErrorKind::InvalidClientConfig,
+            "Redis URL did not parse",
+        ));
+        assert!(matches!(
+            classify_connection_error(redis_err),
+            RedisTestError::InvalidConfig { .. }
+        ));
+    }
+
+    #[test]
+    fn test_classify_acl_permission_error() {
+        let redis_err = redis::RedisError::from((
+            redis::ErrorKind::ResponseError,
+            "An error was signalled by the server",
+            "NOPERM User app has no permissions to run the 'ping' command".to_string(),
+        ));
+        assert!(matches!(
+            classify_connection_error(redis_err),
+            RedisTestError::PermissionDenied { .. }
+        ));
+
+        let wrongpass = redis::RedisError::from((
+            redis::ErrorKind::AuthenticationFailed,
+            "WRONGPASS invalid username-password pair or user is disabled.",
+        ));
+        assert!(matches!(
+            classify_connection_error(wrongpass),
+            RedisTestError::AuthenticationFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_classify_tls_errors() {
+        let untrusted = redis::RedisError::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "invalid peer certificate: UnknownIssuer",
+        ));
+        assert!(matches!(
+            classify_connection_error(untrusted),
+            RedisTestError::TlsFailed { .. }
+        ));
+
+        let missing_ca = redis::RedisError::from((
+            redis::ErrorKind::InvalidClientConfig,
+            "TLS证书文件读取失败",
+            "/etc/redis/ca.pem: No such file or directory".to_string(),
+        ));
+        assert!(matches!(
+            classify_connection_error(missing_ca),
+            RedisTestError::TlsFailed { .. }
+        ));
+
+        let plaintext_server = redis::RedisError::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "received corrupt message of type InvalidContentType",
+        ));
+        assert!(matches!(
+            classify_connection_error(plaintext_server),
+            RedisTestError::TlsFailed { .. }
+        ));
+    }
+
     #[test]
     fn test_classify_connection_refused() {
         let redis_err =
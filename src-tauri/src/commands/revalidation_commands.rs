@@ -0,0 +1,109 @@
+//! 重新验证命令
+//!
+//! 后台按间隔自动重新验证所有账户;前端也可以立即对单个账户、
+//! 某个标签或全部账户发起一轮,进度通过 `revalidation_progress` 事件推送。
+//! 批量验证选中的账户时,进度通过 `validation_progress` 事件推送,可中途取消。
+
+use crate::models::{
+    RevalidationScope, RevalidationStatus, RevalidationSummary, RevalidationTrigger,
+};
+use crate::services::revalidation_service::{
+    emit_finished, emit_progress, emit_validation_progress,
+};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+/// 立即重新验证账户
+///
+/// # 参数
+/// - `uid`: 只验证该账户
+/// - `tag`: 只验证带有该标签的账户
+///
+/// 两者都不指定时验证全部未删除的账户,不能同时指定。
+///
+/// # 返回
+/// 本轮汇总 (同时推送 `revalidation_finished` 事件)
+#[tauri::command]
+pub async fn revalidate_accounts(
+    uid: Option<String>,
+    tag: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RevalidationSummary, String> {
+    tracing::info!(用户ID = ?uid, 标签 = ?tag, "调用revalidate_accounts命令");
+
+    let scope =
+        RevalidationScope::from_args(uid, tag).map_err(|e| format!("Revalidate failed: {}", e))?;
+
+    let summary = state
+        .revalidation
+        .revalidate(scope, RevalidationTrigger::Manual, |progress| {
+            emit_progress(&app, progress)
+        })
+        .await
+        .map_err(|e| format!("Revalidate failed: {}", e))?;
+
+    emit_finished(&app, &summary);
+    Ok(summary)
+}
+
+/// 批量验证账户
+///
+/// 以重新验证的并发上限逐个验证,每个账户开始和完成时推送 `validation_progress` 事件
+/// (完成事件包含验证结果与耗时),确定的结果记录到账户上。
+///
+/// # 参数
+/// - `uids`: 要验证的账户
+/// - `tag`: 验证带有该标签的账户 (与 `uids` 二选一)
+/// - `validation_id`: 前端生成的本轮ID,验证期间可用 `cancel_validation` 取消;
+///   不指定时自动生成 (可从进度事件的 `run_id` 取得)
+///
+/// # 返回
+/// 本轮汇总;被取消时只包含已完成的账户,`cancelled` 为true
+#[tauri::command]
+pub async fn validate_accounts(
+    uids: Option<Vec<String>>,
+    tag: Option<String>,
+    validation_id: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RevalidationSummary, String> {
+    tracing::info!(
+        账户数量 = ?uids.as_ref().map(Vec::len),
+        标签 = ?tag,
+        验证ID = ?validation_id,
+        "调用validate_accounts命令"
+    );
+
+    let scope =
+        RevalidationScope::from_list(uids, tag).map_err(|e| format!("Validate failed: {}", e))?;
+    let run_id = validation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let mut cancellation = state
+        .validations
+        .register(&run_id)
+        .map_err(|e| format!("Validate failed: {}", e))?;
+
+    state
+        .revalidation
+        .validate_batch(
+            run_id.clone(),
+            scope,
+            cancellation.cancelled(),
+            |progress| emit_validation_progress(&app, progress),
+        )
+        .await
+        .map_err(|e| format!("Validate failed: {}", e))
+}
+
+/// 查询重新验证状态
+///
+/// # 返回
+/// 后台间隔与并发配置、进行中的轮数、下次后台验证时间和最近一轮的汇总
+#[tauri::command]
+pub async fn get_revalidation_status(
+    state: State<'_, AppState>,
+) -> Result<RevalidationStatus, String> {
+    tracing::debug!("调用get_revalidation_status命令");
+
+    Ok(state.revalidation.status().await)
+}
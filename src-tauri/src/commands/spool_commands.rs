@@ -0,0 +1,43 @@
+//! 本地暂存队列命令
+//!
+//! Redis不可用期间扫码得到的Cookies暂存在本地,后台自动重试写回。
+//! 前端可以查看待保存的条目,或在Redis恢复后立即重试。
+
+use crate::models::{SpoolError, SpoolFlushReport, SpoolStatus};
+use crate::state::AppState;
+use tauri::State;
+
+/// 查询暂存队列状态
+///
+/// # 返回
+/// 待保存条目摘要 (不含cookies值)、下次自动重试时间和上次清空时间
+#[tauri::command]
+pub async fn get_spool_status(state: State<'_, AppState>) -> Result<SpoolStatus, String> {
+    tracing::debug!("调用get_spool_status命令");
+
+    state
+        .spool
+        .as_ref()
+        .ok_or(SpoolError::Unavailable)
+        .map_err(|e| format!("Get spool status failed: {}", e))?
+        .status()
+        .await
+        .map_err(|e| format!("Get spool status failed: {}", e))
+}
+
+/// 立即重试写回暂存的Cookies
+///
+/// 不等待后台退避间隔。Redis中已有更新登录的条目会被丢弃。
+#[tauri::command]
+pub async fn flush_spool(state: State<'_, AppState>) -> Result<SpoolFlushReport, String> {
+    tracing::info!("调用flush_spool命令");
+
+    state
+        .spool
+        .as_ref()
+        .ok_or(SpoolError::Unavailable)
+        .map_err(|e| format!("Flush spool failed: {}", e))?
+        .flush()
+        .await
+        .map_err(|e| format!("Flush spool failed: {}", e))
+}
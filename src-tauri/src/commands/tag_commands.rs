@@ -0,0 +1,99 @@
+//! 账户标签命令
+//!
+//! 按项目/用途给账户打标签,并按标签筛选账户。
+//! 标签独立于Cookies存储,重新扫码登录不会丢失。
+
+use crate::models::account_tags::normalize_tags;
+use crate::models::{TagCount, TagMatch};
+use crate::state::AppState;
+use tauri::State;
+
+/// 为账户添加标签
+///
+/// # 返回
+/// 账户当前的全部标签
+#[tauri::command]
+pub async fn add_account_tags(
+    uid: String,
+    tags: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    tracing::info!(用户ID = %uid, 标签数量 = %tags.len(), "调用add_account_tags命令");
+
+    let tags = normalize_tags(&tags).map_err(|e| e.to_string())?;
+
+    state
+        .redis
+        .add_tags(&uid, &tags)
+        .await
+        .map_err(|e| format!("Add tags failed: {}", e))
+}
+
+/// 移除账户标签
+///
+/// # 返回
+/// 账户剩余的全部标签
+#[tauri::command]
+pub async fn remove_account_tags(
+    uid: String,
+    tags: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    tracing::info!(用户ID = %uid, 标签数量 = %tags.len(), "调用remove_account_tags命令");
+
+    let tags = normalize_tags(&tags).map_err(|e| e.to_string())?;
+
+    state
+        .redis
+        .remove_tags(&uid, &tags)
+        .await
+        .map_err(|e| format!("Remove tags failed: {}", e))
+}
+
+/// 获取账户的全部标签
+#[tauri::command]
+pub async fn get_account_tags(
+    uid: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    tracing::debug!(用户ID = %uid, "调用get_account_tags命令");
+
+    state
+        .redis
+        .get_tags(&uid)
+        .await
+        .map_err(|e| format!("Get tags failed: {}", e))
+}
+
+/// 按标签列出账户
+///
+/// `mode` 缺省为 `all` (交集);`any` 为并集。
+#[tauri::command]
+pub async fn list_accounts_by_tags(
+    tags: Vec<String>,
+    mode: Option<TagMatch>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let mode = mode.unwrap_or(TagMatch::All);
+    tracing::debug!(标签 = ?tags, 模式 = ?mode, "调用list_accounts_by_tags命令");
+
+    let tags = normalize_tags(&tags).map_err(|e| e.to_string())?;
+
+    state
+        .redis
+        .accounts_by_tags(&tags, mode)
+        .await
+        .map_err(|e| format!("List by tags failed: {}", e))
+}
+
+/// 列出所有标签及其账户数量
+#[tauri::command]
+pub async fn list_tag_counts(state: State<'_, AppState>) -> Result<Vec<TagCount>, String> {
+    tracing::debug!("调用list_tag_counts命令");
+
+    state
+        .redis
+        .tag_counts()
+        .await
+        .map_err(|e| format!("Tag counts failed: {}", e))
+}
@@ -0,0 +1,203 @@
+//! Cookies导入导出命令
+//!
+//! 将账户cookies导出为curl/yt-dlp/Playwright等工具可直接使用的文件,
+//! 或从这些文件及浏览器导出中批量导入。文件路径由前端通过文件对话框选择。
+
+use crate::models::{AuditEvent, AuditOperation, TransferError};
+use crate::services::{ExportReport, ExportScope, ImportReport, ImportStatus};
+use crate::state::AppState;
+use crate::utils::cookie_formats::CookieFormat;
+use std::path::PathBuf;
+use tauri::State;
+
+/// 导出cookies到文件
+///
+/// # 参数
+/// - `scope`: 导出范围 (`uid` / `selection` / `tags` / `all`)
+/// - `format`: `netscape` / `playwright_storage_state` / `cookie_header` / `bundle`
+/// - `path`: 目标文件路径
+///
+/// # 返回
+/// 写入的文件列表与导出/缺失的UID
+#[tauri::command]
+pub async fn export_cookies(
+    scope: ExportScope,
+    format: CookieFormat,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<ExportReport, String> {
+    tracing::info!(导出范围 = ?scope, 导出格式 = ?format, 目标路径 = %path, "调用export_cookies命令");
+
+    let result = state
+        .transfer
+        .export(&scope, format, &PathBuf::from(path))
+        .await;
+    audit_export(&state, "export_cookies", &result).await;
+
+    result.map_err(|e| format!("Export cookies failed: {}", e))
+}
+
+/// 从文件导入cookies
+///
+/// 每个账户分组都会经过格式校验和Playwright验证,只保存验证通过的账户。
+///
+/// # 参数
+/// - `path`: 源文件路径
+/// - `format`: 文件格式,缺省时根据内容推断
+/// - `dry_run`: 为true时只验证不保存,缺省为false
+///
+/// # 返回
+/// 逐个账户的导入/跳过/失败结果
+#[tauri::command]
+pub async fn import_cookies(
+    path: String,
+    format: Option<CookieFormat>,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    tracing::info!(源路径 = %path, 导入格式 = ?format, dry_run = %dry_run, "调用import_cookies命令");
+
+    let result = state
+        .transfer
+        .import_file(&PathBuf::from(path), format, dry_run)
+        .await;
+    audit_import(&state, "import_cookies", &result).await;
+
+    result.map_err(|e| format!("Import cookies failed: {}", e))
+}
+
+/// 从粘贴的文本导入cookies
+///
+/// 适用于直接粘贴的 `Cookie:` 请求头,参数与返回同 `import_cookies`。
+#[tauri::command]
+pub async fn import_cookies_text(
+    content: String,
+    format: Option<CookieFormat>,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    tracing::info!(文本长度 = %content.len(), 导入格式 = ?format, dry_run = %dry_run, "调用import_cookies_text命令");
+
+    let result = state.transfer.import_str(&content, format, dry_run).await;
+    audit_import(&state, "import_cookies_text", &result).await;
+
+    result.map_err(|e| format!("Import cookies failed: {}", e))
+}
+
+/// 导出为口令加密的传输包
+///
+/// 用于把账户交给队友,文件内容在没有口令时不可读,且任何改动都会在导入时被发现。
+///
+/// # 参数
+/// - `scope`: 导出范围
+/// - `path`: 目标文件路径
+/// - `passphrase`: 口令,至少8个字符
+#[tauri::command]
+pub async fn export_encrypted_bundle(
+    scope: ExportScope,
+    path: String,
+    passphrase: String,
+    state: State<'_, AppState>,
+) -> Result<ExportReport, String> {
+    tracing::info!(导出范围 = ?scope, 目标路径 = %path, "调用export_encrypted_bundle命令");
+
+    let result = state
+        .transfer
+        .export_encrypted(&scope, &PathBuf::from(path), &passphrase)
+        .await;
+    audit_export(&state, "export_encrypted_bundle", &result).await;
+
+    result.map_err(|e| format!("Export encrypted bundle failed: {}", e))
+}
+
+/// 导入口令加密的传输包
+///
+/// 解密与完整性校验通过后才会验证和保存账户。
+///
+/// # 参数
+/// - `path`: 源文件路径
+/// - `passphrase`: 导出时设置的口令
+/// - `dry_run`: 为true时只验证不保存,缺省为false
+#[tauri::command]
+pub async fn import_encrypted_bundle(
+    path: String,
+    passphrase: String,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    tracing::info!(源路径 = %path, dry_run = %dry_run, "调用import_encrypted_bundle命令");
+
+    let result = state
+        .transfer
+        .import_encrypted(&PathBuf::from(path), &passphrase, dry_run)
+        .await;
+    audit_import(&state, "import_encrypted_bundle", &result).await;
+
+    result.map_err(|e| format!("Import encrypted bundle failed: {}", e))
+}
+
+/// 记录导出审计: 每个被导出的账户一条,整体失败时记录一条不带UID的失败事件
+async fn audit_export(
+    state: &AppState,
+    caller: &str,
+    result: &Result<ExportReport, TransferError>,
+) {
+    match result {
+        Ok(report) => {
+            let encrypted = if report.encrypted { " (加密)" } else { "" };
+            let detail = format!("{:?}{}", report.format, encrypted);
+            for uid in &report.exported {
+                state
+                    .audit
+                    .record(
+                        AuditEvent::new(AuditOperation::Export, caller)
+                            .with_uid(uid)
+                            .with_detail(detail.clone()),
+                    )
+                    .await;
+            }
+        }
+        Err(e) => {
+            state
+                .audit
+                .record(AuditEvent::new(AuditOperation::Export, caller).failed(e))
+                .await;
+        }
+    }
+}
+
+/// 记录导入审计: dry-run不写入Redis,不记录
+async fn audit_import(
+    state: &AppState,
+    caller: &str,
+    result: &Result<ImportReport, TransferError>,
+) {
+    let report = match result {
+        Ok(report) if report.dry_run => return,
+        Ok(report) => report,
+        Err(e) => {
+            state
+                .audit
+                .record(AuditEvent::new(AuditOperation::Import, caller).failed(e))
+                .await;
+            return;
+        }
+    };
+
+    for entry in &report.entries {
+        let mut event = AuditEvent::new(AuditOperation::Import, caller);
+        if let Some(uid) = &entry.uid {
+            event = event.with_uid(uid);
+        }
+        let reason = entry.reason.clone().unwrap_or_default();
+        let event = match entry.status {
+            ImportStatus::Failed => event.failed(reason),
+            ImportStatus::Skipped => event.with_detail(format!("跳过: {}", reason)),
+            ImportStatus::Imported | ImportStatus::WouldImport => event,
+        };
+        state.audit.record(event).await;
+    }
+}
@@ -7,7 +7,11 @@ mod services;
 mod state;
 mod utils;
 
-use services::ConfigService;
+use services::{
+    ConfigService, RateLimiter, RateLimiterConfig, RefreshService, RevalidationConfig,
+    ValidatorConfig,
+};
+use std::sync::Arc;
 use state::AppState;
 
 fn main() {
@@ -19,7 +23,6 @@ fn main() {
     // 读取 Redis 配置 (从 .env 文件)
     let redis_config = ConfigService::load_redis_config()
         .expect("无法加载 Redis 配置");
-    let redis_url = redis_config.to_connection_url();
 
     tracing::info!(
         redis_config = %redis_config.summary_for_logging(),
@@ -28,25 +31,45 @@ fn main() {
 
     let playwright_server_url = std::env::var("PLAYWRIGHT_SERVER_URL")
         .unwrap_or_else(|_| "ws://localhost:9223".to_string());
-    let playwright_validation_script = std::env::var("PLAYWRIGHT_VALIDATION_SCRIPT")
-        .unwrap_or_else(|_| {
-            "/home/ubuntu/worktrees/desktop/playwright/dist/validate-cookies.js".to_string()
-        });
+    // cookies验证器: 实现选择 (playwright / http)、脚本路径与时限
+    let validator_config = ValidatorConfig::from_env(
+        "/home/ubuntu/worktrees/desktop/playwright/dist/validate-cookies.js",
+    )
+    .expect("无效的验证器配置");
+    // 已保存账户的定期重新验证: 间隔、并发数与随机延迟
+    let revalidation_config = RevalidationConfig::from_env().expect("无效的重新验证配置");
+    // Cookies保活刷新间隔
+    let refresh_interval = RefreshService::interval_from_env().expect("无效的刷新间隔");
+    // 面向微博请求的全局限速: 二维码、验证与刷新各自的令牌桶
+    let rate_limiter = Arc::new(RateLimiter::new(
+        RateLimiterConfig::from_env().expect("无效的限速配置"),
+    ));
 
     tracing::info!(
         playwright_server = %playwright_server_url,
-        validation_script = %playwright_validation_script,
+        validation_script = %validator_config.playwright_script,
         "Playwright server 由外部脚本管理 (scripts/start-playwright-server.sh)"
     );
 
     // 初始化全局状态
     let app_state = AppState::new(
-        &redis_url,
+        &redis_config,
         &playwright_server_url,
-        &playwright_validation_script,
+        &validator_config,
+        &revalidation_config,
+        refresh_interval,
+        rate_limiter,
     )
     .expect("Failed to initialize AppState");
 
+    // 账户变化监听、暂存队列重试、定期重新验证与保活刷新随应用启动,在setup中拿到AppHandle后运行
+    let account_watcher = app_state.watcher.clone();
+    let spool = app_state.spool.clone();
+    let revalidation = app_state.revalidation.clone();
+    let refresh = app_state.refresh.clone();
+    // 退出时终止验证进程,避免遗留node与浏览器进程
+    let validator = app_state.validator.clone();
+
     // 启动Tauri应用
     tauri::Builder::default()
         .manage(app_state)
@@ -56,6 +79,38 @@ fn main() {
             commands::cookies_commands::query_cookies,
             commands::cookies_commands::delete_cookies,
             commands::cookies_commands::list_all_uids,
+            commands::cookies_commands::cancel_validation,
+            commands::cookies_commands::refresh_cookies,
+            commands::account_commands::list_accounts,
+            commands::account_commands::set_account_state,
+            commands::account_commands::restore_account,
+            commands::account_commands::purge_account,
+            commands::account_commands::empty_trash,
+            commands::account_commands::list_cached_accounts,
+            commands::account_commands::get_account_watch_status,
+            commands::history_commands::list_cookies_history,
+            commands::history_commands::diff_cookies_versions,
+            commands::history_commands::rollback_cookies,
+            commands::tag_commands::add_account_tags,
+            commands::tag_commands::remove_account_tags,
+            commands::tag_commands::get_account_tags,
+            commands::tag_commands::list_accounts_by_tags,
+            commands::tag_commands::list_tag_counts,
+            commands::transfer_commands::export_cookies,
+            commands::transfer_commands::import_cookies,
+            commands::transfer_commands::import_cookies_text,
+            commands::transfer_commands::export_encrypted_bundle,
+            commands::transfer_commands::import_encrypted_bundle,
+            commands::lease_commands::acquire_account_lease,
+            commands::lease_commands::renew_account_lease,
+            commands::lease_commands::release_account_lease,
+            commands::lease_commands::list_account_leases,
+            commands::audit_commands::query_audit_log,
+            commands::spool_commands::get_spool_status,
+            commands::spool_commands::flush_spool,
+            commands::revalidation_commands::revalidate_accounts,
+            commands::revalidation_commands::validate_accounts,
+            commands::revalidation_commands::get_revalidation_status,
             commands::dependency_commands::check_dependencies,
             commands::dependency_commands::install_dependency,
             commands::dependency_commands::query_dependency_status,
@@ -69,8 +124,16 @@ fn main() {
             commands::redis_commands::test_redis_connection,
             commands::redis_commands::save_redis_config,
             commands::redis_commands::load_redis_config,
+            commands::redis_commands::migrate_key_prefix,
         ])
-        .setup(move |_app| {
+        .setup(move |app| {
+            tauri::async_runtime::spawn(account_watcher.run(app.handle().clone()));
+            if let Some(spool) = spool {
+                tauri::async_runtime::spawn(spool.run(app.handle().clone()));
+            }
+            tauri::async_runtime::spawn(revalidation.run(app.handle().clone()));
+            tauri::async_runtime::spawn(refresh.run());
+
             // 浏览器后端选择
             let backend = std::env::var("BROWSER_BACKEND")
                 .unwrap_or_else(|_| "playwright".to_string());
@@ -97,8 +160,13 @@ fn main() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("启动Tauri应用时发生错误");
+        .build(tauri::generate_context!())
+        .expect("启动Tauri应用时发生错误")
+        .run(move |_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                validator.shutdown();
+            }
+        });
 
     tracing::info!("应用程序已停止");
 }
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::CookiesData;
+
+/// 租约选择策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaseStrategy {
+    /// 按UID顺序轮转,从上一次发放的账户之后开始
+    RoundRobin,
+
+    /// 优先发放最久未被租用的账户
+    #[default]
+    LeastRecentlyUsed,
+}
+
+/// 账户租约
+///
+/// `token` 是持有凭证,续租和释放时必须提供,只返回给获取租约的一方。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountLease {
+    /// 持有凭证
+    pub token: String,
+
+    /// 被租用的账户
+    pub uid: String,
+
+    /// 持有者标识 (worker名称、主机名等)
+    pub holder: String,
+
+    /// 获取时间
+    pub acquired_at: DateTime<Utc>,
+
+    /// 到期时间 (未续租则自动释放)
+    pub expires_at: DateTime<Utc>,
+}
+
+impl AccountLease {
+    /// 转换为不含凭证的持有者信息
+    pub fn holder_info(&self) -> LeaseHolder {
+        LeaseHolder {
+            uid: self.uid.clone(),
+            holder: self.holder.clone(),
+            acquired_at: self.acquired_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+/// 当前租约持有者 (用于展示,不含凭证)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseHolder {
+    pub uid: String,
+    pub holder: String,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 获取租约的结果: 租约与账户Cookies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeasedAccount {
+    pub lease: AccountLease,
+    pub cookies: CookiesData,
+}
+
+/// 按轮转策略排列候选账户
+///
+/// 候选按UID排序后,从 `cursor` 之后的第一个开始,绕回到开头。
+/// `cursor` 为None或已不在候选中时按UID顺序从头开始。
+///
+/// # 示例
+/// ```
+/// use weibo_login::models::account_lease::round_robin_order;
+///
+/// let uids = vec!["3".to_string(), "1".to_string(), "2".to_string()];
+/// assert_eq!(round_robin_order(uids, Some("2")), vec!["3", "1", "2"]);
+/// ```
+pub fn round_robin_order(mut uids: Vec<String>, cursor: Option<&str>) -> Vec<String> {
+    uids.sort();
+    let start = cursor
+        .map(|cursor| uids.partition_point(|uid| uid.as_str() <= cursor))
+        .unwrap_or(0);
+    let len = uids.len().max(1);
+    uids.rotate_left(start % len);
+    uids
+}
+
+/// 按最近最少使用排列候选账户
+///
+/// `last_used` 为每个候选对应的最近获取时间 (毫秒),从未租用过的排最前;
+/// 时间相同时按UID排序,保证结果稳定。
+pub fn least_recently_used_order(uids: Vec<String>, last_used: &[Option<i64>]) -> Vec<String> {
+    let mut pairs: Vec<(Option<i64>, String)> = last_used.iter().copied().zip(uids).collect();
+    pairs.sort();
+    pairs.into_iter().map(|(_, uid)| uid).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_round_robin_wraps_after_cursor() {
+        let order = round_robin_order(uids(&["a", "b", "c"]), Some("c"));
+        assert_eq!(order, uids(&["a", "b", "c"]));
+
+        let order = round_robin_order(uids(&["a", "b", "c"]), Some("a"));
+        assert_eq!(order, uids(&["b", "c", "a"]));
+    }
+
+    #[test]
+    fn test_round_robin_with_missing_cursor() {
+        // 上次发放的账户已被删除: 从它之后的位置继续
+        let order = round_robin_order(uids(&["a", "c"]), Some("b"));
+        assert_eq!(order, uids(&["c", "a"]));
+
+        assert_eq!(
+            round_robin_order(uids(&["b", "a"]), None),
+            uids(&["a", "b"])
+        );
+        assert!(round_robin_order(Vec::new(), Some("a")).is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_prefers_never_used() {
+        let order =
+            least_recently_used_order(uids(&["a", "b", "c"]), &[Some(200), None, Some(100)]);
+        assert_eq!(order, uids(&["b", "c", "a"]));
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::cookies_history::SnapshotValidation;
+use crate::models::validation_outcome::ValidationRecord;
+
+/// 账户生命周期状态
+///
+/// 状态保存在Cookies Hash的 `state` 字段中,缺少该字段的旧数据视为 `Active`。
+///
+/// 状态转换:
+/// - 保存新Cookies (扫码登录、导入、回滚): 任意状态 → `Active`
+/// - 删除: `Active`/`Invalid`/`Archived` → `DeletedPendingPurge`
+/// - 恢复: `DeletedPendingPurge` → 删除前的状态
+/// - 验证未通过 (过期、封禁、锁定): `Active` → `Invalid`
+/// - 手动标记: `Active`/`Invalid`/`Archived` 之间互相转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountState {
+    /// 正常可用,随Cookies有效期自然过期
+    #[default]
+    Active,
+
+    /// 验证失败,保留数据并标记,不会自动过期
+    Invalid,
+
+    /// 已归档,不参与租约分配,不会自动过期
+    Archived,
+
+    /// 已删除,在回收站中等待清除,清除期限前可恢复
+    DeletedPendingPurge,
+}
+
+impl AccountState {
+    /// Redis中存储的字符串形式
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountState::Active => "active",
+            AccountState::Invalid => "invalid",
+            AccountState::Archived => "archived",
+            AccountState::DeletedPendingPurge => "deleted_pending_purge",
+        }
+    }
+
+    /// 从Redis字段解析,未知值返回None
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(AccountState::Active),
+            "invalid" => Some(AccountState::Invalid),
+            "archived" => Some(AccountState::Archived),
+            "deleted_pending_purge" => Some(AccountState::DeletedPendingPurge),
+            _ => None,
+        }
+    }
+
+    /// 是否在回收站中
+    pub fn is_trashed(&self) -> bool {
+        *self == AccountState::DeletedPendingPurge
+    }
+}
+
+/// 账户状态详情
+///
+/// 从Cookies Hash的状态相关字段解析,不包含cookies值。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountStatus {
+    /// 当前状态
+    pub state: AccountState,
+
+    /// 状态变更时间 (旧数据没有该字段)
+    pub state_changed_at: Option<DateTime<Utc>>,
+
+    /// 状态原因 (如验证失败的错误信息)
+    pub state_reason: Option<String>,
+
+    /// 清除期限 (仅回收站中的账户)
+    pub purge_at: Option<DateTime<Utc>>,
+}
+
+impl AccountStatus {
+    /// 从Cookies Hash字段解析
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::{AccountState, AccountStatus};
+    /// use std::collections::HashMap;
+    ///
+    /// let legacy = HashMap::from([("cookies".to_string(), "{}".to_string())]);
+    /// assert_eq!(AccountStatus::from_fields(&legacy).state, AccountState::Active);
+    /// ```
+    pub fn from_fields(fields: &HashMap<String, String>) -> Self {
+        let timestamp = |name: &str| {
+            fields
+                .get(name)
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        };
+
+        Self {
+            state: fields
+                .get("state")
+                .and_then(|s| AccountState::parse(s))
+                .unwrap_or_default(),
+            state_changed_at: timestamp("state_changed_at"),
+            state_reason: fields
+                .get("state_reason")
+                .filter(|s| !s.is_empty())
+                .cloned(),
+            purge_at: timestamp("purge_at"),
+        }
+    }
+}
+
+/// 账户列表项
+///
+/// 账户管理界面展示用,不包含cookies值。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountSummary {
+    pub uid: String,
+    pub screen_name: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    /// Cookies key的过期时间 (回收站中即清除期限,失效/归档账户为None)
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub status: AccountStatus,
+    /// 最近一次验证 (从未验证或Cookies已被未验证的数据替换时为None)
+    pub last_validation: Option<ValidationRecord>,
+    /// 当前Cookies保存时经过的验证 (早期保存的账户没有记录,为None)
+    pub validation: Option<SnapshotValidation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        for state in [
+            AccountState::Active,
+            AccountState::Invalid,
+            AccountState::Archived,
+            AccountState::DeletedPendingPurge,
+        ] {
+            assert_eq!(AccountState::parse(state.as_str()), Some(state));
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, format!("\"{}\"", state.as_str()));
+        }
+        assert_eq!(AccountState::parse("gone"), None);
+    }
+
+    #[test]
+    fn test_status_from_fields() {
+        let fields = HashMap::from([
+            ("state".to_string(), "deleted_pending_purge".to_string()),
+            ("state_changed_at".to_string(), "1700000000".to_string()),
+            ("purge_at".to_string(), "1700604800".to_string()),
+            ("state_reason".to_string(), String::new()),
+        ]);
+        let status = AccountStatus::from_fields(&fields);
+
+        assert!(status.state.is_trashed());
+        assert_eq!(status.state_changed_at.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(status.purge_at.unwrap().timestamp(), 1_700_604_800);
+        assert_eq!(status.state_reason, None);
+    }
+
+    #[test]
+    fn test_unknown_state_falls_back_to_active() {
+        let fields = HashMap::from([("state".to_string(), "bogus".to_string())]);
+        assert_eq!(
+            AccountStatus::from_fields(&fields).state,
+            AccountState::Active
+        );
+    }
+}
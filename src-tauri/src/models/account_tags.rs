@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::errors::ValidationError;
+
+/// 标签最大长度 (字符数)
+const MAX_TAG_CHARS: usize = 64;
+
+/// 多标签查询的组合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    /// 同时拥有所有标签 (集合交集)
+    All,
+
+    /// 拥有任意一个标签 (集合并集)
+    Any,
+}
+
+/// 标签及其账户数量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// 规范化标签列表
+///
+/// 规则:
+/// 1. 去除首尾空白
+/// 2. 不允许为空,不允许超过64个字符
+/// 3. 去重,保持首次出现的顺序
+///
+/// # 错误
+/// 返回 `ValidationError::InvalidFormat` 如果任一标签不合法
+///
+/// # 示例
+/// ```
+/// use weibo_login::models::account_tags::normalize_tags;
+///
+/// let tags = normalize_tags(&[" 项目A ".to_string(), "项目A".to_string()]).unwrap();
+/// assert_eq!(tags, vec!["项目A"]);
+/// ```
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, ValidationError> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(ValidationError::InvalidFormat("标签不能为空".to_string()));
+        }
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(ValidationError::InvalidFormat(format!(
+                "标签过长 (最多{}个字符): {}",
+                MAX_TAG_CHARS, tag
+            )));
+        }
+        if !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+
+    if normalized.is_empty() {
+        return Err(ValidationError::InvalidFormat(
+            "至少需要一个标签".to_string(),
+        ));
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_trims_and_dedups() {
+        let tags = normalize_tags(&[
+            "scraper".to_string(),
+            " scraper ".to_string(),
+            "监控".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(tags, vec!["scraper", "监控"]);
+    }
+
+    #[test]
+    fn test_normalize_rejects_empty() {
+        assert!(normalize_tags(&[]).is_err());
+        assert!(normalize_tags(&["  ".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_normalize_rejects_too_long() {
+        assert!(normalize_tags(&["a".repeat(65)]).is_err());
+        assert!(normalize_tags(&["标".repeat(64)]).is_ok());
+    }
+}
@@ -0,0 +1,309 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// 审计操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Save,
+    Query,
+    Delete,
+    Restore,
+    Purge,
+    StateChange,
+    Rollback,
+    Export,
+    Import,
+    LeaseAcquire,
+    LeaseRenew,
+    LeaseRelease,
+}
+
+impl AuditOperation {
+    /// Stream中存储的名称 (与serde名称一致)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Save => "save",
+            AuditOperation::Query => "query",
+            AuditOperation::Delete => "delete",
+            AuditOperation::Restore => "restore",
+            AuditOperation::Purge => "purge",
+            AuditOperation::StateChange => "state_change",
+            AuditOperation::Rollback => "rollback",
+            AuditOperation::Export => "export",
+            AuditOperation::Import => "import",
+            AuditOperation::LeaseAcquire => "lease_acquire",
+            AuditOperation::LeaseRenew => "lease_renew",
+            AuditOperation::LeaseRelease => "lease_release",
+        }
+    }
+
+    /// 从存储名称解析
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            AuditOperation::Save,
+            AuditOperation::Query,
+            AuditOperation::Delete,
+            AuditOperation::Restore,
+            AuditOperation::Purge,
+            AuditOperation::StateChange,
+            AuditOperation::Rollback,
+            AuditOperation::Export,
+            AuditOperation::Import,
+            AuditOperation::LeaseAcquire,
+            AuditOperation::LeaseRenew,
+            AuditOperation::LeaseRelease,
+        ]
+        .into_iter()
+        .find(|op| op.as_str() == value)
+    }
+}
+
+/// 操作结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+/// 待写入的审计事件
+///
+/// `detail` 只记录错误信息或数量等摘要,绝不包含cookie值。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    pub operation: AuditOperation,
+    pub uid: Option<String>,
+    pub caller: String,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+}
+
+impl AuditEvent {
+    /// 创建成功事件
+    ///
+    /// # 参数
+    /// - `operation`: 操作类型
+    /// - `caller`: 调用方 (前端命令名或API客户端标识)
+    pub fn new(operation: AuditOperation, caller: &str) -> Self {
+        Self {
+            operation,
+            uid: None,
+            caller: caller.to_string(),
+            outcome: AuditOutcome::Success,
+            detail: None,
+        }
+    }
+
+    /// 设置UID
+    pub fn with_uid(mut self, uid: &str) -> Self {
+        self.uid = Some(uid.to_string());
+        self
+    }
+
+    /// 标记为失败并记录原因
+    pub fn failed(mut self, reason: impl Display) -> Self {
+        self.outcome = AuditOutcome::Failure;
+        self.detail = Some(reason.to_string());
+        self
+    }
+
+    /// 按操作结果设置成功/失败
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::audit::{AuditEvent, AuditOperation, AuditOutcome};
+    ///
+    /// let result: Result<(), String> = Err("未找到".to_string());
+    /// let event = AuditEvent::new(AuditOperation::Query, "query_cookies").with_result(&result);
+    /// assert_eq!(event.outcome, AuditOutcome::Failure);
+    /// assert_eq!(event.detail.as_deref(), Some("未找到"));
+    /// ```
+    pub fn with_result<T, E: Display>(self, result: &Result<T, E>) -> Self {
+        match result {
+            Ok(_) => self,
+            Err(e) => self.failed(e),
+        }
+    }
+
+    /// 设置附加说明
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// 转换为Stream字段
+    pub fn to_fields(&self, timestamp: DateTime<Utc>) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("ts", timestamp.timestamp_millis().to_string()),
+            ("op", self.operation.as_str().to_string()),
+            ("caller", self.caller.clone()),
+            (
+                "outcome",
+                match self.outcome {
+                    AuditOutcome::Success => "success",
+                    AuditOutcome::Failure => "failure",
+                }
+                .to_string(),
+            ),
+        ];
+        if let Some(uid) = &self.uid {
+            fields.push(("uid", uid.clone()));
+        }
+        if let Some(detail) = &self.detail {
+            fields.push(("detail", detail.clone()));
+        }
+        fields
+    }
+}
+
+/// 审计日志条目 (从Stream读取)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Stream条目ID,也用作分页游标
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub operation: AuditOperation,
+    pub uid: Option<String>,
+    pub caller: String,
+    pub outcome: AuditOutcome,
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    /// 从Stream条目解析,字段缺失或无法识别时返回None
+    pub fn from_fields(id: String, fields: &HashMap<String, String>) -> Option<Self> {
+        let timestamp = fields
+            .get("ts")
+            .and_then(|ts| ts.parse::<i64>().ok())
+            .and_then(DateTime::from_timestamp_millis)?;
+        let operation = fields.get("op").and_then(|op| AuditOperation::parse(op))?;
+        let outcome = match fields.get("outcome").map(String::as_str) {
+            Some("success") => AuditOutcome::Success,
+            Some("failure") => AuditOutcome::Failure,
+            _ => return None,
+        };
+
+        Some(Self {
+            id,
+            timestamp,
+            operation,
+            uid: fields.get("uid").cloned(),
+            caller: fields.get("caller").cloned().unwrap_or_default(),
+            outcome,
+            detail: fields.get("detail").cloned(),
+        })
+    }
+}
+
+/// 审计日志查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// 只看该UID
+    pub uid: Option<String>,
+
+    /// 只看这些操作 (空表示全部)
+    #[serde(default)]
+    pub operations: Vec<AuditOperation>,
+
+    /// 从该条目ID之前 (更早) 开始,None表示从最新开始
+    pub before: Option<String>,
+
+    /// 每页条数
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    /// 条目是否满足过滤条件
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        let uid_matches = self
+            .uid
+            .as_ref()
+            .is_none_or(|uid| entry.uid.as_deref() == Some(uid.as_str()));
+        let op_matches = self.operations.is_empty() || self.operations.contains(&entry.operation);
+        uid_matches && op_matches
+    }
+}
+
+/// 审计日志分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPage {
+    /// 按时间倒序的条目
+    pub entries: Vec<AuditEntry>,
+
+    /// 下一页游标 (传给 `AuditQuery::before`),None表示已到最早
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_fields_round_trip() {
+        let now = DateTime::from_timestamp_millis(Utc::now().timestamp_millis()).unwrap();
+        let event = AuditEvent::new(AuditOperation::LeaseAcquire, "worker-1")
+            .with_uid("123")
+            .failed("没有可用的账户");
+
+        let fields: HashMap<String, String> = event
+            .to_fields(now)
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let entry = AuditEntry::from_fields("1-0".to_string(), &fields).unwrap();
+
+        assert_eq!(entry.timestamp, now);
+        assert_eq!(entry.operation, AuditOperation::LeaseAcquire);
+        assert_eq!(entry.uid.as_deref(), Some("123"));
+        assert_eq!(entry.caller, "worker-1");
+        assert_eq!(entry.outcome, AuditOutcome::Failure);
+        assert_eq!(entry.detail.as_deref(), Some("没有可用的账户"));
+    }
+
+    #[test]
+    fn test_operation_names_match_serde() {
+        for op in [
+            AuditOperation::Save,
+            AuditOperation::StateChange,
+            AuditOperation::LeaseRelease,
+        ] {
+            let json = serde_json::to_string(&op).unwrap();
+            assert_eq!(json, format!("\"{}\"", op.as_str()));
+            assert_eq!(AuditOperation::parse(op.as_str()), Some(op));
+        }
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let entry = AuditEntry {
+            id: "1-0".to_string(),
+            timestamp: Utc::now(),
+            operation: AuditOperation::Query,
+            uid: Some("123".to_string()),
+            caller: "query_cookies".to_string(),
+            outcome: AuditOutcome::Success,
+            detail: None,
+        };
+
+        assert!(AuditQuery::default().matches(&entry));
+        assert!(AuditQuery {
+            uid: Some("123".to_string()),
+            operations: vec![AuditOperation::Query, AuditOperation::Save],
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditQuery {
+            operations: vec![AuditOperation::Delete],
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditQuery {
+            uid: Some("456".to_string()),
+            ..Default::default()
+        }
+        .matches(&entry));
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::models::errors::ValidationError;
+use crate::models::key_namespace::KeyNamespace;
 
 /// Cookies数据
 ///
@@ -22,7 +23,7 @@ pub struct CookiesData {
     /// 验证时间 (通过微博资料API验证的时间)
     pub validated_at: DateTime<Utc>,
 
-    /// Redis存储key (格式: weibo:cookies:{uid})
+    /// Redis存储key (格式: {prefix}:cookies:{uid},默认前缀为 weibo)
     pub redis_key: String,
 
     /// 用户昵称 (可选,验证时从API获取)
@@ -50,7 +51,7 @@ impl CookiesData {
     pub fn new(uid: String, cookies: HashMap<String, String>) -> Self {
         let now = Utc::now();
         Self {
-            redis_key: format!("weibo:cookies:{}", uid),
+            redis_key: KeyNamespace::default().cookies_key(&uid),
             uid,
             cookies,
             fetched_at: now,
@@ -128,6 +129,22 @@ impl CookiesData {
         self.screen_name = Some(screen_name);
         self
     }
+
+    /// 设置key命名空间 (构建器模式)
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::{CookiesData, KeyNamespace};
+    /// use std::collections::HashMap;
+    /// let ns = KeyNamespace::new("staging").unwrap();
+    /// let data = CookiesData::new("1234567890".to_string(), HashMap::new())
+    ///     .with_namespace(&ns);
+    /// assert_eq!(data.redis_key, "staging:cookies:1234567890");
+    /// ```
+    pub fn with_namespace(mut self, namespace: &KeyNamespace) -> Self {
+        self.redis_key = namespace.cookies_key(&self.uid);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::models::CookiesData;
+
+/// Cookies快照来源
+///
+/// 记录每个历史版本是如何产生的,便于判断回滚目标的可信度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotSource {
+    /// 扫码登录 (WebSocket LoginConfirmed)
+    QrLogin,
+
+    /// 前端手动保存 (save_cookies命令)
+    Manual,
+
+    /// 从历史版本回滚
+    Rollback,
+
+    /// 从文件导入 (cookies.txt / storageState / bundle等)
+    Import,
+
+    /// 保活刷新 (微博轮换后的cookies)
+    Refresh,
+}
+
+/// 快照保存时的验证结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotValidation {
+    /// 本地通过Playwright验证
+    Verified,
+
+    /// 信任服务端 (Playwright server已通过VIP API确认UID)
+    TrustedServer,
+
+    /// 未经验证
+    Unverified,
+
+    /// 已保存,后台验证进行中 (扫码登录的异步验证策略)
+    Pending,
+
+    /// 后台验证未通过: Cookies被拒绝,或验证得到的UID与扫码结果不符
+    Mismatch,
+}
+
+impl SnapshotValidation {
+    /// 存储在Redis中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Verified => "verified",
+            Self::TrustedServer => "trusted_server",
+            Self::Unverified => "unverified",
+            Self::Pending => "pending",
+            Self::Mismatch => "mismatch",
+        }
+    }
+
+    /// 解析存储的名称,未知值返回None
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "verified" => Some(Self::Verified),
+            "trusted_server" => Some(Self::TrustedServer),
+            "unverified" => Some(Self::Unverified),
+            "pending" => Some(Self::Pending),
+            "mismatch" => Some(Self::Mismatch),
+            _ => None,
+        }
+    }
+}
+
+/// Cookies历史快照
+///
+/// 每次保存都会生成一个快照,按版本号单调递增。
+/// 快照包含完整cookies值,用于回滚;日志和差异比较只使用键名。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookiesSnapshot {
+    /// 版本号 (同一UID内单调递增,从1开始)
+    pub version: u64,
+
+    /// 微博用户ID
+    pub uid: String,
+
+    /// Cookie键值对
+    pub cookies: HashMap<String, String>,
+
+    /// 用户昵称
+    pub screen_name: Option<String>,
+
+    /// 获取时间
+    pub fetched_at: DateTime<Utc>,
+
+    /// 验证时间
+    pub validated_at: DateTime<Utc>,
+
+    /// 快照来源
+    pub source: SnapshotSource,
+
+    /// 保存时的验证结果
+    pub validation: SnapshotValidation,
+
+    /// 快照写入时间
+    pub saved_at: DateTime<Utc>,
+}
+
+impl CookiesSnapshot {
+    /// 从CookiesData创建快照
+    pub fn from_cookies_data(
+        version: u64,
+        cookies_data: &CookiesData,
+        source: SnapshotSource,
+        validation: SnapshotValidation,
+    ) -> Self {
+        Self {
+            version,
+            uid: cookies_data.uid.clone(),
+            cookies: cookies_data.cookies.clone(),
+            screen_name: cookies_data.screen_name.clone(),
+            fetched_at: cookies_data.fetched_at,
+            validated_at: cookies_data.validated_at,
+            source,
+            validation,
+            saved_at: Utc::now(),
+        }
+    }
+
+    /// 转换为不含cookies值的摘要 (用于列表展示)
+    pub fn summary(&self) -> CookiesSnapshotSummary {
+        let mut cookie_names: Vec<String> = self.cookies.keys().cloned().collect();
+        cookie_names.sort();
+
+        CookiesSnapshotSummary {
+            version: self.version,
+            screen_name: self.screen_name.clone(),
+            fetched_at: self.fetched_at,
+            validated_at: self.validated_at,
+            source: self.source,
+            validation: self.validation,
+            saved_at: self.saved_at,
+            cookie_names,
+        }
+    }
+}
+
+/// 历史快照摘要
+///
+/// 返回给前端的列表项,只包含cookie键名,不暴露值。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookiesSnapshotSummary {
+    pub version: u64,
+    pub screen_name: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub validated_at: DateTime<Utc>,
+    pub source: SnapshotSource,
+    pub validation: SnapshotValidation,
+    pub saved_at: DateTime<Utc>,
+    pub cookie_names: Vec<String>,
+}
+
+/// 两个快照之间的差异
+///
+/// 仅按cookie名称比较,值只用于判断是否变化,绝不出现在结果中。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookiesDiff {
+    /// 起始版本
+    pub from_version: u64,
+
+    /// 目标版本
+    pub to_version: u64,
+
+    /// 目标版本新增的cookie
+    pub added: Vec<String>,
+
+    /// 目标版本移除的cookie
+    pub removed: Vec<String>,
+
+    /// 两个版本都存在但值不同的cookie
+    pub changed: Vec<String>,
+
+    /// 两个版本值相同的cookie
+    pub unchanged: Vec<String>,
+}
+
+impl CookiesDiff {
+    /// 比较两个快照
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::cookies_history::CookiesDiff;
+    /// use std::collections::HashMap;
+    ///
+    /// let from = HashMap::from([("SUB".to_string(), "a".to_string())]);
+    /// let to = HashMap::from([
+    ///     ("SUB".to_string(), "b".to_string()),
+    ///     ("SUBP".to_string(), "c".to_string()),
+    /// ]);
+    ///
+    /// let diff = CookiesDiff::between(1, &from, 2, &to);
+    /// assert_eq!(diff.added, vec!["SUBP"]);
+    /// assert_eq!(diff.changed, vec!["SUB"]);
+    /// ```
+    pub fn between(
+        from_version: u64,
+        from: &HashMap<String, String>,
+        to_version: u64,
+        to: &HashMap<String, String>,
+    ) -> Self {
+        let names: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+
+        let mut diff = Self {
+            from_version,
+            to_version,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            unchanged: Vec::new(),
+        };
+
+        for name in names {
+            match (from.get(name), to.get(name)) {
+                (None, Some(_)) => diff.added.push(name.clone()),
+                (Some(_), None) => diff.removed.push(name.clone()),
+                (Some(a), Some(b)) if a != b => diff.changed.push(name.clone()),
+                (Some(_), Some(_)) => diff.unchanged.push(name.clone()),
+                (None, None) => {}
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookies(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_classifies_names() {
+        let from = cookies(&[("SUB", "1"), ("SUBP", "2"), ("_T_WM", "3")]);
+        let to = cookies(&[("SUB", "1"), ("SUBP", "changed"), ("ALF", "4")]);
+
+        let diff = CookiesDiff::between(1, &from, 2, &to);
+        assert_eq!(diff.added, vec!["ALF"]);
+        assert_eq!(diff.removed, vec!["_T_WM"]);
+        assert_eq!(diff.changed, vec!["SUBP"]);
+        assert_eq!(diff.unchanged, vec!["SUB"]);
+    }
+
+    #[test]
+    fn test_diff_never_contains_values() {
+        let from = cookies(&[("SUB", "secret_from")]);
+        let to = cookies(&[("SUB", "secret_to")]);
+
+        let diff = CookiesDiff::between(1, &from, 2, &to);
+        let json = serde_json::to_string(&diff).unwrap();
+        assert!(!json.contains("secret_from"));
+        assert!(!json.contains("secret_to"));
+    }
+
+    #[test]
+    fn test_validation_round_trip() {
+        for validation in [
+            SnapshotValidation::Verified,
+            SnapshotValidation::TrustedServer,
+            SnapshotValidation::Unverified,
+            SnapshotValidation::Pending,
+            SnapshotValidation::Mismatch,
+        ] {
+            assert_eq!(SnapshotValidation::parse(validation.as_str()), Some(validation));
+            assert_eq!(
+                serde_json::to_value(validation).unwrap(),
+                validation.as_str()
+            );
+        }
+        assert_eq!(SnapshotValidation::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_snapshot_summary_hides_values() {
+        let data = CookiesData::new(
+            "123".to_string(),
+            cookies(&[("SUBP", "yyy"), ("SUB", "xxx")]),
+        );
+        let snapshot = CookiesSnapshot::from_cookies_data(
+            3,
+            &data,
+            SnapshotSource::Manual,
+            SnapshotValidation::Verified,
+        );
+
+        let summary = snapshot.summary();
+        assert_eq!(summary.version, 3);
+        assert_eq!(summary.cookie_names, vec!["SUB", "SUBP"]);
+        assert!(!serde_json::to_string(&summary).unwrap().contains("xxx"));
+    }
+}
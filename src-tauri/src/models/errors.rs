@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::models::validation_outcome::ValidationOutcome;
+
 /// API调用相关错误
 ///
 /// 处理与微博API交互时的各种失败场景。
@@ -100,9 +102,19 @@ pub enum ApiError {
 #[derive(Debug, Error, Serialize, Deserialize)]
 #[serde(tag = "error", content = "details")]
 pub enum ValidationError {
+    /// 微博拒绝了cookies
+    ///
+    /// `outcome` 说明具体原因 (过期、封禁、锁定、验证码、频率限制),
+    /// 由验证器根据接口响应分类得到
+    #[error("Cookies验证未通过 ({}): {message}", outcome.as_str())]
+    Rejected {
+        outcome: ValidationOutcome,
+        message: String,
+    },
+
     /// 调用个人资料API失败
     ///
-    /// 使用cookies访问/api/profile端点失败
+    /// 使用cookies访问/api/profile端点失败,且无法识别原因
     #[error("个人资料API调用失败 (状态码 {status}): {message}")]
     ProfileApiFailed { status: u16, message: String },
 
@@ -129,6 +141,70 @@ pub enum ValidationError {
     /// 无法从个人资料API响应中提取用户UID
     #[error("无法提取用户UID: {0}")]
     UidExtractionFailed(String),
+
+    /// 验证进程崩溃
+    ///
+    /// 常驻的Playwright验证进程在返回结果前退出,下一次验证时自动重启
+    #[error("验证进程异常退出: {0}")]
+    WorkerCrashed(String),
+
+    /// 验证请求失败
+    ///
+    /// HTTP验证器无法连接微博或请求超时,与cookies是否有效无关
+    #[error("验证请求失败: {0}")]
+    RequestFailed(String),
+
+    /// 验证超时
+    ///
+    /// 超过配置的时限仍未得到结果 (Playwright验证进程会在其他验证结束后重启)
+    #[error("验证超时 ({seconds}秒)")]
+    Timeout { seconds: u64 },
+
+    /// 验证已取消
+    ///
+    /// 前端通过 `cancel_validation` 取消了进行中的验证
+    #[error("验证已取消")]
+    Cancelled,
+
+    /// 本地限速
+    ///
+    /// 验证请求过于频繁,排队时间超过上限,未发送给微博
+    #[error("验证请求过于频繁,请{}秒后重试", retry_after.unwrap_or(60))]
+    RateLimitExceeded { retry_after: Option<u64> },
+}
+
+impl ValidationError {
+    /// 超过时限,不足一秒的部分向上取整 (500毫秒的时限报告为1秒而不是0秒)
+    ///
+    /// # 示例
+    /// ```
+    /// use std::time::Duration;
+    /// use weibo_login::models::ValidationError;
+    ///
+    /// let err = ValidationError::timeout(Duration::from_millis(500));
+    /// assert!(matches!(err, ValidationError::Timeout { seconds: 1 }));
+    /// ```
+    pub fn timeout(limit: std::time::Duration) -> Self {
+        Self::Timeout {
+            seconds: ceil_secs(limit),
+        }
+    }
+
+    /// 微博拒绝了cookies: 按响应内容分类,无法识别原因时为 `ProfileApiFailed`
+    ///
+    /// # 参数
+    /// - `status`: HTTP状态码 (如有)
+    /// - `hint`: 重定向目标、最终URL或响应片段,仅用于分类
+    /// - `message`: 展示给用户的错误信息
+    pub fn rejected(status: Option<u16>, hint: Option<&str>, message: String) -> Self {
+        match ValidationOutcome::classify(status, hint, &message) {
+            ValidationOutcome::Unknown => Self::ProfileApiFailed {
+                status: status.unwrap_or(401),
+                message,
+            },
+            outcome => Self::Rejected { outcome, message },
+        }
+    }
 }
 
 /// Redis存储相关错误
@@ -166,6 +242,250 @@ pub enum StorageError {
     /// 具体的Redis命令(GET/SET/DEL等)执行出错
     #[error("Redis命令执行失败: {0}")]
     CommandFailed(String),
+
+    /// 账户当前状态不允许此操作
+    ///
+    /// 如恢复不在回收站中的账户,或修改回收站中账户的状态
+    #[error("账户状态不允许此操作: {0}")]
+    InvalidState(String),
+}
+
+/// 导入导出相关错误
+///
+/// 处理cookies文件读写与格式转换时的失败场景
+#[derive(Debug, Error, Serialize, Deserialize)]
+#[serde(tag = "error", content = "details")]
+pub enum TransferError {
+    /// Redis读写失败
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    /// 文件读写失败
+    ///
+    /// 目标路径不可写、源文件不存在等
+    #[error("文件读写失败: {0}")]
+    Io(String),
+
+    /// 文件格式无效
+    ///
+    /// 内容无法按所选格式解析,或格式不支持当前操作
+    #[error("文件格式无效: {0}")]
+    InvalidFormat(String),
+
+    /// 选择范围内没有任何账户
+    #[error("没有可导出的账户: {0}")]
+    EmptySelection(String),
+
+    /// 加密失败
+    ///
+    /// 口令不满足要求或密钥派生失败
+    #[error("加密失败: {0}")]
+    Encryption(String),
+
+    /// 解密失败
+    ///
+    /// 口令错误,或文件被篡改/损坏,认证标签校验未通过
+    #[error("解密失败: {0}")]
+    Decryption(String),
+}
+
+/// 账户租约相关错误
+#[derive(Debug, Error, Serialize, Deserialize)]
+#[serde(tag = "error", content = "details")]
+pub enum LeaseError {
+    /// Redis读写失败
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    /// 没有可租用的账户
+    ///
+    /// 候选账户全部被占用、处于冷却期,或筛选条件下没有账户
+    #[error("没有可用的账户: {0}")]
+    NoAccountAvailable(String),
+
+    /// 租约不存在或凭证不匹配
+    ///
+    /// 租约已过期被自动释放,或已被他人重新获取
+    #[error("未持有账户 {0} 的租约")]
+    NotHeld(String),
+
+    /// 租约时长无效
+    #[error("租约时长无效: {0}")]
+    InvalidTtl(String),
+}
+
+/// Cookies保活刷新相关错误
+#[derive(Debug, Error, Serialize, Deserialize)]
+#[serde(tag = "error", content = "details")]
+pub enum RefreshError {
+    /// Redis读写失败
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    /// 刷新请求未通过
+    ///
+    /// 会话已失效 (过期、封禁等),或网络错误、超时
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
+
+/// 本地暂存队列相关错误
+#[derive(Debug, Error, Serialize, Deserialize)]
+#[serde(tag = "error", content = "details")]
+pub enum SpoolError {
+    /// Redis读写失败
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    /// 队列目录或文件读写失败
+    #[error("暂存队列读写失败: {0}")]
+    Io(String),
+
+    /// 加密失败
+    #[error("暂存队列加密失败: {0}")]
+    Encryption(String),
+
+    /// 条目无法解密或解析
+    ///
+    /// 文件被篡改、截断,或本地密钥已更换
+    #[error("暂存条目已损坏: {0}")]
+    Corrupted(String),
+
+    /// 启动时队列目录或密钥无法打开,本次运行没有暂存队列
+    #[error("本地暂存队列不可用")]
+    Unavailable,
+}
+
+/// 已登录微博请求相关错误
+///
+/// 由 `WeiboHttpClient` 返回,按HTTP状态、重定向目标与响应外层的错误码分类
+#[derive(Debug, Error, Serialize, Deserialize)]
+#[serde(tag = "error", content = "details")]
+pub enum WeiboHttpError {
+    /// 登录已失效
+    ///
+    /// 被重定向到登录页、HTTP 401,或接口返回未登录 (如 `ok: -100`)
+    #[error("登录已失效: {message}")]
+    LoginExpired { message: String },
+
+    /// 请求过于频繁
+    ///
+    /// HTTP 429 或频率限制错误码,`retry_after` 来自 `Retry-After` 响应头
+    #[error("请求过于频繁: {message}")]
+    RateLimited {
+        retry_after: Option<u64>,
+        message: String,
+    },
+
+    /// 账户受限
+    ///
+    /// 账户被封禁、锁定,或需要完成验证码
+    #[error("账户受限 ({}): {message}", outcome.as_str())]
+    AccountRestricted {
+        outcome: ValidationOutcome,
+        message: String,
+    },
+
+    /// 接口返回错误
+    ///
+    /// 响应外层 `ok` 不为1,且无法识别为以上原因
+    #[error("接口返回错误 (错误码 {code:?}): {message}")]
+    Api { code: Option<i64>, message: String },
+
+    /// HTTP错误状态
+    #[error("{path} 请求失败 (HTTP {status})")]
+    HttpStatus { status: u16, path: String },
+
+    /// 响应格式无效
+    ///
+    /// 不是JSON,或缺少预期字段
+    #[error("响应格式无效: {0}")]
+    InvalidResponse(String),
+
+    /// 网络请求失败
+    #[error("请求失败: {0}")]
+    RequestFailed(String),
+
+    /// 请求超时
+    #[error("请求超时 ({seconds}秒)")]
+    Timeout { seconds: u64 },
+
+    /// 请求无效
+    ///
+    /// 站点地址无法解析,或账户缺少必需的cookie
+    #[error("请求无效: {0}")]
+    InvalidRequest(String),
+}
+
+/// 时限的整秒数,不足一秒的部分向上取整
+fn ceil_secs(limit: std::time::Duration) -> u64 {
+    limit.as_secs() + u64::from(limit.subsec_nanos() > 0)
+}
+
+impl WeiboHttpError {
+    /// 请求超过时限,不足一秒的部分向上取整
+    pub fn timeout(limit: std::time::Duration) -> Self {
+        Self::Timeout {
+            seconds: ceil_secs(limit),
+        }
+    }
+
+    /// 对应的验证结果,便于记录到账户上
+    pub fn outcome(&self) -> ValidationOutcome {
+        match self {
+            Self::LoginExpired { .. } => ValidationOutcome::Expired,
+            Self::RateLimited { .. } => ValidationOutcome::RateLimited,
+            Self::AccountRestricted { outcome, .. } => *outcome,
+            Self::RequestFailed(_) => ValidationOutcome::NetworkError,
+            Self::Timeout { .. } => ValidationOutcome::Timeout,
+            Self::Api { .. }
+            | Self::HttpStatus { .. }
+            | Self::InvalidResponse(_)
+            | Self::InvalidRequest(_) => ValidationOutcome::Unknown,
+        }
+    }
+}
+
+/// 从 WeiboHttpError 转换为 ValidationError
+///
+/// 验证器与保活刷新基于 `WeiboHttpClient`: 登录失效、频率限制与账户受限映射为 `Rejected`,
+/// 接口错误、HTTP错误状态与无法识别的响应为 `ProfileApiFailed`。
+impl From<WeiboHttpError> for ValidationError {
+    fn from(err: WeiboHttpError) -> Self {
+        let outcome = err.outcome();
+        match err {
+            WeiboHttpError::LoginExpired { message }
+            | WeiboHttpError::RateLimited { message, .. }
+            | WeiboHttpError::AccountRestricted { message, .. } => {
+                ValidationError::Rejected { outcome, message }
+            }
+            WeiboHttpError::Api { message, .. } | WeiboHttpError::InvalidResponse(message) => {
+                ValidationError::ProfileApiFailed {
+                    status: 200,
+                    message,
+                }
+            }
+            err @ WeiboHttpError::HttpStatus { status, .. } => ValidationError::ProfileApiFailed {
+                status,
+                message: err.to_string(),
+            },
+            WeiboHttpError::RequestFailed(message) => ValidationError::RequestFailed(message),
+            WeiboHttpError::Timeout { seconds } => ValidationError::Timeout { seconds },
+            WeiboHttpError::InvalidRequest(message) => ValidationError::InvalidFormat(message),
+        }
+    }
+}
+
+impl From<std::io::Error> for SpoolError {
+    fn from(err: std::io::Error) -> Self {
+        SpoolError::Io(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for TransferError {
+    fn from(err: std::io::Error) -> Self {
+        TransferError::Io(err.to_string())
+    }
 }
 
 /// 实现从reqwest::Error到ApiError的转换
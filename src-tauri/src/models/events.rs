@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::models::{CookiesData, QrCodeStatus};
+use crate::models::{AccountSummary, CookiesData, QrCodeStatus, ValidationOutcome};
 
 /// 登录状态更新事件
 ///
@@ -114,3 +114,117 @@ impl LoginErrorEvent {
         }
     }
 }
+
+/// 保存后验证未通过事件 (`login_validation_mismatch`)
+///
+/// 扫码登录采用异步验证策略时,已保存的Cookies被微博拒绝
+/// 或属于另一个账户,账户的验证方式已标记为 `mismatch`
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginValidationMismatchEvent {
+    /// 二维码会话ID
+    pub qr_id: String,
+
+    /// 扫码登录的微博用户ID
+    pub uid: String,
+
+    /// 拒绝原因,Cookies属于另一个账户时为None
+    pub outcome: Option<ValidationOutcome>,
+
+    /// 验证失败的说明
+    pub message: String,
+
+    /// 验证完成时间
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LoginValidationMismatchEvent {
+    pub fn new(
+        qr_id: String,
+        uid: String,
+        outcome: Option<ValidationOutcome>,
+        message: String,
+    ) -> Self {
+        Self {
+            qr_id,
+            uid,
+            outcome,
+            message,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// 账户变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountChangeKind {
+    /// 内容或状态被修改 (包括新增)
+    Changed,
+
+    /// TTL到期被Redis删除
+    Expired,
+
+    /// 被删除 (本应用之外的工具、驱逐或重命名)
+    Deleted,
+}
+
+impl AccountChangeKind {
+    /// 推送到前端的事件名
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            AccountChangeKind::Changed => "account_changed",
+            AccountChangeKind::Expired => "account_expired",
+            AccountChangeKind::Deleted => "account_deleted",
+        }
+    }
+}
+
+/// 变化的发现方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeDetection {
+    /// Redis keyspace通知
+    Notification,
+
+    /// 定时轮询比对
+    Polling,
+}
+
+/// 账户变化事件
+///
+/// 推送到前端,前端据此更新缓存的账户列表,无需重新查询
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountChangeEvent {
+    /// 微博用户ID
+    pub uid: String,
+
+    /// 变化类型
+    pub kind: AccountChangeKind,
+
+    /// 变化后的账户摘要 (过期或删除时为None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountSummary>,
+
+    /// 发现方式
+    pub detected_via: ChangeDetection,
+
+    /// 发现时间
+    pub detected_at: DateTime<Utc>,
+}
+
+impl AccountChangeEvent {
+    pub fn new(
+        uid: String,
+        kind: AccountChangeKind,
+        account: Option<AccountSummary>,
+        detected_via: ChangeDetection,
+    ) -> Self {
+        Self {
+            uid,
+            kind,
+            account,
+            detected_via,
+            detected_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::models::RedisConfigError;
+
+/// 默认key前缀
+pub const DEFAULT_KEY_PREFIX: &str = "weibo";
+
+/// Redis key命名空间
+///
+/// 所有key都由此生成,同一个Redis实例上的不同团队或环境
+/// (如staging与production) 使用不同前缀即可互不干扰。
+///
+/// key布局:
+/// - `{prefix}:cookies:{uid}`: 账户Cookies (Hash)
+/// - `{prefix}:history:{uid}`: 历史快照 (List)
+/// - `{prefix}:history:{uid}:seq`: 快照版本计数器
+/// - `{prefix}:tag:{tag}`: 拥有该标签的UID (Set)
+/// - `{prefix}:account_tags:{uid}`: 账户的标签 (Set)
+/// - `{prefix}:tags`: 所有出现过的标签 (Set)
+/// - `{prefix}:lease:{uid}`: 账户租约 (Hash,带TTL)
+/// - `{prefix}:lease_cooldown:{uid}`: 释放后的冷却标记 (带TTL)
+/// - `{prefix}:lease_usage`: 账户最近获取时间 (ZSet,毫秒)
+/// - `{prefix}:lease_cursor`: 轮转策略上次发放的UID
+/// - `{prefix}:audit`: 审计日志 (Stream)
+///
+/// Redis集群中多key的Lua脚本要求所有key位于同一槽位。开启hash-tag后前缀写作
+/// `{weibo}:cookies:123`,集群只按花括号内的 `weibo` 计算槽位,整个命名空间落在同一槽位。
+/// 按UID分槽看似更分散,但标签索引、租约轮转等脚本本就跨账户访问,
+/// 账户规模也远不到需要分片的程度。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyNamespace {
+    prefix: String,
+    #[serde(default)]
+    hash_tag: bool,
+}
+
+impl KeyNamespace {
+    /// 创建命名空间
+    ///
+    /// 前缀末尾的 `:` 会被去除,`weibo` 与 `weibo:` 等价。
+    ///
+    /// # 错误
+    /// 返回 `RedisConfigError::InvalidNamespace` 如果前缀为空、包含空白、glob通配符或花括号
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::KeyNamespace;
+    ///
+    /// let ns = KeyNamespace::new("team-a:").unwrap();
+    /// assert_eq!(ns.cookies_key("123"), "team-a:cookies:123");
+    /// assert!(KeyNamespace::new("bad prefix").is_err());
+    /// ```
+    pub fn new(prefix: &str) -> Result<Self, RedisConfigError> {
+        let prefix = prefix.trim().trim_end_matches(':');
+
+        if prefix.is_empty() {
+            return Err(RedisConfigError::InvalidNamespace("key前缀不能为空".to_string()));
+        }
+
+        if prefix
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '*' | '?' | '[' | ']' | '\\' | '{' | '}'))
+        {
+            return Err(RedisConfigError::InvalidNamespace(format!(
+                "key前缀包含非法字符: {}",
+                prefix
+            )));
+        }
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            hash_tag: false,
+        })
+    }
+
+    /// 前缀带上集群hash-tag (构建器模式)
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::KeyNamespace;
+    ///
+    /// let ns = KeyNamespace::new("weibo").unwrap().with_hash_tag();
+    /// assert_eq!(ns.cookies_key("123"), "{weibo}:cookies:123");
+    /// assert_eq!(ns.prefix(), "weibo");
+    /// ```
+    pub fn with_hash_tag(mut self) -> Self {
+        self.hash_tag = true;
+        self
+    }
+
+    /// 是否带集群hash-tag
+    pub fn is_hash_tagged(&self) -> bool {
+        self.hash_tag
+    }
+
+    /// 前缀 (不含末尾的 `:` 与hash-tag花括号)
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// 实际写在key开头的部分
+    fn root(&self) -> Cow<'_, str> {
+        if self.hash_tag {
+            Cow::Owned(format!("{{{}}}", self.prefix))
+        } else {
+            Cow::Borrowed(&self.prefix)
+        }
+    }
+
+    /// 账户Cookies key
+    pub fn cookies_key(&self, uid: &str) -> String {
+        format!("{}:cookies:{}", self.root(), uid)
+    }
+
+    /// 历史快照列表key
+    pub fn history_key(&self, uid: &str) -> String {
+        format!("{}:history:{}", self.root(), uid)
+    }
+
+    /// 历史版本号计数器key
+    pub fn history_seq_key(&self, uid: &str) -> String {
+        format!("{}:history:{}:seq", self.root(), uid)
+    }
+
+    /// 标签成员key
+    pub fn tag_key(&self, tag: &str) -> String {
+        format!("{}:tag:{}", self.root(), tag)
+    }
+
+    /// 账户标签key
+    pub fn account_tags_key(&self, uid: &str) -> String {
+        format!("{}:account_tags:{}", self.root(), uid)
+    }
+
+    /// 标签索引key
+    pub fn tag_index_key(&self) -> String {
+        format!("{}:tags", self.root())
+    }
+
+    /// 账户租约key
+    pub fn lease_key(&self, uid: &str) -> String {
+        format!("{}:lease:{}", self.root(), uid)
+    }
+
+    /// 租约冷却key
+    pub fn lease_cooldown_key(&self, uid: &str) -> String {
+        format!("{}:lease_cooldown:{}", self.root(), uid)
+    }
+
+    /// 租约使用记录key
+    pub fn lease_usage_key(&self) -> String {
+        format!("{}:lease_usage", self.root())
+    }
+
+    /// 轮转游标key
+    pub fn lease_cursor_key(&self) -> String {
+        format!("{}:lease_cursor", self.root())
+    }
+
+    /// 审计日志Stream key
+    pub fn audit_key(&self) -> String {
+        format!("{}:audit", self.root())
+    }
+
+    /// 匹配所有租约key的模式
+    pub fn lease_pattern(&self) -> String {
+        format!("{}:lease:*", self.root())
+    }
+
+    /// 从租约key中提取UID
+    pub fn uid_from_lease_key<'a>(&self, key: &'a str) -> Option<&'a str> {
+        key.strip_prefix(self.root().as_ref())
+            .and_then(|rest| rest.strip_prefix(":lease:"))
+    }
+
+    /// 订阅账户Cookies key变化的keyspace通知频道模式
+    pub fn cookies_keyspace_pattern(&self, db: i64) -> String {
+        format!("__keyspace@{}__:{}", db, self.cookies_pattern())
+    }
+
+    /// 从keyspace通知频道名中提取UID
+    pub fn uid_from_keyspace_channel<'a>(&self, db: i64, channel: &'a str) -> Option<&'a str> {
+        channel
+            .strip_prefix(&format!("__keyspace@{}__:", db))
+            .and_then(|key| self.uid_from_cookies_key(key))
+    }
+
+    /// 匹配所有账户Cookies key的SCAN/KEYS模式
+    pub fn cookies_pattern(&self) -> String {
+        format!("{}:cookies:*", self.root())
+    }
+
+    /// 匹配命名空间下所有key的模式 (用于迁移)
+    ///
+    /// 前缀可以包含 `:`,模式同样会匹配嵌套命名空间的key,需用 [`owns_key`](Self::owns_key) 过滤。
+    pub fn all_keys_pattern(&self) -> String {
+        format!("{}:*", self.root())
+    }
+
+    /// key是否为本命名空间的已知key
+    ///
+    /// `weibo:staging:cookies:1` 属于 `weibo:staging`,不属于 `weibo`。
+    /// 以UID结尾的key要求UID中不含 `:`,标签key无法区分,只看 `:tag:` 前缀。
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::KeyNamespace;
+    ///
+    /// let ns = KeyNamespace::new("weibo").unwrap();
+    /// assert!(ns.owns_key("weibo:cookies:1"));
+    /// assert!(ns.owns_key("weibo:history:1:seq"));
+    /// assert!(!ns.owns_key("weibo:staging:cookies:1"));
+    /// ```
+    pub fn owns_key(&self, key: &str) -> bool {
+        let Some(rest) = key
+            .strip_prefix(self.root().as_ref())
+            .and_then(|rest| rest.strip_prefix(':'))
+        else {
+            return false;
+        };
+
+        if matches!(rest, "tags" | "lease_usage" | "lease_cursor" | "audit") {
+            return true;
+        }
+        if rest.strip_prefix("tag:").is_some_and(|tag| !tag.is_empty()) {
+            return true;
+        }
+
+        let is_uid = |uid: &str| !uid.is_empty() && !uid.contains(':');
+        match rest.split_once(':') {
+            Some(("history", uid)) => is_uid(uid.strip_suffix(":seq").unwrap_or(uid)),
+            Some(("cookies" | "account_tags" | "lease" | "lease_cooldown", uid)) => is_uid(uid),
+            _ => false,
+        }
+    }
+
+    /// 从Cookies key中提取UID
+    pub fn uid_from_cookies_key<'a>(&self, key: &'a str) -> Option<&'a str> {
+        key.strip_prefix(self.root().as_ref())
+            .and_then(|rest| rest.strip_prefix(":cookies:"))
+    }
+
+    /// 将本命名空间下的key改写到另一个命名空间
+    ///
+    /// 不属于本命名空间的key (见 [`owns_key`](Self::owns_key)) 返回None。
+    pub fn rebase_key(&self, key: &str, target: &KeyNamespace) -> Option<String> {
+        if !self.owns_key(key) {
+            return None;
+        }
+        key.strip_prefix(self.root().as_ref())
+            .map(|rest| format!("{}{}", target.root(), rest))
+    }
+
+    /// 两个命名空间是否互相嵌套 (如 `weibo` 与 `weibo:staging`)
+    ///
+    /// 嵌套的命名空间之间迁移会让SCAN扫到刚写入的key,必须拒绝。
+    pub fn overlaps(&self, other: &KeyNamespace) -> bool {
+        let a = format!("{}:", self.root());
+        let b = format!("{}:", other.root());
+        a.starts_with(&b) || b.starts_with(&a)
+    }
+}
+
+impl Default for KeyNamespace {
+    fn default() -> Self {
+        Self {
+            prefix: DEFAULT_KEY_PREFIX.to_string(),
+            hash_tag: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_layout_matches_legacy_keys() {
+        let ns = KeyNamespace::default();
+        assert_eq!(ns.cookies_key("123"), "weibo:cookies:123");
+        assert_eq!(ns.history_key("123"), "weibo:history:123");
+        assert_eq!(ns.history_seq_key("123"), "weibo:history:123:seq");
+        assert_eq!(ns.cookies_pattern(), "weibo:cookies:*");
+    }
+
+    #[test]
+    fn test_invalid_prefixes_rejected() {
+        assert!(KeyNamespace::new("").is_err());
+        assert!(KeyNamespace::new(":").is_err());
+        assert!(KeyNamespace::new("a b").is_err());
+        assert!(KeyNamespace::new("team*").is_err());
+        assert!(matches!(
+            KeyNamespace::new("a b"),
+            Err(RedisConfigError::InvalidNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_uid_from_cookies_key() {
+        let ns = KeyNamespace::new("staging").unwrap();
+        assert_eq!(ns.uid_from_cookies_key("staging:cookies:42"), Some("42"));
+        assert_eq!(ns.uid_from_cookies_key("weibo:cookies:42"), None);
+        assert_eq!(ns.uid_from_cookies_key("staging:history:42"), None);
+    }
+
+    #[test]
+    fn test_lease_keys_do_not_collide() {
+        let ns = KeyNamespace::default();
+        assert_eq!(ns.lease_key("1"), "weibo:lease:1");
+        assert_eq!(ns.uid_from_lease_key("weibo:lease:1"), Some("1"));
+        assert_eq!(ns.uid_from_lease_key(&ns.lease_usage_key()), None);
+        assert_eq!(ns.uid_from_lease_key(&ns.lease_cooldown_key("1")), None);
+    }
+
+    #[test]
+    fn test_keyspace_channel() {
+        let ns = KeyNamespace::new("staging").unwrap();
+        assert_eq!(ns.cookies_keyspace_pattern(2), "__keyspace@2__:staging:cookies:*");
+        assert_eq!(
+            ns.uid_from_keyspace_channel(2, "__keyspace@2__:staging:cookies:42"),
+            Some("42")
+        );
+        assert_eq!(ns.uid_from_keyspace_channel(0, "__keyspace@2__:staging:cookies:42"), None);
+        assert_eq!(ns.uid_from_keyspace_channel(2, "__keyspace@2__:staging:history:42"), None);
+    }
+
+    #[test]
+    fn test_rebase_key() {
+        let from = KeyNamespace::new("weibo").unwrap();
+        let to = KeyNamespace::new("prod").unwrap();
+        assert_eq!(
+            from.rebase_key("weibo:history:1:seq", &to),
+            Some("prod:history:1:seq".to_string())
+        );
+        assert_eq!(from.rebase_key("weibox:cookies:1", &to), None);
+        assert_eq!(from.rebase_key("weibo:staging:cookies:1", &to), None);
+        assert_eq!(from.rebase_key("weibo:unknown", &to), None);
+    }
+
+    #[test]
+    fn test_owns_key_skips_nested_namespaces() {
+        let ns = KeyNamespace::new("app").unwrap();
+        for key in [
+            ns.cookies_key("1"),
+            ns.history_key("1"),
+            ns.history_seq_key("1"),
+            ns.tag_key("项目:a"),
+            ns.account_tags_key("1"),
+            ns.tag_index_key(),
+            ns.lease_key("1"),
+            ns.lease_cooldown_key("1"),
+            ns.lease_usage_key(),
+            ns.lease_cursor_key(),
+            ns.audit_key(),
+        ] {
+            assert!(ns.owns_key(&key), "{}", key);
+        }
+
+        let nested = KeyNamespace::new("app:tenant").unwrap();
+        assert!(!ns.owns_key(&nested.cookies_key("1")));
+        assert!(!ns.owns_key(&nested.history_seq_key("1")));
+        assert!(!ns.owns_key(&nested.audit_key()));
+        assert!(!ns.owns_key("app:cookies:"));
+    }
+
+    #[test]
+    fn test_hash_tagged_layout() {
+        let ns = KeyNamespace::new("staging").unwrap().with_hash_tag();
+        assert_eq!(ns.history_seq_key("1"), "{staging}:history:1:seq");
+        assert_eq!(ns.tag_index_key(), "{staging}:tags");
+        assert_eq!(ns.cookies_pattern(), "{staging}:cookies:*");
+        assert_eq!(ns.uid_from_cookies_key("{staging}:cookies:42"), Some("42"));
+        assert_eq!(ns.uid_from_cookies_key("staging:cookies:42"), None);
+        assert_eq!(ns.uid_from_lease_key("{staging}:lease:42"), Some("42"));
+        assert_eq!(
+            ns.rebase_key("{staging}:audit", &KeyNamespace::new("prod").unwrap()),
+            Some("prod:audit".to_string())
+        );
+        assert!(KeyNamespace::new("{staging}").is_err());
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let weibo = KeyNamespace::new("weibo").unwrap();
+        assert!(weibo.overlaps(&KeyNamespace::new("weibo:staging").unwrap()));
+        assert!(weibo.overlaps(&KeyNamespace::new("weibo").unwrap()));
+        assert!(!weibo.overlaps(&KeyNamespace::new("weibox").unwrap()));
+    }
+}
@@ -1,9 +1,19 @@
 //! 数据模型模块
 //!
 //! 包含所有核心数据结构:
-//! - errors: 错误类型定义 (API、验证、存储、应用级错误)
+//! - errors: 错误类型定义 (API、验证、存储、导入导出、应用级错误)
 //! - login_session: 登录会话管理 (二维码状态追踪)
 //! - cookies_data: Cookies数据结构 (凭证存储与验证)
+//! - account_state: 账户生命周期状态 (失效、归档、回收站)
+//! - account_tags: 账户标签 (分组与筛选)
+//! - account_lease: 账户租约 (下游worker独占使用账户)
+//! - audit: 审计日志 (cookies读写记录)
+//! - cookies_history: Cookies历史快照 (版本记录与回滚)
+//! - key_namespace: Redis key命名空间 (可配置前缀)
+//! - redis_health: Redis健康报告 (版本、内存、持久化)
+//! - revalidation: 后台重新验证的范围、进度与汇总
+//! - spool: Redis不可用时的本地暂存队列条目
+//! - validation_outcome: Cookies验证结果分类 (过期、封禁、验证码、频率限制等)
 //!
 //! # 设计原则
 //!
@@ -14,23 +24,53 @@
 //! 4. **错误处理**: 所有验证返回 Result,提供完整上下文
 //! 5. **日志安全**: 敏感数据不记录到日志 (如 cookies 值)
 
+pub mod account_lease;
+pub mod account_state;
+pub mod account_tags;
+pub mod audit;
 pub mod cookies_data;
+pub mod cookies_history;
 pub mod dependency;
 pub mod errors;
 pub mod events;
 pub mod frontend_log;
+pub mod key_namespace;
 pub mod login_session;
 pub mod redis_config;
+pub mod redis_health;
+pub mod revalidation;
+pub mod spool;
+pub mod validation_outcome;
 
 // 重导出常用类型,简化外部引用
+pub use account_lease::{AccountLease, LeaseHolder, LeaseStrategy, LeasedAccount};
+pub use account_state::{AccountState, AccountStatus, AccountSummary};
+pub use account_tags::{TagCount, TagMatch};
+pub use audit::{AuditEntry, AuditEvent, AuditOperation, AuditOutcome, AuditPage, AuditQuery};
 pub use cookies_data::CookiesData;
+pub use cookies_history::{
+    CookiesDiff, CookiesSnapshot, CookiesSnapshotSummary, SnapshotSource, SnapshotValidation,
+};
 pub use dependency::{
     Dependency, DependencyLevel, CheckMethod, CheckStatus, DependencyCheckResult,
     InstallationTask, InstallStatus
 };
-pub use errors::{ApiError, StorageError, ValidationError};
+pub use errors::{
+    ApiError, LeaseError, RefreshError, SpoolError, StorageError, TransferError,
+    ValidationError, WeiboHttpError,
+};
+pub use key_namespace::KeyNamespace;
 pub use login_session::{LoginSession, QrCodeStatus};
-pub use redis_config::{RedisConfig, RedisConfigError};
+pub use redis_config::{RedisConfig, RedisConfigError, RedisNode, RedisTls, RedisTopology};
+pub use redis_health::{
+    HealthSeverity, HealthWarning, HealthWarningCode, PersistenceMode, RedisHealthReport,
+};
+pub use revalidation::{
+    RevalidationProgress, RevalidationScope, RevalidationStatus, RevalidationSummary,
+    RevalidationTrigger, ValidationStage,
+};
+pub use spool::{SpoolEntry, SpoolEntrySummary, SpoolFlushReport, SpoolStatus};
+pub use validation_outcome::{ValidationOutcome, ValidationRecord};
 
 /// 解析微博API返回码为二维码状态
 ///
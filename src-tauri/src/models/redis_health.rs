@@ -0,0 +1,423 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 内存使用超过maxmemory的该比例时提示
+const MEMORY_WARNING_RATIO: f64 = 0.9;
+
+/// 持久化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PersistenceMode {
+    /// 未开启任何持久化,重启后数据全部丢失
+    Disabled,
+    /// RDB快照
+    Rdb,
+    /// AOF日志
+    Aof,
+    /// RDB与AOF同时开启
+    RdbAndAof,
+    /// 无法判断 (INFO不可用,或 `CONFIG` 命令被禁用且AOF未开启)
+    Unknown,
+}
+
+/// 警告级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthSeverity {
+    /// 可能丢失账户数据,需要立即处理
+    Critical,
+    /// 建议关注
+    Warning,
+}
+
+/// 健康检查发现的问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthWarningCode {
+    PersistenceDisabled,
+    PersistenceUnknown,
+    PersistenceFailing,
+    EvictionMayDeleteAccounts,
+    MemoryNearLimit,
+    VersionUnsupported,
+    VersionUnknown,
+}
+
+/// 健康检查警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthWarning {
+    pub code: HealthWarningCode,
+    pub severity: HealthSeverity,
+    /// 面向用户的说明,包含后果与处理建议
+    pub message: String,
+}
+
+impl HealthWarning {
+    fn critical(code: HealthWarningCode, message: String) -> Self {
+        Self {
+            code,
+            severity: HealthSeverity::Critical,
+            message,
+        }
+    }
+
+    fn warning(code: HealthWarningCode, message: String) -> Self {
+        Self {
+            code,
+            severity: HealthSeverity::Warning,
+            message,
+        }
+    }
+}
+
+/// Redis健康报告
+///
+/// 由 `INFO` 输出构建,集群模式下描述命名空间所在槽位的主节点。
+/// 取不到的指标为None,不影响其余部分。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedisHealthReport {
+    /// 服务端版本 (`redis_version`)
+    pub server_version: Option<String>,
+
+    /// 应用要求的版本范围 (semver格式,如 `>=7.0.0`)
+    pub version_requirement: String,
+
+    /// 版本是否满足要求,版本未知时为false
+    pub version_supported: bool,
+
+    /// 服务端运行模式 (`standalone` / `sentinel` / `cluster`)
+    pub server_mode: Option<String>,
+
+    /// 已用内存 (字节)
+    pub used_memory_bytes: Option<u64>,
+
+    /// 已用内存 (可读格式,如 `1.50M`)
+    pub used_memory_human: Option<String>,
+
+    /// 内存上限 (字节),0表示不限制
+    pub maxmemory_bytes: Option<u64>,
+
+    /// 内存淘汰策略
+    pub maxmemory_policy: Option<String>,
+
+    /// 持久化方式
+    pub persistence: PersistenceMode,
+
+    /// 账户Cookies key数量 (含回收站)
+    pub cookies_key_count: Option<usize>,
+
+    /// 发现的问题,严重问题在前
+    pub warnings: Vec<HealthWarning>,
+}
+
+impl RedisHealthReport {
+    /// 从 `INFO` 输出构建报告
+    ///
+    /// # 参数
+    /// - `info`: `INFO` 命令输出,不可用时为None
+    /// - `save_config`: `CONFIG GET save` 的值,`CONFIG` 被禁用或无权限时为None
+    /// - `cookies_key_count`: 账户Cookies key数量
+    /// - `version_requirement`: 版本要求,与依赖检测使用同一个值
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::{PersistenceMode, RedisHealthReport};
+    ///
+    /// let info = "redis_version:7.2.4\r\naof_enabled:1\r\nmaxmemory:0\r\nmaxmemory_policy:noeviction\r\n";
+    /// let report = RedisHealthReport::from_info(Some(info), Some(""), Some(3), ">=7.0.0");
+    /// assert!(report.version_supported);
+    /// assert_eq!(report.persistence, PersistenceMode::Aof);
+    /// assert!(report.warnings.is_empty());
+    /// ```
+    pub fn from_info(
+        info: Option<&str>,
+        save_config: Option<&str>,
+        cookies_key_count: Option<usize>,
+        version_requirement: &str,
+    ) -> Self {
+        let fields = info.map(parse_info).unwrap_or_default();
+        let field = |name: &str| fields.get(name).map(|v| v.to_string());
+        let number = |name: &str| fields.get(name).and_then(|v| v.parse::<u64>().ok());
+
+        let server_version = field("redis_version");
+        let version_supported = server_version
+            .as_deref()
+            .is_some_and(|version| version_satisfies(version, version_requirement));
+
+        let aof_enabled = fields.get("aof_enabled").map(|v| *v == "1");
+        // save为空字符串表示关闭RDB
+        let rdb_enabled = save_config.map(|save| !save.trim().is_empty());
+        let persistence = match (rdb_enabled, aof_enabled) {
+            (Some(true), Some(true)) => PersistenceMode::RdbAndAof,
+            (_, Some(true)) => PersistenceMode::Aof,
+            (Some(true), Some(false)) => PersistenceMode::Rdb,
+            (Some(false), Some(false)) => PersistenceMode::Disabled,
+            _ => PersistenceMode::Unknown,
+        };
+
+        let mut report = Self {
+            server_version,
+            version_requirement: version_requirement.to_string(),
+            version_supported,
+            server_mode: field("redis_mode"),
+            used_memory_bytes: number("used_memory"),
+            used_memory_human: field("used_memory_human"),
+            maxmemory_bytes: number("maxmemory"),
+            maxmemory_policy: field("maxmemory_policy"),
+            persistence,
+            cookies_key_count,
+            warnings: Vec::new(),
+        };
+        report.warnings = report.collect_warnings(&fields);
+        report
+    }
+
+    /// 是否存在可能丢失账户数据的问题
+    pub fn has_critical_warnings(&self) -> bool {
+        self.warnings
+            .iter()
+            .any(|warning| warning.severity == HealthSeverity::Critical)
+    }
+
+    fn collect_warnings(&self, fields: &HashMap<&str, &str>) -> Vec<HealthWarning> {
+        use HealthWarningCode::*;
+        let mut warnings = Vec::new();
+
+        match self.persistence {
+            PersistenceMode::Disabled => warnings.push(HealthWarning::critical(
+                PersistenceDisabled,
+                "Redis未开启持久化 (RDB与AOF均关闭),重启后所有账户Cookies将丢失。请开启AOF (appendonly yes) 或配置RDB快照".to_string(),
+            )),
+            PersistenceMode::Unknown => warnings.push(HealthWarning::warning(
+                PersistenceUnknown,
+                "无法确认持久化配置 (CONFIG命令不可用且AOF未开启),请确认服务端已配置RDB快照".to_string(),
+            )),
+            _ => {}
+        }
+
+        if let Some(status) = fields.get("rdb_last_bgsave_status").filter(|s| **s != "ok") {
+            if matches!(
+                self.persistence,
+                PersistenceMode::Rdb | PersistenceMode::RdbAndAof
+            ) {
+                warnings.push(HealthWarning::critical(
+                    PersistenceFailing,
+                    format!(
+                        "最近一次RDB快照失败 (状态: {}),新保存的账户可能未落盘",
+                        status
+                    ),
+                ));
+            }
+        }
+        if let Some(status) = fields.get("aof_last_write_status").filter(|s| **s != "ok") {
+            if matches!(
+                self.persistence,
+                PersistenceMode::Aof | PersistenceMode::RdbAndAof
+            ) {
+                warnings.push(HealthWarning::critical(
+                    PersistenceFailing,
+                    format!(
+                        "最近一次AOF写入失败 (状态: {}),新保存的账户可能未落盘",
+                        status
+                    ),
+                ));
+            }
+        }
+
+        // 账户key带有过期时间,allkeys-* 与 volatile-* 策略都可能淘汰账户
+        let maxmemory = self.maxmemory_bytes.unwrap_or(0);
+        if let Some(policy) = self
+            .maxmemory_policy
+            .as_deref()
+            .filter(|p| *p != "noeviction")
+        {
+            if maxmemory > 0 {
+                warnings.push(HealthWarning::critical(
+                    EvictionMayDeleteAccounts,
+                    format!(
+                        "内存淘汰策略为 {},内存达到上限 ({} 字节) 时Redis会删除账户Cookies。请改为 noeviction 或为账户使用独立实例",
+                        policy, maxmemory
+                    ),
+                ));
+            } else {
+                warnings.push(HealthWarning::warning(
+                    EvictionMayDeleteAccounts,
+                    format!(
+                        "内存淘汰策略为 {},当前未设置maxmemory不会触发;一旦设置上限,账户Cookies可能被淘汰",
+                        policy
+                    ),
+                ));
+            }
+        }
+
+        if let Some(used) = self.used_memory_bytes.filter(|_| maxmemory > 0) {
+            if used as f64 >= maxmemory as f64 * MEMORY_WARNING_RATIO {
+                warnings.push(HealthWarning::warning(
+                    MemoryNearLimit,
+                    format!("内存使用接近上限 ({} / {} 字节)", used, maxmemory),
+                ));
+            }
+        }
+
+        match &self.server_version {
+            Some(version) if !self.version_supported => warnings.push(HealthWarning::warning(
+                VersionUnsupported,
+                format!(
+                    "Redis版本 {} 不满足要求 {}",
+                    version, self.version_requirement
+                ),
+            )),
+            None => warnings.push(HealthWarning::warning(
+                VersionUnknown,
+                "无法读取Redis版本 (INFO命令不可用)".to_string(),
+            )),
+            _ => {}
+        }
+
+        warnings.sort_by_key(|warning| warning.severity != HealthSeverity::Critical);
+        warnings
+    }
+}
+
+/// 解析 `INFO` 输出的 `field:value` 行,忽略 `# Section` 标题与空行
+fn parse_info(info: &str) -> HashMap<&str, &str> {
+    info.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .collect()
+}
+
+/// 版本是否满足semver要求
+fn version_satisfies(version: &str, requirement: &str) -> bool {
+    match (
+        semver::Version::parse(version),
+        semver::VersionReq::parse(requirement),
+    ) {
+        (Ok(version), Ok(requirement)) => requirement.matches(&version),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEALTHY_INFO: &str = "# Server\r\n\
+        redis_version:7.2.4\r\n\
+        redis_mode:standalone\r\n\
+        \r\n\
+        # Memory\r\n\
+        used_memory:1048576\r\n\
+        used_memory_human:1.00M\r\n\
+        maxmemory:0\r\n\
+        maxmemory_policy:noeviction\r\n\
+        \r\n\
+        # Persistence\r\n\
+        rdb_last_bgsave_status:ok\r\n\
+        aof_enabled:0\r\n\
+        aof_last_write_status:ok\r\n";
+
+    fn codes(report: &RedisHealthReport) -> Vec<HealthWarningCode> {
+        report.warnings.iter().map(|w| w.code).collect()
+    }
+
+    #[test]
+    fn test_healthy_server() {
+        let report = RedisHealthReport::from_info(
+            Some(HEALTHY_INFO),
+            Some("3600 1 300 100 60 10000"),
+            Some(12),
+            ">=7.0.0",
+        );
+        assert_eq!(report.server_version.as_deref(), Some("7.2.4"));
+        assert_eq!(report.server_mode.as_deref(), Some("standalone"));
+        assert_eq!(report.used_memory_bytes, Some(1048576));
+        assert_eq!(report.maxmemory_policy.as_deref(), Some("noeviction"));
+        assert_eq!(report.persistence, PersistenceMode::Rdb);
+        assert_eq!(report.cookies_key_count, Some(12));
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn test_persistence_disabled_is_critical() {
+        let report = RedisHealthReport::from_info(Some(HEALTHY_INFO), Some(""), Some(1), ">=7.0.0");
+        assert_eq!(report.persistence, PersistenceMode::Disabled);
+        assert!(report.has_critical_warnings());
+        assert_eq!(codes(&report), vec![HealthWarningCode::PersistenceDisabled]);
+    }
+
+    #[test]
+    fn test_unknown_persistence_when_config_unavailable() {
+        let report = RedisHealthReport::from_info(Some(HEALTHY_INFO), None, Some(1), ">=7.0.0");
+        assert_eq!(report.persistence, PersistenceMode::Unknown);
+        assert!(!report.has_critical_warnings());
+
+        let aof = HEALTHY_INFO.replace("aof_enabled:0", "aof_enabled:1");
+        let report = RedisHealthReport::from_info(Some(&aof), None, Some(1), ">=7.0.0");
+        assert_eq!(report.persistence, PersistenceMode::Aof);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_eviction_policy_with_limit_is_critical() {
+        let info = HEALTHY_INFO
+            .replace("maxmemory:0", "maxmemory:1000000")
+            .replace(
+                "maxmemory_policy:noeviction",
+                "maxmemory_policy:volatile-lru",
+            )
+            .replace("used_memory:1048576", "used_memory:950000");
+        let report = RedisHealthReport::from_info(Some(&info), Some("900 1"), Some(1), ">=7.0.0");
+        assert_eq!(
+            codes(&report),
+            vec![
+                HealthWarningCode::EvictionMayDeleteAccounts,
+                HealthWarningCode::MemoryNearLimit
+            ]
+        );
+        assert_eq!(report.warnings[0].severity, HealthSeverity::Critical);
+
+        let unlimited = HEALTHY_INFO.replace(
+            "maxmemory_policy:noeviction",
+            "maxmemory_policy:allkeys-lru",
+        );
+        let report =
+            RedisHealthReport::from_info(Some(&unlimited), Some("900 1"), Some(1), ">=7.0.0");
+        assert_eq!(
+            codes(&report),
+            vec![HealthWarningCode::EvictionMayDeleteAccounts]
+        );
+        assert!(!report.has_critical_warnings());
+    }
+
+    #[test]
+    fn test_failing_snapshot_and_old_version() {
+        let info = HEALTHY_INFO
+            .replace("rdb_last_bgsave_status:ok", "rdb_last_bgsave_status:err")
+            .replace("redis_version:7.2.4", "redis_version:6.2.14");
+        let report = RedisHealthReport::from_info(Some(&info), Some("900 1"), Some(1), ">=7.0.0");
+        assert!(!report.version_supported);
+        assert_eq!(
+            codes(&report),
+            vec![
+                HealthWarningCode::PersistenceFailing,
+                HealthWarningCode::VersionUnsupported
+            ]
+        );
+    }
+
+    #[test]
+    fn test_info_unavailable() {
+        let report = RedisHealthReport::from_info(None, None, None, ">=7.0.0");
+        assert!(!report.version_supported);
+        assert_eq!(report.persistence, PersistenceMode::Unknown);
+        assert_eq!(
+            codes(&report),
+            vec![
+                HealthWarningCode::PersistenceUnknown,
+                HealthWarningCode::VersionUnknown
+            ]
+        );
+    }
+}
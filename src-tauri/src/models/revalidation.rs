@@ -0,0 +1,274 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::models::validation_outcome::ValidationOutcome;
+
+/// 重新验证的范围
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum RevalidationScope {
+    /// 所有未删除的账户
+    All,
+
+    /// 单个账户
+    Uid { uid: String },
+
+    /// 带有某个标签的账户
+    Tag { tag: String },
+
+    /// 指定的一组账户 (批量验证)
+    Uids { uids: Vec<String> },
+}
+
+impl RevalidationScope {
+    /// 由命令参数确定范围: 都不指定时为全部账户
+    ///
+    /// # 错误
+    /// 同时指定了UID和标签
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::RevalidationScope;
+    ///
+    /// assert_eq!(RevalidationScope::from_args(None, None), Ok(RevalidationScope::All));
+    /// assert_eq!(
+    ///     RevalidationScope::from_args(None, Some("项目A".to_string())),
+    ///     Ok(RevalidationScope::Tag { tag: "项目A".to_string() })
+    /// );
+    /// assert!(RevalidationScope::from_args(Some("1".to_string()), Some("项目A".to_string())).is_err());
+    /// ```
+    pub fn from_args(uid: Option<String>, tag: Option<String>) -> Result<Self, String> {
+        match (uid, tag) {
+            (None, None) => Ok(Self::All),
+            (Some(uid), None) => Ok(Self::Uid { uid }),
+            (None, Some(tag)) => Ok(Self::Tag { tag }),
+            (Some(_), Some(_)) => Err("UID和标签只能指定一个".to_string()),
+        }
+    }
+
+    /// 由批量验证的参数确定范围: 必须指定一组UID或一个标签
+    ///
+    /// 重复的UID只验证一次,保持首次出现的顺序。
+    ///
+    /// # 错误
+    /// 两者都未指定 (或UID列表为空),或同时指定
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::RevalidationScope;
+    ///
+    /// let uids = vec!["2".to_string(), "1".to_string(), "2".to_string()];
+    /// assert_eq!(
+    ///     RevalidationScope::from_list(Some(uids), None),
+    ///     Ok(RevalidationScope::Uids { uids: vec!["2".to_string(), "1".to_string()] })
+    /// );
+    /// assert!(RevalidationScope::from_list(Some(Vec::new()), None).is_err());
+    /// assert!(RevalidationScope::from_list(None, None).is_err());
+    /// ```
+    pub fn from_list(uids: Option<Vec<String>>, tag: Option<String>) -> Result<Self, String> {
+        let uids = uids.filter(|uids| !uids.is_empty());
+        match (uids, tag) {
+            (Some(uids), None) => {
+                let mut seen = HashSet::new();
+                let uids = uids
+                    .into_iter()
+                    .filter(|uid| seen.insert(uid.clone()))
+                    .collect();
+                Ok(Self::Uids { uids })
+            }
+            (None, Some(tag)) => Ok(Self::Tag { tag }),
+            (None, None) => Err("需要指定UID列表或标签".to_string()),
+            (Some(_), Some(_)) => Err("UID列表和标签只能指定一个".to_string()),
+        }
+    }
+}
+
+/// 重新验证的发起方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevalidationTrigger {
+    /// 后台定时任务
+    Scheduled,
+
+    /// 前端手动发起
+    Manual,
+}
+
+/// 单个账户的验证阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStage {
+    /// 取得并发名额,开始验证
+    Started,
+
+    /// 验证完成 (或跳过)
+    Finished,
+}
+
+/// 单个账户的验证进度
+///
+/// 后台与手动重新验证只推送 `Finished` 阶段 (`revalidation_progress` 事件),
+/// 批量验证两个阶段都推送 (`validation_progress` 事件)。
+#[derive(Debug, Clone, Serialize)]
+pub struct RevalidationProgress {
+    /// 本轮验证ID
+    pub run_id: String,
+
+    /// 微博用户ID
+    pub uid: String,
+
+    pub stage: ValidationStage,
+
+    /// 验证结果,尚未完成或账户已不存在、无法读取时为None
+    pub outcome: Option<ValidationOutcome>,
+
+    /// 失败或跳过的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// 从开始到完成的耗时 (毫秒),`Started` 阶段为None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+
+    /// 本轮已完成的账户数 (`Finished` 阶段含本账户)
+    pub completed: usize,
+
+    /// 本轮账户总数
+    pub total: usize,
+}
+
+/// 一轮重新验证的汇总 (`revalidation_finished` 事件)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevalidationSummary {
+    pub run_id: String,
+    pub trigger: RevalidationTrigger,
+    pub scope: RevalidationScope,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// 本轮账户总数
+    pub total: usize,
+
+    /// 仍然有效的账户数
+    pub valid: usize,
+
+    /// 验证未通过的账户 (过期、封禁、锁定等),按UID排序
+    pub failed: Vec<String>,
+
+    /// 网络错误、超时等无法判断的账户数 (不改变已记录的结果)
+    pub inconclusive: usize,
+
+    /// 验证前已被删除或无法读取的账户数
+    pub skipped: usize,
+
+    /// 各验证结果的账户数
+    pub outcomes: HashMap<ValidationOutcome, usize>,
+
+    /// 本轮被取消,未完成的账户没有验证
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+impl RevalidationSummary {
+    /// 开始一轮验证
+    pub fn start(
+        run_id: String,
+        trigger: RevalidationTrigger,
+        scope: RevalidationScope,
+        total: usize,
+    ) -> Self {
+        Self {
+            run_id,
+            trigger,
+            scope,
+            started_at: Utc::now(),
+            finished_at: None,
+            total,
+            valid: 0,
+            failed: Vec::new(),
+            inconclusive: 0,
+            skipped: 0,
+            outcomes: HashMap::new(),
+            cancelled: false,
+        }
+    }
+
+    /// 计入一个账户的结果,None表示跳过
+    pub fn record(&mut self, uid: &str, outcome: Option<ValidationOutcome>) {
+        let Some(outcome) = outcome else {
+            self.skipped += 1;
+            return;
+        };
+
+        *self.outcomes.entry(outcome).or_default() += 1;
+        match outcome {
+            ValidationOutcome::Valid => self.valid += 1,
+            outcome if outcome.is_conclusive() => self.failed.push(uid.to_string()),
+            _ => self.inconclusive += 1,
+        }
+    }
+
+    /// 已完成的账户数
+    pub fn completed(&self) -> usize {
+        self.valid + self.failed.len() + self.inconclusive + self.skipped
+    }
+
+    /// 结束本轮验证
+    pub fn finish(&mut self) {
+        self.failed.sort();
+        self.finished_at = Some(Utc::now());
+    }
+}
+
+/// 后台重新验证状态 (供前端展示)
+#[derive(Debug, Clone, Serialize)]
+pub struct RevalidationStatus {
+    /// 后台验证间隔,未启用时为None
+    pub interval_secs: Option<u64>,
+
+    /// 同时验证的账户数上限
+    pub concurrency: usize,
+
+    /// 正在进行的验证轮数 (后台与手动可同时进行,共享并发上限)
+    pub running: usize,
+
+    /// 下次后台验证时间
+    pub next_run_at: Option<DateTime<Utc>>,
+
+    /// 最近完成的一轮验证
+    pub last_summary: Option<RevalidationSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_tally() {
+        let mut summary = RevalidationSummary::start(
+            "run".to_string(),
+            RevalidationTrigger::Manual,
+            RevalidationScope::All,
+            5,
+        );
+        summary.record("3", Some(ValidationOutcome::Banned));
+        summary.record("1", Some(ValidationOutcome::Valid));
+        summary.record("2", Some(ValidationOutcome::Expired));
+        summary.record("4", Some(ValidationOutcome::Timeout));
+        summary.record("5", None);
+        summary.finish();
+
+        assert_eq!(summary.completed(), 5);
+        assert_eq!(summary.valid, 1);
+        assert_eq!(summary.failed, vec!["2", "3"]);
+        assert_eq!(summary.inconclusive, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.outcomes[&ValidationOutcome::Timeout], 1);
+        assert!(summary.finished_at.is_some());
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["scope"]["scope"], "all");
+        assert_eq!(json["outcomes"]["banned"], 1);
+    }
+}
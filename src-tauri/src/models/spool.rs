@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CookiesData, SnapshotSource, SnapshotValidation};
+
+/// 本地暂存的待保存登录
+///
+/// Redis不可用时扫码得到的Cookies先加密写入本地队列,恢复后按原参数重新保存。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpoolEntry {
+    /// 条目ID (同时是文件名)
+    pub id: String,
+
+    /// 待保存的Cookies
+    pub cookies: CookiesData,
+
+    /// 保存时使用的快照来源
+    pub source: SnapshotSource,
+
+    /// 保存时使用的验证结果
+    pub validation: SnapshotValidation,
+
+    /// 进入队列的时间
+    pub spooled_at: DateTime<Utc>,
+
+    /// 已重试次数 (不含最初失败的那次)
+    pub attempts: u32,
+
+    /// 最近一次失败原因
+    pub last_error: Option<String>,
+}
+
+impl SpoolEntry {
+    /// 创建队列条目
+    ///
+    /// ID以微秒时间戳开头,按文件名排序即按进入队列的先后排序。
+    pub fn new(
+        cookies: CookiesData,
+        source: SnapshotSource,
+        validation: SnapshotValidation,
+        error: impl Into<String>,
+    ) -> Self {
+        let spooled_at = Utc::now();
+        Self {
+            id: format!(
+                "{:016}-{}",
+                spooled_at.timestamp_micros(),
+                uuid::Uuid::new_v4().simple()
+            ),
+            cookies,
+            source,
+            validation,
+            spooled_at,
+            attempts: 0,
+            last_error: Some(error.into()),
+        }
+    }
+
+    /// 前端展示用摘要 (不含cookies值)
+    pub fn summary(&self) -> SpoolEntrySummary {
+        SpoolEntrySummary {
+            id: self.id.clone(),
+            uid: self.cookies.uid.clone(),
+            screen_name: self.cookies.screen_name.clone(),
+            spooled_at: self.spooled_at,
+            attempts: self.attempts,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// 队列条目摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpoolEntrySummary {
+    pub id: String,
+    pub uid: String,
+    pub screen_name: Option<String>,
+    pub spooled_at: DateTime<Utc>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+/// 队列状态
+#[derive(Debug, Clone, Serialize)]
+pub struct SpoolStatus {
+    /// 待保存的条目 (按进入队列的先后排序)
+    pub pending: Vec<SpoolEntrySummary>,
+
+    /// 下次自动重试时间 (队列为空时为None)
+    pub next_retry_at: Option<DateTime<Utc>>,
+
+    /// 上次成功清空队列的时间
+    pub last_flushed_at: Option<DateTime<Utc>>,
+}
+
+/// 一次重试的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpoolFlushReport {
+    /// 成功写入Redis的UID
+    pub saved: Vec<String>,
+
+    /// Redis中已有更新登录而放弃的UID
+    pub superseded: Vec<String>,
+
+    /// 本次因连接问题仍然失败、留待下次重试的条目数
+    pub failed: usize,
+
+    /// Redis拒绝保存而隔离的UID (不再重试)
+    #[serde(default)]
+    pub quarantined: Vec<String>,
+
+    /// 队列中剩余的条目数
+    pub remaining: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_ids_sort_by_spool_time() {
+        let cookies = CookiesData::new("1".to_string(), HashMap::new());
+        let first = SpoolEntry::new(
+            cookies.clone(),
+            SnapshotSource::QrLogin,
+            SnapshotValidation::TrustedServer,
+            "Redis连接失败",
+        );
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = SpoolEntry::new(
+            cookies,
+            SnapshotSource::QrLogin,
+            SnapshotValidation::TrustedServer,
+            "Redis连接失败",
+        );
+
+        assert!(first.id < second.id);
+        assert_eq!(first.summary().last_error.as_deref(), Some("Redis连接失败"));
+    }
+}
@@ -0,0 +1,321 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::errors::ValidationError;
+
+/// Cookies验证结果分类
+///
+/// 区分"cookies无效"的具体原因,界面据此给出不同的处理建议:
+/// 过期需要重新扫码,频率限制只需稍后重试,封禁的账户重新登录也无济于事。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationOutcome {
+    /// cookies有效
+    Valid,
+
+    /// cookies已过期或已登出 → 重新扫码登录
+    Expired,
+
+    /// 账户被封禁 → 人工处理,重新登录无效
+    Banned,
+
+    /// 账户被锁定或需要安全验证 → 在微博完成解锁后重新登录
+    Locked,
+
+    /// 遇到验证码 → 稍后重试或在浏览器中完成验证
+    CaptchaRequired,
+
+    /// 请求过于频繁 → 稍后重试
+    RateLimited,
+
+    /// 网络错误,无法判断cookies状态 → 检查网络后重试
+    NetworkError,
+
+    /// 验证超时,无法判断cookies状态 → 稍后重试
+    Timeout,
+
+    /// 验证器自身故障 (脚本无法启动、进程崩溃),与账户无关
+    ValidatorError,
+
+    /// 无法识别的失败
+    Unknown,
+}
+
+impl ValidationOutcome {
+    /// Redis中存储的字符串形式
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Expired => "expired",
+            Self::Banned => "banned",
+            Self::Locked => "locked",
+            Self::CaptchaRequired => "captcha_required",
+            Self::RateLimited => "rate_limited",
+            Self::NetworkError => "network_error",
+            Self::Timeout => "timeout",
+            Self::ValidatorError => "validator_error",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// 从Redis字段解析,未知值返回None
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "valid" => Some(Self::Valid),
+            "expired" => Some(Self::Expired),
+            "banned" => Some(Self::Banned),
+            "locked" => Some(Self::Locked),
+            "captcha_required" => Some(Self::CaptchaRequired),
+            "rate_limited" => Some(Self::RateLimited),
+            "network_error" => Some(Self::NetworkError),
+            "timeout" => Some(Self::Timeout),
+            "validator_error" => Some(Self::ValidatorError),
+            "unknown" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+
+    /// 结果是否反映账户本身的状态
+    ///
+    /// 网络错误、超时和验证器故障说明不了cookies是否有效,
+    /// 稍后重试即可,不应据此改变对账户的判断。
+    pub fn is_conclusive(&self) -> bool {
+        !matches!(
+            self,
+            Self::NetworkError | Self::Timeout | Self::ValidatorError
+        )
+    }
+
+    /// 结果是否说明账户已无法使用 (过期、封禁、锁定)
+    ///
+    /// 记录这类结果时正常状态的账户被自动标记为失效,保留数据而不是随TTL过期。
+    pub fn invalidates_account(&self) -> bool {
+        matches!(self, Self::Expired | Self::Banned | Self::Locked)
+    }
+
+    /// 根据验证器的原始输出分类失败原因
+    ///
+    /// # 参数
+    /// - `status`: HTTP状态码 (如有)
+    /// - `redirect`: 重定向目标或最终URL (如有)
+    /// - `message`: 接口返回的错误信息
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::ValidationOutcome;
+    ///
+    /// assert_eq!(
+    ///     ValidationOutcome::classify(Some(429), None, ""),
+    ///     ValidationOutcome::RateLimited
+    /// );
+    /// assert_eq!(
+    ///     ValidationOutcome::classify(Some(302), Some("https://passport.weibo.com/sso/signin"), ""),
+    ///     ValidationOutcome::Expired
+    /// );
+    /// assert_eq!(
+    ///     ValidationOutcome::classify(Some(200), None, "您的账号已被冻结"),
+    ///     ValidationOutcome::Banned
+    /// );
+    /// ```
+    pub fn classify(status: Option<u16>, redirect: Option<&str>, message: &str) -> Self {
+        if status == Some(429) {
+            return Self::RateLimited;
+        }
+
+        let text = format!("{} {}", redirect.unwrap_or_default(), message).to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| text.contains(word));
+
+        // 顺序有意义: 封禁/锁定页面也常带有"登录"字样
+        if mentions(&["频繁", "rate limit", "too many"]) {
+            Self::RateLimited
+        } else if mentions(&["验证码", "captcha", "geetest"]) {
+            Self::CaptchaRequired
+        } else if mentions(&["封禁", "封号", "冻结", "banned", "suspended", "frozen"]) {
+            Self::Banned
+        } else if mentions(&[
+            "锁定",
+            "locked",
+            "security.weibo.com",
+            "安全验证",
+            "账号异常",
+            "帐号异常",
+        ]) {
+            Self::Locked
+        } else if matches!(status, Some(301..=303 | 307 | 308 | 401))
+            || mentions(&[
+                "passport.weibo",
+                "login",
+                "signin",
+                "未登录",
+                "过期",
+                "expired",
+            ])
+        {
+            Self::Expired
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// 验证失败对应的结果
+    ///
+    /// # 示例
+    /// ```
+    /// use weibo_login::models::{ValidationError, ValidationOutcome};
+    ///
+    /// let err = ValidationError::Timeout { seconds: 30 };
+    /// assert_eq!(ValidationOutcome::from_error(&err), ValidationOutcome::Timeout);
+    /// ```
+    pub fn from_error(error: &ValidationError) -> Self {
+        match error {
+            ValidationError::Rejected { outcome, .. } => *outcome,
+            ValidationError::RequestFailed(_) => Self::NetworkError,
+            ValidationError::Timeout { .. } => Self::Timeout,
+            ValidationError::PlaywrightFailed(_)
+            | ValidationError::WorkerCrashed(_)
+            | ValidationError::RateLimitExceeded { .. } => {
+                Self::ValidatorError
+            }
+            ValidationError::ProfileApiFailed { .. }
+            | ValidationError::MissingCookie(_)
+            | ValidationError::InvalidFormat(_)
+            | ValidationError::UidExtractionFailed(_)
+            | ValidationError::Cancelled => Self::Unknown,
+        }
+    }
+}
+
+/// 账户最近一次验证
+///
+/// 保存在Cookies Hash的 `last_outcome` / `last_outcome_at` 字段中。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationRecord {
+    pub outcome: ValidationOutcome,
+    pub at: DateTime<Utc>,
+}
+
+impl ValidationRecord {
+    /// 从Cookies Hash字段解析,从未记录过或字段损坏时返回None
+    pub fn from_fields(fields: &HashMap<String, String>) -> Option<Self> {
+        Some(Self {
+            outcome: ValidationOutcome::parse(fields.get("last_outcome")?)?,
+            at: fields
+                .get("last_outcome_at")
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ValidationOutcome; 10] = [
+        ValidationOutcome::Valid,
+        ValidationOutcome::Expired,
+        ValidationOutcome::Banned,
+        ValidationOutcome::Locked,
+        ValidationOutcome::CaptchaRequired,
+        ValidationOutcome::RateLimited,
+        ValidationOutcome::NetworkError,
+        ValidationOutcome::Timeout,
+        ValidationOutcome::ValidatorError,
+        ValidationOutcome::Unknown,
+    ];
+
+    #[test]
+    fn test_outcome_round_trip() {
+        for outcome in ALL {
+            assert_eq!(ValidationOutcome::parse(outcome.as_str()), Some(outcome));
+            let json = serde_json::to_string(&outcome).unwrap();
+            assert_eq!(json, format!("\"{}\"", outcome.as_str()));
+        }
+        assert_eq!(ValidationOutcome::parse("fine"), None);
+    }
+
+    #[test]
+    fn test_classify() {
+        use ValidationOutcome::*;
+
+        let cases = [
+            (Some(429), None, "", RateLimited),
+            (Some(200), None, "操作过于频繁,请稍后再试", RateLimited),
+            (Some(200), None, "请输入验证码", CaptchaRequired),
+            (
+                Some(302),
+                Some("https://passport.weibo.com/visitor/captcha"),
+                "",
+                CaptchaRequired,
+            ),
+            (Some(200), None, "该账号已被封禁", Banned),
+            (Some(200), None, "账号异常,请完成安全验证", Locked),
+            (
+                Some(302),
+                Some("https://security.weibo.com/account/unlock"),
+                "",
+                Locked,
+            ),
+            (
+                Some(302),
+                Some("https://passport.weibo.com/sso/signin"),
+                "",
+                Expired,
+            ),
+            (Some(401), None, "", Expired),
+            (None, None, "未登录", Expired),
+            (Some(500), None, "Internal Server Error", Unknown),
+        ];
+        for (status, redirect, message, expected) in cases {
+            assert_eq!(
+                ValidationOutcome::classify(status, redirect, message),
+                expected,
+                "{:?} {:?} {}",
+                status,
+                redirect,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_error() {
+        let rejected = ValidationError::Rejected {
+            outcome: ValidationOutcome::Banned,
+            message: "封禁".to_string(),
+        };
+        assert_eq!(
+            ValidationOutcome::from_error(&rejected),
+            ValidationOutcome::Banned
+        );
+        assert_eq!(
+            ValidationOutcome::from_error(&ValidationError::RequestFailed("dns".into())),
+            ValidationOutcome::NetworkError
+        );
+        assert_eq!(
+            ValidationOutcome::from_error(&ValidationError::WorkerCrashed("exit".into())),
+            ValidationOutcome::ValidatorError
+        );
+        assert!(!ValidationOutcome::NetworkError.is_conclusive());
+        assert!(ValidationOutcome::Expired.is_conclusive());
+        assert!(ValidationOutcome::Banned.invalidates_account());
+        assert!(!ValidationOutcome::RateLimited.invalidates_account());
+        assert!(!ValidationOutcome::Valid.invalidates_account());
+    }
+
+    #[test]
+    fn test_record_from_fields() {
+        let fields = HashMap::from([
+            ("last_outcome".to_string(), "rate_limited".to_string()),
+            ("last_outcome_at".to_string(), "1700000000".to_string()),
+        ]);
+        let record = ValidationRecord::from_fields(&fields).unwrap();
+        assert_eq!(record.outcome, ValidationOutcome::RateLimited);
+        assert_eq!(record.at.timestamp(), 1_700_000_000);
+
+        assert_eq!(ValidationRecord::from_fields(&HashMap::new()), None);
+        let corrupt = HashMap::from([("last_outcome".to_string(), "valid".to_string())]);
+        assert_eq!(ValidationRecord::from_fields(&corrupt), None);
+    }
+}
@@ -0,0 +1,468 @@
+//! 账户变化监听服务
+//!
+//! 其他工具修改或删除 `{prefix}:cookies:*`、或TTL到期时,通过Redis keyspace通知
+//! 及时发现并推送 `account_changed` / `account_expired` / `account_deleted` 事件。
+//! 服务端未开启 `notify-keyspace-events` (或禁用了CONFIG命令) 时退回定时轮询。
+//!
+//! 监听器维护一份账户摘要缓存,每次发现变化都先更新缓存再推送事件,
+//! 事件中携带变化后的摘要,前端缓存的列表可以直接据此更新。
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::RwLock;
+
+use crate::models::events::{AccountChangeEvent, AccountChangeKind, ChangeDetection};
+use crate::models::{AccountSummary, StorageError};
+use crate::services::RedisService;
+
+/// 默认轮询间隔 (同时也是通知模式下重新检测配置的间隔)
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 过期时间比较容差 (秒)
+const EXPIRY_TOLERANCE_SECONDS: i64 = 5;
+
+/// 合并连续通知的时间窗口: 一次保存会触发HSET、EXPIRE等多条通知
+const COALESCE_WINDOW: Duration = Duration::from_millis(200);
+
+/// 监听方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    /// 尚未启动或尚未完成首次检测
+    Starting,
+
+    /// 订阅keyspace通知
+    Notifications,
+
+    /// 定时轮询
+    Polling,
+}
+
+/// 监听状态 (供前端展示)
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchStatus {
+    pub mode: WatchMode,
+    pub cached_accounts: usize,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// 账户变化监听器
+pub struct AccountWatcher {
+    redis: Arc<RedisService>,
+    poll_interval: Duration,
+    cache: RwLock<HashMap<String, AccountSummary>>,
+    status: RwLock<WatchStatus>,
+}
+
+impl AccountWatcher {
+    /// 创建监听器,使用默认轮询间隔
+    pub fn new(redis: Arc<RedisService>) -> Self {
+        Self {
+            redis,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            cache: RwLock::new(HashMap::new()),
+            status: RwLock::new(WatchStatus {
+                mode: WatchMode::Starting,
+                cached_accounts: 0,
+                last_sync_at: None,
+                last_error: None,
+            }),
+        }
+    }
+
+    /// 设置轮询间隔 (构建器模式),最短1秒
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// 当前监听状态
+    pub async fn status(&self) -> WatchStatus {
+        self.status.read().await.clone()
+    }
+
+    /// 缓存的账户列表 (按UID排序)
+    pub async fn cached_accounts(&self) -> Vec<AccountSummary> {
+        let mut accounts: Vec<AccountSummary> = self.cache.read().await.values().cloned().collect();
+        accounts.sort_by(|a, b| a.uid.cmp(&b.uid));
+        accounts
+    }
+
+    /// 运行监听循环 (不会返回,随应用退出)
+    ///
+    /// 每轮先检测keyspace通知是否可用: 可用则订阅直到连接断开或配置被关闭,
+    /// 否则做一次全量比对后等待下一个轮询间隔。
+    pub async fn run(self: Arc<Self>, app: AppHandle) {
+        tracing::info!(轮询间隔秒 = %self.poll_interval.as_secs(), "账户变化监听已启动");
+
+        loop {
+            match self.redis.keyspace_notifications_enabled().await {
+                Ok(true) => {
+                    self.set_mode(WatchMode::Notifications).await;
+                    match self.watch_notifications(&app).await {
+                        Ok(()) => {
+                            tracing::warn!("keyspace通知已被关闭,切换为轮询");
+                            continue;
+                        }
+                        Err(e) => {
+                            tracing::warn!(错误 = %e, "keyspace通知订阅中断");
+                            self.set_error(&e).await;
+                        }
+                    }
+                }
+                Ok(false) => {
+                    self.set_mode(WatchMode::Polling).await;
+                    self.sync_logged(&app).await;
+                }
+                Err(e) => {
+                    tracing::debug!(错误 = %e, "无法读取notify-keyspace-events,使用轮询");
+                    self.set_mode(WatchMode::Polling).await;
+                    self.sync_logged(&app).await;
+                }
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// 订阅keyspace通知
+    ///
+    /// 订阅建立后先全量比对一次,补齐订阅之前错过的变化。
+    /// 连接断开返回错误;定期复查发现通知被关闭时返回Ok。
+    async fn watch_notifications(&self, app: &AppHandle) -> Result<(), StorageError> {
+        let namespace = self.redis.namespace();
+        let db = self.redis.database();
+
+        let mut pubsub = self.redis.pubsub().await?;
+        pubsub
+            .psubscribe(namespace.cookies_keyspace_pattern(db))
+            .await
+            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+        tracing::info!(频道 = %namespace.cookies_keyspace_pattern(db), "已订阅账户keyspace通知");
+
+        self.sync(app, ChangeDetection::Notification).await?;
+
+        let mut messages = pubsub.on_message();
+        let mut recheck = tokio::time::interval(self.poll_interval);
+        recheck.tick().await;
+
+        loop {
+            tokio::select! {
+                message = messages.next() => {
+                    let Some(message) = message else {
+                        return Err(StorageError::RedisConnectionFailed("订阅连接已断开".to_string()));
+                    };
+
+                    // 同一UID在窗口内的多条通知只保留最后一条
+                    let mut pending = BTreeMap::new();
+                    let mut collect = |message: redis::Msg| {
+                        let event: String = message.get_payload().unwrap_or_default();
+                        let uid = namespace.uid_from_keyspace_channel(db, message.get_channel_name());
+                        if let (Some(uid), Some(kind)) = (uid, classify_keyspace_event(&event)) {
+                            pending.insert(uid.to_string(), kind);
+                        }
+                    };
+                    collect(message);
+
+                    let deadline = tokio::time::Instant::now() + COALESCE_WINDOW;
+                    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, messages.next()).await {
+                        collect(message);
+                    }
+
+                    for (uid, kind) in pending {
+                        self.apply(app, uid, kind, ChangeDetection::Notification).await?;
+                    }
+                }
+                _ = recheck.tick() => {
+                    if !self.redis.keyspace_notifications_enabled().await.unwrap_or(false) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// 处理一条通知: 更新缓存并推送事件
+    async fn apply(
+        &self,
+        app: &AppHandle,
+        uid: String,
+        kind: AccountChangeKind,
+        detected_via: ChangeDetection,
+    ) -> Result<(), StorageError> {
+        let (kind, account) = match kind {
+            AccountChangeKind::Changed => match self.redis.account_summary(&uid).await? {
+                Some(account) => (AccountChangeKind::Changed, Some(account)),
+                // 通知到达前key已被删除
+                None => (AccountChangeKind::Deleted, None),
+            },
+            kind => (kind, None),
+        };
+
+        let (unchanged, cached) = {
+            let mut cache = self.cache.write().await;
+            let unchanged = match &account {
+                Some(account) => cache
+                    .insert(uid.clone(), account.clone())
+                    .is_some_and(|cached| same_account(&cached, account)),
+                None => cache.remove(&uid).is_none(),
+            };
+            (unchanged, cache.len())
+        };
+        self.status.write().await.cached_accounts = cached;
+
+        if !unchanged {
+            emit(
+                app,
+                AccountChangeEvent::new(uid, kind, account, detected_via),
+            );
+        }
+        Ok(())
+    }
+
+    /// 全量比对,失败只记录日志 (轮询模式下Redis暂时不可用是常态)
+    async fn sync_logged(&self, app: &AppHandle) {
+        if let Err(e) = self.sync(app, ChangeDetection::Polling).await {
+            tracing::warn!(错误 = %e, "账户列表同步失败");
+            self.set_error(&e).await;
+        }
+    }
+
+    /// 读取全部账户并与缓存比对,推送差异
+    ///
+    /// 首次同步只填充缓存,不推送事件。
+    async fn sync(
+        &self,
+        app: &AppHandle,
+        detected_via: ChangeDetection,
+    ) -> Result<(), StorageError> {
+        let accounts: HashMap<String, AccountSummary> = self
+            .redis
+            .list_accounts(&[])
+            .await?
+            .into_iter()
+            .map(|account| (account.uid.clone(), account))
+            .collect();
+
+        let first_sync = self.status.read().await.last_sync_at.is_none();
+        let cached = accounts.len();
+        let changes = {
+            let mut cache = self.cache.write().await;
+            let changes = diff_accounts(&cache, &accounts, Utc::now());
+            *cache = accounts;
+            changes
+        };
+
+        {
+            let mut status = self.status.write().await;
+            status.cached_accounts = cached;
+            status.last_sync_at = Some(Utc::now());
+            status.last_error = None;
+        }
+
+        if first_sync {
+            tracing::info!("账户缓存已初始化");
+            return Ok(());
+        }
+
+        for (uid, kind) in changes {
+            let account = self.cache.read().await.get(&uid).cloned();
+            emit(
+                app,
+                AccountChangeEvent::new(uid, kind, account, detected_via),
+            );
+        }
+        Ok(())
+    }
+
+    async fn set_mode(&self, mode: WatchMode) {
+        let mut status = self.status.write().await;
+        if status.mode != mode {
+            tracing::info!(监听方式 = ?mode, "账户变化监听方式切换");
+            status.mode = mode;
+        }
+    }
+
+    async fn set_error(&self, error: &StorageError) {
+        self.status.write().await.last_error = Some(error.to_string());
+    }
+}
+
+/// 推送账户变化事件
+fn emit(app: &AppHandle, event: AccountChangeEvent) {
+    tracing::info!(
+        用户ID = %event.uid,
+        变化类型 = ?event.kind,
+        发现方式 = ?event.detected_via,
+        "账户发生变化"
+    );
+    let _ = app.emit_all(event.kind.event_name(), event);
+}
+
+/// 将keyspace通知的事件名归类
+///
+/// 其余写命令产生的事件 (`hset`、`expire`、`persist`、`rename_to` 等) 都归为修改;
+/// `new` 之后必然跟着写入事件,忽略。
+///
+/// # 示例
+/// ```
+/// use weibo_login::models::events::AccountChangeKind;
+/// use weibo_login::services::account_watcher::classify_keyspace_event;
+///
+/// assert_eq!(classify_keyspace_event("expired"), Some(AccountChangeKind::Expired));
+/// assert_eq!(classify_keyspace_event("del"), Some(AccountChangeKind::Deleted));
+/// assert_eq!(classify_keyspace_event("hset"), Some(AccountChangeKind::Changed));
+/// ```
+pub fn classify_keyspace_event(event: &str) -> Option<AccountChangeKind> {
+    match event {
+        "expired" => Some(AccountChangeKind::Expired),
+        "del" | "unlink" | "evicted" | "rename_from" | "move_from" => {
+            Some(AccountChangeKind::Deleted)
+        }
+        "" | "new" => None,
+        _ => Some(AccountChangeKind::Changed),
+    }
+}
+
+/// 两次读取的摘要是否代表同一状态
+///
+/// `expires_at` 由读取时刻加剩余TTL算出,前后两次读取会有1秒左右的抖动,
+/// 差距在容差内视为未变化 (重新保存会把过期时间推后数天,不会被误判)。
+fn same_account(a: &AccountSummary, b: &AccountSummary) -> bool {
+    let expiry_close = match (a.expires_at, b.expires_at) {
+        (Some(x), Some(y)) => (x - y).num_seconds().abs() <= EXPIRY_TOLERANCE_SECONDS,
+        (x, y) => x == y,
+    };
+    expiry_close
+        && a.uid == b.uid
+        && a.screen_name == b.screen_name
+        && a.fetched_at == b.fetched_at
+        && a.status == b.status
+        && a.last_validation == b.last_validation
+        && a.validation == b.validation
+}
+
+/// 比对两次读取的账户列表
+///
+/// 消失的账户: 原过期时间已到则视为过期,否则视为删除。
+/// 新出现或摘要不同的账户视为修改。结果按UID排序。
+pub fn diff_accounts(
+    before: &HashMap<String, AccountSummary>,
+    after: &HashMap<String, AccountSummary>,
+    now: DateTime<Utc>,
+) -> Vec<(String, AccountChangeKind)> {
+    let mut changes: Vec<(String, AccountChangeKind)> = before
+        .iter()
+        .filter(|(uid, _)| !after.contains_key(*uid))
+        .map(|(uid, account)| {
+            let kind = if account
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now)
+            {
+                AccountChangeKind::Expired
+            } else {
+                AccountChangeKind::Deleted
+            };
+            (uid.clone(), kind)
+        })
+        .collect();
+
+    changes.extend(
+        after
+            .iter()
+            .filter(|(uid, account)| {
+                !before
+                    .get(*uid)
+                    .is_some_and(|cached| same_account(cached, account))
+            })
+            .map(|(uid, _)| (uid.clone(), AccountChangeKind::Changed)),
+    );
+
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AccountState, AccountStatus};
+
+    fn summary(uid: &str, fetched_at: i64, expires_at: Option<i64>) -> AccountSummary {
+        AccountSummary {
+            uid: uid.to_string(),
+            screen_name: None,
+            fetched_at: DateTime::from_timestamp(fetched_at, 0).unwrap(),
+            expires_at: expires_at.and_then(|ts| DateTime::from_timestamp(ts, 0)),
+            status: AccountStatus {
+                state: AccountState::Active,
+                state_changed_at: None,
+                state_reason: None,
+                purge_at: None,
+            },
+            last_validation: None,
+            validation: None,
+        }
+    }
+
+    fn map(accounts: Vec<AccountSummary>) -> HashMap<String, AccountSummary> {
+        accounts
+            .into_iter()
+            .map(|account| (account.uid.clone(), account))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_detects_expiry_deletion_and_changes() {
+        let now = DateTime::from_timestamp(1_000, 0).unwrap();
+        let before = map(vec![
+            summary("expired", 1, Some(900)),
+            summary("deleted", 1, Some(5_000)),
+            summary("changed", 1, None),
+            summary("same", 1, None),
+        ]);
+        let after = map(vec![
+            summary("changed", 2, None),
+            summary("same", 1, None),
+            summary("new", 1, None),
+        ]);
+
+        // 过期时间的读取抖动不算变化
+        let mut jittered = after.clone();
+        jittered.get_mut("same").unwrap().expires_at = DateTime::from_timestamp(2, 0);
+        let mut before = before;
+        before.get_mut("same").unwrap().expires_at = DateTime::from_timestamp(1, 0);
+
+        assert_eq!(
+            diff_accounts(&before, &jittered, now),
+            vec![
+                ("changed".to_string(), AccountChangeKind::Changed),
+                ("deleted".to_string(), AccountChangeKind::Deleted),
+                ("expired".to_string(), AccountChangeKind::Expired),
+                ("new".to_string(), AccountChangeKind::Changed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_keyspace_event() {
+        assert_eq!(
+            classify_keyspace_event("unlink"),
+            Some(AccountChangeKind::Deleted)
+        );
+        assert_eq!(
+            classify_keyspace_event("rename_to"),
+            Some(AccountChangeKind::Changed)
+        );
+        assert_eq!(
+            classify_keyspace_event("persist"),
+            Some(AccountChangeKind::Changed)
+        );
+        assert_eq!(classify_keyspace_event("new"), None);
+    }
+}
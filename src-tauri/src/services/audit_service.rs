@@ -0,0 +1,208 @@
+//! 审计日志服务
+//!
+//! 每次保存、查询、删除、导出和租约操作都追加到Redis Stream `{prefix}:audit`,
+//! 记录时间、操作、UID、调用方和结果。Stream按条数近似截断,保留最近的记录。
+
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::{AuditEntry, AuditEvent, AuditPage, AuditQuery, StorageError};
+use crate::services::RedisService;
+
+/// 默认保留的审计条目数量
+pub const DEFAULT_AUDIT_MAX_LEN: usize = 100_000;
+
+/// 默认每页条数
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// 每页最大条数
+const MAX_PAGE_SIZE: usize = 500;
+
+/// 单次查询最多扫描的批次,过滤条件很稀疏时避免扫完整个Stream
+const MAX_SCAN_BATCHES: usize = 20;
+
+/// 审计日志
+pub struct AuditLog {
+    redis: Arc<RedisService>,
+    max_len: usize,
+}
+
+impl AuditLog {
+    /// 创建审计日志,使用默认保留条数
+    pub fn new(redis: Arc<RedisService>) -> Self {
+        Self {
+            redis,
+            max_len: DEFAULT_AUDIT_MAX_LEN,
+        }
+    }
+
+    /// 设置保留条数上限
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len.max(1);
+        self
+    }
+
+    /// 记录审计事件
+    ///
+    /// 审计失败不影响业务操作本身,只记录警告日志。
+    pub async fn record(&self, event: AuditEvent) {
+        if let Err(e) = self.append(&event).await {
+            tracing::warn!(
+                操作 = %event.operation.as_str(),
+                用户ID = ?event.uid,
+                调用方 = %event.caller,
+                错误 = %e,
+                "审计日志写入失败"
+            );
+        }
+    }
+
+    /// 追加一条事件,返回Stream条目ID
+    pub async fn append(&self, event: &AuditEvent) -> Result<String, StorageError> {
+        let mut conn = self.redis.connection().await?;
+
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(self.redis.namespace().audit_key())
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(self.max_len)
+            .arg("*");
+        for (field, value) in event.to_fields(Utc::now()) {
+            cmd.arg(field).arg(value);
+        }
+
+        let id: String = cmd
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        Ok(id)
+    }
+
+    /// 分页查询 (按时间倒序)
+    ///
+    /// 从 `query.before` 之前开始向更早的方向扫描,直到凑满一页或到达Stream开头。
+    /// 过滤条件很稀疏时单次最多扫描有限批次,未凑满一页也会返回游标以便继续。
+    pub async fn page(&self, query: &AuditQuery) -> Result<AuditPage, StorageError> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let batch_size = (limit * 4).max(100);
+        let key = self.redis.namespace().audit_key();
+
+        let mut conn = self.redis.connection().await?;
+        let mut entries = Vec::with_capacity(limit);
+        let mut cursor = query.before.clone();
+
+        for _ in 0..MAX_SCAN_BATCHES {
+            // "(" 表示不包含游标本身 (Redis >= 6.2)
+            let end = cursor
+                .as_ref()
+                .map(|id| format!("({}", id))
+                .unwrap_or_else(|| "+".to_string());
+
+            let batch: Vec<(String, HashMap<String, String>)> = redis::cmd("XREVRANGE")
+                .arg(&key)
+                .arg(end)
+                .arg("-")
+                .arg("COUNT")
+                .arg(batch_size)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+            let exhausted = batch.len() < batch_size;
+            for (id, fields) in batch {
+                cursor = Some(id.clone());
+                let Some(entry) = AuditEntry::from_fields(id, &fields) else {
+                    continue;
+                };
+                if query.matches(&entry) {
+                    entries.push(entry);
+                    if entries.len() == limit {
+                        return Ok(AuditPage {
+                            entries,
+                            next_cursor: cursor,
+                        });
+                    }
+                }
+            }
+
+            if exhausted {
+                return Ok(AuditPage {
+                    entries,
+                    next_cursor: None,
+                });
+            }
+        }
+
+        Ok(AuditPage {
+            entries,
+            next_cursor: cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AuditOperation, KeyNamespace};
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_append_and_page_with_filters() {
+        let redis = Arc::new(
+            RedisService::new("redis://localhost:6379")
+                .unwrap()
+                .with_namespace(KeyNamespace::new("audit-test").unwrap()),
+        );
+        let audit = AuditLog::new(redis.clone()).with_max_len(1000);
+
+        for i in 0..5 {
+            audit
+                .append(
+                    &AuditEvent::new(AuditOperation::Query, "query_cookies")
+                        .with_uid(&format!("{}", i % 2)),
+                )
+                .await
+                .unwrap();
+        }
+        audit
+            .append(&AuditEvent::new(AuditOperation::Delete, "delete_cookies").with_uid("1"))
+            .await
+            .unwrap();
+
+        let page = audit
+            .page(&AuditQuery {
+                uid: Some("1".to_string()),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.entries[0].operation, AuditOperation::Delete);
+
+        let rest = audit
+            .page(&AuditQuery {
+                uid: Some("1".to_string()),
+                before: page.next_cursor,
+                limit: Some(10),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(rest
+            .entries
+            .iter()
+            .all(|entry| entry.operation == AuditOperation::Query));
+
+        let mut conn = redis.connection().await.unwrap();
+        let _: () = redis::cmd("DEL")
+            .arg(redis.namespace().audit_key())
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use crate::models::{RedisConfig, RedisConfigError};
+use crate::models::{RedisConfig, RedisConfigError, RedisNode, RedisTls, RedisTopology};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
@@ -115,13 +115,25 @@ impl ConfigService {
     /// 读取环境变量:
     /// - REDIS_HOST: Redis服务器地址 (默认: localhost)
     /// - REDIS_PORT: Redis端口 (默认: 6379)
+    /// - REDIS_USERNAME: ACL用户名 (可选,Redis 6+)
     /// - REDIS_PASSWORD: 认证密码 (可选)
     /// - REDIS_DATABASE: 数据库索引 (可选,0-15)
+    /// - REDIS_KEY_PREFIX: key前缀 (默认: weibo)
+    /// - REDIS_UNIX_SOCKET: unix socket绝对路径 (可选,设置后忽略主机与端口)
+    /// - REDIS_TLS: 是否使用TLS `true` / `false` (默认: false)
+    /// - REDIS_TLS_CA_CERT / REDIS_TLS_CLIENT_CERT / REDIS_TLS_CLIENT_KEY: PEM证书路径 (可选)
+    /// - REDIS_TLS_INSECURE: 跳过服务端证书校验 (默认: false,仅限测试环境)
+    /// - REDIS_MODE: 部署方式 `standalone` / `sentinel` / `cluster` (默认: standalone)
+    /// - REDIS_SENTINEL_MASTER: 哨兵监控的主节点名称 (哨兵模式)
+    /// - REDIS_SENTINELS: 哨兵节点,逗号分隔的 `host:port` (哨兵模式,默认端口26379)
+    /// - REDIS_SENTINEL_PASSWORD: 哨兵自身的密码 (可选)
+    /// - REDIS_CLUSTER_NODES: 种子节点,逗号分隔的 `host:port` (集群模式,默认端口6379)
     ///
     /// # 错误处理
     /// - 文件不存在时返回默认配置(不报错)
     /// - 文件读取失败时返回 IoError
-    /// - 端口/数据库索引格式错误时返回 InvalidUrl
+    /// - 端口/数据库索引格式错误、部署方式参数不完整时返回 InvalidUrl
+    /// - key前缀非法时返回 InvalidNamespace
     pub fn load_redis_config() -> Result<RedisConfig, RedisConfigError> {
         let env_path = Self::env_file_path()?;
 
@@ -137,7 +149,19 @@ impl ConfigService {
         // 读取文件内容
         let content = fs::read_to_string(&env_path)?;
         let vars = Self::parse_env_content(&content);
+        let config = Self::config_from_vars(&vars)?;
 
+        tracing::info!(
+            path = %env_path.display(),
+            config = %config.summary_for_logging(),
+            "已加载 Redis 配置"
+        );
+
+        Ok(config)
+    }
+
+    /// 从配置项解析 Redis 配置
+    fn config_from_vars(vars: &HashMap<String, String>) -> Result<RedisConfig, RedisConfigError> {
         // 解析 Redis 配置
         let host = vars
             .get("REDIS_HOST")
@@ -165,7 +189,21 @@ impl ConfigService {
             }
         }
 
-        let config = RedisConfig::new(host, port);
+        let mut config = RedisConfig::new(host, port);
+        config.topology = Self::topology_from_vars(vars)?;
+        // 保存时用空值清除,空值视为未设置
+        let non_empty = |key: &str| vars.get(key).filter(|v| !v.is_empty()).cloned();
+        config.username = non_empty("REDIS_USERNAME");
+        config.socket_path = non_empty("REDIS_UNIX_SOCKET");
+        if Self::parse_flag(vars, "REDIS_TLS")? {
+            config.tls = Some(RedisTls {
+                ca_cert: non_empty("REDIS_TLS_CA_CERT"),
+                client_cert: non_empty("REDIS_TLS_CLIENT_CERT"),
+                client_key: non_empty("REDIS_TLS_CLIENT_KEY"),
+                insecure: Self::parse_flag(vars, "REDIS_TLS_INSECURE")?,
+            });
+        }
+
         let config = if let Some(pwd) = password {
             config.with_password(pwd)
         } else {
@@ -176,16 +214,125 @@ impl ConfigService {
         } else {
             config
         };
+        let config = if let Some(prefix) = vars.get("REDIS_KEY_PREFIX") {
+            config.with_key_prefix(prefix.clone())
+        } else {
+            config
+        };
 
-        tracing::info!(
-            path = %env_path.display(),
-            config = %config.summary_for_logging(),
-            "已加载 Redis 配置"
-        );
+        // 验证key前缀与部署方式参数
+        config.validate()?;
 
         Ok(config)
     }
 
+    /// 解析部署方式
+    fn topology_from_vars(vars: &HashMap<String, String>) -> Result<RedisTopology, RedisConfigError> {
+        let mode = vars.get("REDIS_MODE").map(String::as_str).unwrap_or("standalone");
+
+        match mode {
+            "standalone" => Ok(RedisTopology::Standalone),
+            "sentinel" => Ok(RedisTopology::Sentinel {
+                master_name: vars.get("REDIS_SENTINEL_MASTER").cloned().unwrap_or_default(),
+                sentinels: Self::parse_nodes(vars.get("REDIS_SENTINELS"), 26379)?,
+                sentinel_password: vars.get("REDIS_SENTINEL_PASSWORD").cloned(),
+            }),
+            "cluster" => Ok(RedisTopology::Cluster {
+                nodes: Self::parse_nodes(vars.get("REDIS_CLUSTER_NODES"), 6379)?,
+            }),
+            other => Err(RedisConfigError::InvalidUrl(format!(
+                "未知的部署方式: {} (有效值: standalone, sentinel, cluster)",
+                other
+            ))),
+        }
+    }
+
+    /// 解析布尔配置项,缺省为false
+    fn parse_flag(vars: &HashMap<String, String>, key: &str) -> Result<bool, RedisConfigError> {
+        match vars.get(key).map(|v| v.to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("false") | Some("0") => Ok(false),
+            Some("true") | Some("1") => Ok(true),
+            Some(other) => Err(RedisConfigError::InvalidUrl(format!(
+                "{} 的值无效: {} (有效值: true, false)",
+                key, other
+            ))),
+        }
+    }
+
+    /// 解析逗号分隔的节点列表
+    fn parse_nodes(value: Option<&String>, default_port: u16) -> Result<Vec<RedisNode>, RedisConfigError> {
+        value
+            .map(|list| {
+                list.split(',')
+                    .filter(|node| !node.trim().is_empty())
+                    .map(|node| RedisNode::parse(node, default_port))
+                    .collect()
+            })
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    /// 将 Redis 配置转换为配置项
+    fn config_to_vars(config: &RedisConfig) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert("REDIS_HOST".to_string(), config.host.clone());
+        vars.insert("REDIS_PORT".to_string(), config.port.to_string());
+
+        if let Some(ref password) = config.password {
+            vars.insert("REDIS_PASSWORD".to_string(), password.clone());
+        }
+
+        if let Some(database) = config.database {
+            vars.insert("REDIS_DATABASE".to_string(), database.to_string());
+        }
+
+        vars.insert("REDIS_KEY_PREFIX".to_string(), config.key_prefix.clone());
+
+        // 可选项写入空值,清除文件中的旧值
+        let or_empty = |value: &Option<String>| value.clone().unwrap_or_default();
+        vars.insert("REDIS_USERNAME".to_string(), or_empty(&config.username));
+        vars.insert("REDIS_UNIX_SOCKET".to_string(), or_empty(&config.socket_path));
+        let tls = config.tls.clone();
+        vars.insert("REDIS_TLS".to_string(), tls.is_some().to_string());
+        let tls = tls.unwrap_or_default();
+        vars.insert("REDIS_TLS_CA_CERT".to_string(), or_empty(&tls.ca_cert));
+        vars.insert("REDIS_TLS_CLIENT_CERT".to_string(), or_empty(&tls.client_cert));
+        vars.insert("REDIS_TLS_CLIENT_KEY".to_string(), or_empty(&tls.client_key));
+        vars.insert("REDIS_TLS_INSECURE".to_string(), tls.insecure.to_string());
+
+        let join = |nodes: &[RedisNode]| {
+            nodes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        // 其他部署方式的配置项保留在文件中,切换回去时无需重新填写
+        match &config.topology {
+            RedisTopology::Standalone => {
+                vars.insert("REDIS_MODE".to_string(), "standalone".to_string());
+            }
+            RedisTopology::Sentinel {
+                master_name,
+                sentinels,
+                sentinel_password,
+            } => {
+                vars.insert("REDIS_MODE".to_string(), "sentinel".to_string());
+                vars.insert("REDIS_SENTINEL_MASTER".to_string(), master_name.clone());
+                vars.insert("REDIS_SENTINELS".to_string(), join(sentinels));
+                if let Some(password) = sentinel_password {
+                    vars.insert("REDIS_SENTINEL_PASSWORD".to_string(), password.clone());
+                }
+            }
+            RedisTopology::Cluster { nodes } => {
+                vars.insert("REDIS_MODE".to_string(), "cluster".to_string());
+                vars.insert("REDIS_CLUSTER_NODES".to_string(), join(nodes));
+            }
+        }
+
+        vars
+    }
+
     /// 保存 Redis 配置到 .env 文件
     ///
     /// 更新策略:
@@ -198,8 +345,13 @@ impl ConfigService {
     /// - `config`: 待保存的 Redis 配置
     ///
     /// # 错误处理
+    /// - 部署方式参数不完整时返回 InvalidUrl
+    /// - 前缀非法时返回 InvalidNamespace
     /// - 无法创建或写入文件时返回 IoError
     pub fn save_redis_config(config: &RedisConfig) -> Result<(), RedisConfigError> {
+        // 拒绝保存非法配置,避免下次启动失败
+        config.validate()?;
+
         let env_path = Self::env_file_path()?;
 
         // 读取原有内容(如果文件存在)
@@ -210,17 +362,7 @@ impl ConfigService {
         };
 
         // 准备更新的配置项
-        let mut updated_vars = HashMap::new();
-        updated_vars.insert("REDIS_HOST".to_string(), config.host.clone());
-        updated_vars.insert("REDIS_PORT".to_string(), config.port.to_string());
-
-        if let Some(ref password) = config.password {
-            updated_vars.insert("REDIS_PASSWORD".to_string(), password.clone());
-        }
-
-        if let Some(database) = config.database {
-            updated_vars.insert("REDIS_DATABASE".to_string(), database.to_string());
-        }
+        let updated_vars = Self::config_to_vars(config);
 
         // 序列化新内容
         let new_content = Self::serialize_env_content(&original_content, &updated_vars);
@@ -298,4 +440,91 @@ RUST_LOG=info
         assert!(result.contains("REDIS_HOST=localhost"));
         assert!(result.contains("REDIS_PORT=6379"));
     }
+
+    #[test]
+    fn test_sentinel_config_round_trip() {
+        let config = RedisConfig::sentinel(
+            "mymaster",
+            vec![RedisNode::new("s1", 26379), RedisNode::new("s2", 26380)],
+        )
+        .with_sentinel_password("sentinel_pw".to_string())
+        .with_password("master_pw".to_string())
+        .with_database(1);
+
+        let vars = ConfigService::config_to_vars(&config);
+        assert_eq!(vars.get("REDIS_MODE"), Some(&"sentinel".to_string()));
+        assert_eq!(vars.get("REDIS_SENTINELS"), Some(&"s1:26379,s2:26380".to_string()));
+
+        let parsed = ConfigService::config_from_vars(&vars).unwrap();
+        assert_eq!(parsed.topology, config.topology);
+        assert_eq!(parsed.password, config.password);
+        assert_eq!(parsed.database, Some(1));
+    }
+
+    #[test]
+    fn test_cluster_nodes_default_port() {
+        let content = "REDIS_MODE=cluster\nREDIS_CLUSTER_NODES=n1:7000, n2\n";
+        let vars = ConfigService::parse_env_content(content);
+        let config = ConfigService::config_from_vars(&vars).unwrap();
+
+        assert_eq!(
+            config.topology,
+            RedisTopology::Cluster {
+                nodes: vec![RedisNode::new("n1", 7000), RedisNode::new("n2", 6379)],
+            }
+        );
+        assert_eq!(config.namespace().unwrap().cookies_key("1"), "{weibo}:cookies:1");
+    }
+
+    #[test]
+    fn test_invalid_topology_rejected() {
+        let vars = ConfigService::parse_env_content("REDIS_MODE=sentinel\n");
+        assert!(ConfigService::config_from_vars(&vars).is_err(), "缺少主节点名称与哨兵");
+
+        let vars = ConfigService::parse_env_content("REDIS_MODE=replica\n");
+        assert!(ConfigService::config_from_vars(&vars).is_err());
+
+        let vars = ConfigService::parse_env_content("REDIS_MODE=cluster\nREDIS_CLUSTER_NODES=n1:abc\n");
+        assert!(ConfigService::config_from_vars(&vars).is_err());
+    }
+
+    #[test]
+    fn test_tls_and_acl_round_trip() {
+        let config = RedisConfig::new("redis.internal".to_string(), 6380)
+            .with_username("app".to_string())
+            .with_password("pw".to_string())
+            .with_tls(RedisTls {
+                ca_cert: Some("/etc/redis/ca.pem".to_string()),
+                client_cert: Some("/etc/redis/client.pem".to_string()),
+                client_key: Some("/etc/redis/client.key".to_string()),
+                insecure: false,
+            });
+
+        let vars = ConfigService::config_to_vars(&config);
+        assert_eq!(vars.get("REDIS_TLS"), Some(&"true".to_string()));
+        assert_eq!(ConfigService::config_from_vars(&vars).unwrap(), config);
+
+        // 关闭TLS、清除用户名后保存,旧值被空值覆盖
+        let plain = RedisConfig::new("redis.internal".to_string(), 6380);
+        let vars = ConfigService::config_to_vars(&plain);
+        assert_eq!(vars.get("REDIS_USERNAME"), Some(&String::new()));
+        let parsed = ConfigService::config_from_vars(&vars).unwrap();
+        assert!(parsed.username.is_none());
+        assert!(parsed.tls.is_none());
+    }
+
+    #[test]
+    fn test_unix_socket_from_vars() {
+        let vars = ConfigService::parse_env_content(
+            "REDIS_UNIX_SOCKET=/var/run/redis.sock\nREDIS_DATABASE=2\n",
+        );
+        let config = ConfigService::config_from_vars(&vars).unwrap();
+        assert_eq!(config.to_connection_url(), "unix:///var/run/redis.sock?db=2");
+
+        let vars = ConfigService::parse_env_content("REDIS_UNIX_SOCKET=redis.sock\n");
+        assert!(ConfigService::config_from_vars(&vars).is_err(), "相对路径");
+
+        let vars = ConfigService::parse_env_content("REDIS_TLS=yes\n");
+        assert!(ConfigService::config_from_vars(&vars).is_err());
+    }
 }
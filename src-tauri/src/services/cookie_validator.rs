@@ -0,0 +1,642 @@
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::models::{SnapshotValidation, ValidationError, ValidationOutcome};
+use crate::services::rate_limiter::{RateLimiter, RateOperation};
+use crate::services::{HttpValidator, ValidationService};
+
+/// Cookies验证器
+///
+/// 确认一组cookies当前可用,并返回其所属账户的 `(uid, screen_name)`。
+/// 保存、导入等流程只依赖此trait,具体实现由配置 `COOKIE_VALIDATOR` 选择:
+/// - [`ValidationService`](crate::services::ValidationService): Playwright浏览器验证 (默认)
+/// - [`HttpValidator`](crate::services::HttpValidator): 纯Rust HTTP验证,不依赖Node.js
+/// - 测试中使用 [`NoopValidator`],不发任何请求
+pub trait CookieValidator: Send + Sync {
+    /// 验证器名称,用于日志
+    fn name(&self) -> &'static str;
+
+    /// 验证cookies,成功时返回 `(uid, screen_name)`
+    fn validate_cookies<'a>(
+        &'a self,
+        cookies: &'a HashMap<String, String>,
+    ) -> BoxFuture<'a, Result<(String, String), ValidationError>>;
+
+    /// 终止验证器启动的子进程 (应用退出时调用),默认无事可做
+    fn shutdown(&self) {}
+}
+
+/// 验证器实现选择
+///
+/// 对应 `.env` 中的 `COOKIE_VALIDATOR`: `playwright` / `http`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorKind {
+    /// Playwright浏览器验证
+    #[default]
+    Playwright,
+    /// reqwest直接请求微博接口
+    Http,
+}
+
+impl ValidatorKind {
+    /// 配置值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Playwright => "playwright",
+            Self::Http => "http",
+        }
+    }
+}
+
+impl FromStr for ValidatorKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "playwright" => Ok(Self::Playwright),
+            "http" => Ok(Self::Http),
+            other => Err(format!("未知的验证器: {} (可选 playwright / http)", other)),
+        }
+    }
+}
+
+/// 扫码登录后的验证策略
+///
+/// 对应 `.env` 中的 `QR_LOGIN_VALIDATION`: `trust` / `sync` / `async`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginValidationPolicy {
+    /// 信任Playwright server (已通过VIP API确认UID),直接保存
+    #[default]
+    Trust,
+    /// 保存前验证,未通过则不保存
+    Sync,
+    /// 立即保存,后台验证后标记不符的账户
+    Async,
+}
+
+impl LoginValidationPolicy {
+    /// 配置值
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Trust => "trust",
+            Self::Sync => "sync",
+            Self::Async => "async",
+        }
+    }
+}
+
+impl FromStr for LoginValidationPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "trust" => Ok(Self::Trust),
+            "sync" => Ok(Self::Sync),
+            "async" => Ok(Self::Async),
+            other => Err(format!(
+                "未知的扫码登录验证策略: {} (可选 trust / sync / async)",
+                other
+            )),
+        }
+    }
+}
+
+/// 扫码登录结果的验证结论
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginCheck {
+    /// Cookies有效且属于扫码的账户
+    Verified,
+
+    /// Cookies被拒绝 (`outcome` 为拒绝原因),或属于另一个账户 (`outcome` 为None)
+    Mismatch {
+        outcome: Option<ValidationOutcome>,
+        message: String,
+    },
+
+    /// 网络错误、超时等,无法判断
+    Inconclusive { message: String },
+}
+
+impl LoginCheck {
+    /// 验证扫码登录得到的cookies,并确认其属于扫码的账户
+    pub async fn run(
+        validator: &dyn CookieValidator,
+        uid: &str,
+        cookies: &HashMap<String, String>,
+    ) -> Self {
+        match validator.validate_cookies(cookies).await {
+            Ok((validated_uid, _)) if validated_uid == uid => Self::Verified,
+            Ok((validated_uid, _)) => Self::Mismatch {
+                outcome: None,
+                message: format!(
+                    "验证得到的UID ({}) 与扫码结果 ({}) 不符",
+                    validated_uid, uid
+                ),
+            },
+            Err(e) => {
+                let outcome = ValidationOutcome::from_error(&e);
+                if outcome.is_conclusive() {
+                    Self::Mismatch {
+                        outcome: Some(outcome),
+                        message: e.to_string(),
+                    }
+                } else {
+                    Self::Inconclusive {
+                        message: e.to_string(),
+                    }
+                }
+            }
+        }
+    }
+
+    /// 对应的验证方式: 无法判断时按未验证保存
+    pub fn validation(&self) -> SnapshotValidation {
+        match self {
+            Self::Verified => SnapshotValidation::Verified,
+            Self::Mismatch { .. } => SnapshotValidation::Mismatch,
+            Self::Inconclusive { .. } => SnapshotValidation::Unverified,
+        }
+    }
+
+    /// 可记录到账户的验证结果
+    pub fn outcome(&self) -> Option<ValidationOutcome> {
+        match self {
+            Self::Verified => Some(ValidationOutcome::Valid),
+            Self::Mismatch { outcome, .. } => *outcome,
+            Self::Inconclusive { .. } => None,
+        }
+    }
+}
+
+/// 验证器配置
+///
+/// 读取环境变量:
+/// - COOKIE_VALIDATOR: `playwright` / `http` (默认: playwright)
+/// - PLAYWRIGHT_VALIDATION_SCRIPT: Playwright验证脚本路径
+/// - VALIDATION_TIMEOUT_SECS: 单次验证时限,秒 (默认: 30)
+/// - QR_LOGIN_VALIDATION: 扫码登录后的验证策略 `trust` / `sync` / `async` (默认: trust)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorConfig {
+    pub kind: ValidatorKind,
+    pub playwright_script: String,
+    pub timeout: Duration,
+    pub login_policy: LoginValidationPolicy,
+}
+
+impl ValidatorConfig {
+    /// 默认单次验证时限
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Playwright验证器配置
+    pub fn playwright(script: impl Into<String>) -> Self {
+        Self {
+            kind: ValidatorKind::Playwright,
+            playwright_script: script.into(),
+            timeout: Self::DEFAULT_TIMEOUT,
+            login_policy: LoginValidationPolicy::default(),
+        }
+    }
+
+    /// 从环境变量读取
+    ///
+    /// # 错误
+    /// 验证器名称或验证策略未知、时限不是正整数时返回说明
+    pub fn from_env(default_script: &str) -> Result<Self, String> {
+        Self::from_lookup(default_script, |key| std::env::var(key).ok())
+    }
+
+    /// 从任意键值来源读取 (环境变量或测试中的固定值)
+    ///
+    /// # 错误
+    /// 同 [`from_env`](Self::from_env)
+    pub fn from_lookup(
+        default_script: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, String> {
+        let var = |key: &str| lookup(key).unwrap_or_default();
+
+        let kind = var("COOKIE_VALIDATOR").parse()?;
+        let login_policy = var("QR_LOGIN_VALIDATION").parse()?;
+        let script = var("PLAYWRIGHT_VALIDATION_SCRIPT");
+        let timeout = match var("VALIDATION_TIMEOUT_SECS").trim() {
+            "" => Self::DEFAULT_TIMEOUT,
+            value => match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => Duration::from_secs(secs),
+                _ => {
+                    return Err(format!(
+                        "无效的 VALIDATION_TIMEOUT_SECS: {} (应为正整数秒)",
+                        value
+                    ))
+                }
+            },
+        };
+
+        Ok(Self {
+            kind,
+            playwright_script: if script.is_empty() {
+                default_script.to_string()
+            } else {
+                script
+            },
+            timeout,
+            login_policy,
+        })
+    }
+
+    /// 创建配置选择的验证器
+    pub fn build(&self) -> Arc<dyn CookieValidator> {
+        match self.kind {
+            ValidatorKind::Playwright => Arc::new(
+                ValidationService::new(self.playwright_script.clone()).with_timeout(self.timeout),
+            ),
+            ValidatorKind::Http => Arc::new(HttpValidator::new().with_timeout(self.timeout)),
+        }
+    }
+}
+
+/// 进行中的可取消验证
+///
+/// 前端发起验证时附带一个 `validation_id`,之后可通过 `cancel_validation` 取消。
+/// 取消只是不再等待结果: Playwright验证进程中的请求由其自身的超时结束。
+#[derive(Default)]
+pub struct ValidationCancellations {
+    /// 验证ID → (登记序号, 取消通道)
+    active: Mutex<HashMap<String, (u64, oneshot::Sender<()>)>>,
+    next_generation: AtomicU64,
+}
+
+/// 已登记的可取消验证
+///
+/// [`cancelled`](Self::cancelled) 在被取消时完成;丢弃 (完成、失败或调用方放弃) 时注销。
+pub struct Cancellation<'a> {
+    cancellations: &'a ValidationCancellations,
+    validation_id: &'a str,
+    generation: u64,
+    receiver: oneshot::Receiver<()>,
+}
+
+impl Cancellation<'_> {
+    /// 等待取消
+    pub async fn cancelled(&mut self) {
+        if (&mut self.receiver).await.is_err() {
+            // 已注销,不会再被取消
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+impl Drop for Cancellation<'_> {
+    fn drop(&mut self) {
+        // 已被取消的ID可能重新登记给了新的验证,只注销自己的登记
+        let mut active = self.cancellations.active.lock().unwrap();
+        if active
+            .get(self.validation_id)
+            .is_some_and(|(generation, _)| *generation == self.generation)
+        {
+            active.remove(self.validation_id);
+        }
+    }
+}
+
+impl ValidationCancellations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个可按 `validation_id` 取消的验证
+    ///
+    /// # 错误
+    /// - `ValidationError::InvalidFormat`: 同一 `validation_id` 的验证正在进行
+    pub fn register<'a>(
+        &'a self,
+        validation_id: &'a str,
+    ) -> Result<Cancellation<'a>, ValidationError> {
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let mut active = self.active.lock().unwrap();
+        if active.contains_key(validation_id) {
+            return Err(ValidationError::InvalidFormat(format!(
+                "验证ID {} 正在使用",
+                validation_id
+            )));
+        }
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        active.insert(validation_id.to_string(), (generation, cancel_tx));
+
+        Ok(Cancellation {
+            cancellations: self,
+            validation_id,
+            generation,
+            receiver: cancel_rx,
+        })
+    }
+
+    /// 运行验证,期间可按 `validation_id` 取消
+    ///
+    /// # 错误
+    /// - `ValidationError::Cancelled`: 被 [`cancel`](Self::cancel) 取消
+    /// - `ValidationError::InvalidFormat`: 同一 `validation_id` 的验证正在进行
+    /// - 其余错误来自验证本身
+    pub async fn run<T, F>(&self, validation_id: &str, validation: F) -> Result<T, ValidationError>
+    where
+        F: Future<Output = Result<T, ValidationError>>,
+    {
+        let mut cancellation = self.register(validation_id)?;
+
+        tokio::select! {
+            result = validation => result,
+            () = cancellation.cancelled() => {
+                tracing::info!(验证ID = %validation_id, "验证已被取消");
+                Err(ValidationError::Cancelled)
+            }
+        }
+    }
+
+    /// 取消验证,返回该验证是否仍在进行
+    pub fn cancel(&self, validation_id: &str) -> bool {
+        match self.active.lock().unwrap().remove(validation_id) {
+            Some((_, cancel)) => cancel.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// 经过全局限速器的验证器
+///
+/// 包装任意验证器: 每次验证先取得 `Validation` 令牌,排队过久时
+/// 返回 `RateLimitExceeded`,不会把请求发给微博。
+pub struct RateLimitedValidator {
+    inner: Arc<dyn CookieValidator>,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitedValidator {
+    pub fn new(inner: Arc<dyn CookieValidator>, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            inner,
+            rate_limiter,
+        }
+    }
+}
+
+impl CookieValidator for RateLimitedValidator {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn validate_cookies<'a>(
+        &'a self,
+        cookies: &'a HashMap<String, String>,
+    ) -> BoxFuture<'a, Result<(String, String), ValidationError>> {
+        Box::pin(async move {
+            self.rate_limiter.acquire(RateOperation::Validation).await?;
+            self.inner.validate_cookies(cookies).await
+        })
+    }
+
+    fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+}
+
+/// 不发任何请求的验证器
+///
+/// 用于测试: 固定接受并返回预设账户,或固定拒绝。
+pub struct NoopValidator {
+    identity: Option<(String, String)>,
+}
+
+impl NoopValidator {
+    /// 接受任何cookies,返回指定账户
+    pub fn accepting(uid: impl Into<String>, screen_name: impl Into<String>) -> Self {
+        Self {
+            identity: Some((uid.into(), screen_name.into())),
+        }
+    }
+
+    /// 拒绝任何cookies,如同cookies已过期
+    pub fn rejecting() -> Self {
+        Self { identity: None }
+    }
+}
+
+impl CookieValidator for NoopValidator {
+    fn name(&self) -> &'static str {
+        "noop"
+    }
+
+    fn validate_cookies<'a>(
+        &'a self,
+        _cookies: &'a HashMap<String, String>,
+    ) -> BoxFuture<'a, Result<(String, String), ValidationError>> {
+        let result = self
+            .identity
+            .clone()
+            .ok_or_else(|| ValidationError::Rejected {
+                outcome: ValidationOutcome::Expired,
+                message: "NoopValidator拒绝了cookies".to_string(),
+            });
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_kind_from_str() {
+        assert_eq!("".parse::<ValidatorKind>(), Ok(ValidatorKind::Playwright));
+        assert_eq!(
+            "Playwright".parse::<ValidatorKind>(),
+            Ok(ValidatorKind::Playwright)
+        );
+        assert_eq!(" http ".parse::<ValidatorKind>(), Ok(ValidatorKind::Http));
+        assert!("curl".parse::<ValidatorKind>().is_err());
+
+        for kind in [ValidatorKind::Playwright, ValidatorKind::Http] {
+            assert_eq!(kind.as_str().parse::<ValidatorKind>(), Ok(kind));
+        }
+    }
+
+    #[test]
+    fn test_login_policy_from_str() {
+        assert_eq!(
+            "".parse::<LoginValidationPolicy>(),
+            Ok(LoginValidationPolicy::Trust)
+        );
+        assert_eq!(
+            " ASYNC ".parse::<LoginValidationPolicy>(),
+            Ok(LoginValidationPolicy::Async)
+        );
+        assert!("later".parse::<LoginValidationPolicy>().is_err());
+
+        for policy in [
+            LoginValidationPolicy::Trust,
+            LoginValidationPolicy::Sync,
+            LoginValidationPolicy::Async,
+        ] {
+            assert_eq!(policy.as_str().parse::<LoginValidationPolicy>(), Ok(policy));
+        }
+    }
+
+    #[test]
+    fn test_config_from_lookup() {
+        let config = |pairs: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            ValidatorConfig::from_lookup("default.js", |key| vars.get(key).cloned())
+        };
+
+        assert_eq!(config(&[]), Ok(ValidatorConfig::playwright("default.js")));
+
+        let parsed = config(&[
+            ("COOKIE_VALIDATOR", "http"),
+            ("PLAYWRIGHT_VALIDATION_SCRIPT", "custom.js"),
+            ("VALIDATION_TIMEOUT_SECS", " 5 "),
+            ("QR_LOGIN_VALIDATION", "sync"),
+        ])
+        .unwrap();
+        assert_eq!(parsed.kind, ValidatorKind::Http);
+        assert_eq!(parsed.playwright_script, "custom.js");
+        assert_eq!(parsed.timeout, Duration::from_secs(5));
+        assert_eq!(parsed.login_policy, LoginValidationPolicy::Sync);
+
+        assert!(config(&[("VALIDATION_TIMEOUT_SECS", "0")]).is_err());
+        assert!(config(&[("VALIDATION_TIMEOUT_SECS", "soon")]).is_err());
+        assert!(config(&[("COOKIE_VALIDATOR", "curl")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_login_check() {
+        let cookies = HashMap::new();
+
+        let check = LoginCheck::run(&NoopValidator::accepting("1", "甲"), "1", &cookies).await;
+        assert_eq!(check, LoginCheck::Verified);
+        assert_eq!(check.validation(), SnapshotValidation::Verified);
+        assert_eq!(check.outcome(), Some(ValidationOutcome::Valid));
+
+        // 有效但属于另一个账户
+        let check = LoginCheck::run(&NoopValidator::accepting("2", "乙"), "1", &cookies).await;
+        assert!(matches!(check, LoginCheck::Mismatch { outcome: None, .. }));
+        assert_eq!(check.validation(), SnapshotValidation::Mismatch);
+        assert_eq!(check.outcome(), None);
+
+        let check = LoginCheck::run(&NoopValidator::rejecting(), "1", &cookies).await;
+        assert_eq!(check.outcome(), Some(ValidationOutcome::Expired));
+        assert_eq!(check.validation(), SnapshotValidation::Mismatch);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limited_validator() {
+        use crate::services::rate_limiter::{RateLimit, RateLimiterConfig};
+
+        let limiter = Arc::new(RateLimiter::new(RateLimiterConfig {
+            limits: HashMap::from([(
+                RateOperation::Validation,
+                Some(RateLimit::new(1, Duration::from_secs(60))),
+            )]),
+            max_wait: Duration::ZERO,
+        }));
+        let validator =
+            RateLimitedValidator::new(Arc::new(NoopValidator::accepting("1", "甲")), limiter);
+        assert_eq!(validator.name(), "noop");
+
+        let cookies = HashMap::new();
+        assert!(validator.validate_cookies(&cookies).await.is_ok());
+        let err = validator.validate_cookies(&cookies).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::RateLimitExceeded {
+                retry_after: Some(60)
+            }
+        ));
+        assert_eq!(
+            ValidationOutcome::from_error(&err),
+            ValidationOutcome::ValidatorError
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_validation() {
+        let cancellations = Arc::new(ValidationCancellations::new());
+
+        let running = {
+            let cancellations = cancellations.clone();
+            tokio::spawn(async move {
+                cancellations
+                    .run("v1", async {
+                        tokio::time::sleep(Duration::from_secs(30)).await;
+                        Ok(())
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // 同一ID不能重复使用
+        let duplicate = cancellations.run("v1", async { Ok(()) }).await;
+        assert!(matches!(duplicate, Err(ValidationError::InvalidFormat(_))));
+
+        assert!(cancellations.cancel("v1"));
+        assert!(matches!(
+            running.await.unwrap(),
+            Err(ValidationError::Cancelled)
+        ));
+        assert!(!cancellations.cancel("v1"), "取消后已注销");
+    }
+
+    #[tokio::test]
+    async fn test_finished_validation_is_unregistered() {
+        let cancellations = ValidationCancellations::new();
+        let result = cancellations.run("v1", async { Ok(42) }).await;
+        assert_eq!(result.unwrap(), 42);
+        assert!(!cancellations.cancel("v1"));
+        assert!(cancellations.active.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_retry_after_cancel_keeps_new_registration() {
+        let cancellations = ValidationCancellations::new();
+
+        let old = cancellations.register("v1").unwrap();
+        assert!(cancellations.cancel("v1"));
+
+        // 旧的验证尚未结束时同一ID被重新登记,旧登记注销时不能删掉新的
+        let mut new = cancellations.register("v1").unwrap();
+        drop(old);
+        assert!(cancellations.cancel("v1"));
+        assert_eq!(new.receiver.try_recv(), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_noop_validator() {
+        let cookies = HashMap::from([("SUB".to_string(), "x".to_string())]);
+
+        let validator: Box<dyn CookieValidator> =
+            Box::new(NoopValidator::accepting("123", "测试账户"));
+        assert_eq!(
+            validator.validate_cookies(&cookies).await.unwrap(),
+            ("123".to_string(), "测试账户".to_string())
+        );
+
+        let validator: Box<dyn CookieValidator> = Box::new(NoopValidator::rejecting());
+        assert!(matches!(
+            validator.validate_cookies(&cookies).await,
+            Err(ValidationError::Rejected {
+                outcome: ValidationOutcome::Expired,
+                ..
+            })
+        ));
+    }
+}
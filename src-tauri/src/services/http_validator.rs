@@ -0,0 +1,318 @@
+use futures::future::BoxFuture;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::models::ValidationError;
+use crate::services::cookie_validator::CookieValidator;
+use crate::services::weibo_http::{WeiboHttpClient, DEFAULT_BASE_URL, DEFAULT_TIMEOUT};
+
+/// HTTP Cookies验证器
+///
+/// 不启动浏览器,用 [`WeiboHttpClient`] 直接请求微博接口:
+/// 1. `GET /ajax/config`: 确认已登录,取得uid
+/// 2. `GET /ajax/profile/info?uid=`: 取得昵称
+///
+/// 每次验证使用独立的客户端,不同账户的cookies互不影响。
+/// 不跟随重定向 - 微博对未登录请求重定向到登录页,直接视为cookies失效。
+/// 响应解析与错误分类由客户端完成,这里只转换为 `ValidationError`。
+pub struct HttpValidator {
+    base_url: Url,
+    timeout: Duration,
+}
+
+impl Default for HttpValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpValidator {
+    /// 创建访问 weibo.com 的验证器
+    pub fn new() -> Self {
+        Self {
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("默认地址合法"),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// 设置站点地址 (构建器模式),用于测试时指向本地服务器
+    ///
+    /// # 错误
+    /// 地址无法解析时返回 `ValidationError::InvalidFormat`
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self, ValidationError> {
+        self.base_url = Url::parse(base_url)
+            .map_err(|e| ValidationError::InvalidFormat(format!("无效的验证地址: {}", e)))?;
+        Ok(self)
+    }
+
+    /// 设置单个请求的超时时间 (构建器模式)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 验证Cookies有效性
+    ///
+    /// # 返回值
+    /// - `Ok((uid, screen_name))`: cookies有效
+    ///
+    /// # 错误
+    /// - `ValidationError::Rejected`: 未登录或被重定向,附带原因 (过期、封禁、锁定、验证码、频率限制)
+    /// - `ValidationError::ProfileApiFailed`: 接口返回错误、HTTP错误状态或无法识别的响应
+    /// - `ValidationError::UidExtractionFailed`: 两个接口的uid不一致
+    /// - `ValidationError::RequestFailed`: 网络错误
+    /// - `ValidationError::Timeout`: 请求超时
+    pub async fn validate_cookies(
+        &self,
+        cookies: &HashMap<String, String>,
+    ) -> Result<(String, String), ValidationError> {
+        tracing::debug!(
+            验证地址 = %self.base_url,
+            cookies数量 = %cookies.len(),
+            "开始HTTP验证"
+        );
+
+        let client =
+            WeiboHttpClient::for_cookies("", cookies, self.base_url.clone(), self.timeout)?;
+
+        let uid = client
+            .login_status()
+            .await
+            .map_err(|e| {
+                tracing::warn!(错误 = %e, "Cookies验证失败");
+                ValidationError::from(e)
+            })?
+            .uid;
+
+        let profile = client.profile_info(&uid).await?;
+        if profile.uid != uid {
+            return Err(ValidationError::UidExtractionFailed(format!(
+                "个人资料UID {} 与登录UID {} 不一致",
+                profile.uid, uid
+            )));
+        }
+
+        let screen_name = if profile.screen_name.is_empty() {
+            "Unknown".to_string()
+        } else {
+            profile.screen_name
+        };
+
+        tracing::info!(用户ID = %uid, 昵称 = %screen_name, "Cookies验证成功");
+        Ok((uid, screen_name))
+    }
+}
+
+impl CookieValidator for HttpValidator {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn validate_cookies<'a>(
+        &'a self,
+        cookies: &'a HashMap<String, String>,
+    ) -> BoxFuture<'a, Result<(String, String), ValidationError>> {
+        Box::pin(HttpValidator::validate_cookies(self, cookies))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ValidationOutcome;
+    use crate::services::weibo_http::{CONFIG_PATH, PROFILE_PATH, USER_AGENT};
+    use crate::utils::mock_http::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn cookies() -> HashMap<String, String> {
+        HashMap::from([
+            ("SUB".to_string(), "sub_value".to_string()),
+            ("SUBP".to_string(), "subp_value".to_string()),
+        ])
+    }
+
+    fn validator(server: &MockServer) -> HttpValidator {
+        HttpValidator::new().with_base_url(&server.url()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_cookies() {
+        let server = MockServer::start(vec![
+            (
+                CONFIG_PATH,
+                MockResponse::json(200, json!({"ok": 1, "data": {"login": true, "uid": 1234567890}})),
+            ),
+            (
+                PROFILE_PATH,
+                MockResponse::json(
+                    200,
+                    json!({"ok": 1, "data": {"user": {"idstr": "1234567890", "screen_name": "测试账户"}}}),
+                ),
+            ),
+        ])
+        .await;
+
+        let (uid, screen_name) = validator(&server)
+            .validate_cookies(&cookies())
+            .await
+            .unwrap();
+        assert_eq!(uid, "1234567890");
+        assert_eq!(screen_name, "测试账户");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].path, "/ajax/profile/info?uid=1234567890");
+        for request in &requests {
+            let cookie = &request.headers["cookie"];
+            assert!(cookie.contains("SUB=sub_value"));
+            assert!(cookie.contains("SUBP=subp_value"));
+            assert_eq!(request.headers["user-agent"], USER_AGENT);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_not_logged_in() {
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            MockResponse::json(200, json!({"ok": 1, "data": {"login": false}})),
+        )])
+        .await;
+
+        let result = validator(&server).validate_cookies(&cookies()).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::Rejected {
+                outcome: ValidationOutcome::Expired,
+                ..
+            })
+        ));
+        // 未登录时不再请求个人资料
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_login_page() {
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            MockResponse::text(302, "")
+                .with_header("Location", "https://passport.weibo.com/sso/signin"),
+        )])
+        .await;
+
+        let result = validator(&server).validate_cookies(&cookies()).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::Rejected {
+                outcome: ValidationOutcome::Expired,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_html_response_is_invalid() {
+        // 只按状态码与重定向目标判断,页面中的"登录"字样不代表登录失效
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            MockResponse::text(200, "<html>请登录</html>"),
+        )])
+        .await;
+
+        let result = validator(&server).validate_cookies(&cookies()).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::ProfileApiFailed { status: 200, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failure_outcomes() {
+        let cases = [
+            (
+                MockResponse::text(302, "")
+                    .with_header("Location", "https://security.weibo.com/account/unlock"),
+                ValidationOutcome::Locked,
+            ),
+            (
+                MockResponse::text(429, "Too Many Requests"),
+                ValidationOutcome::RateLimited,
+            ),
+            (
+                MockResponse::json(200, json!({"ok": 0, "msg": "请输入验证码", "data": {}})),
+                ValidationOutcome::CaptchaRequired,
+            ),
+            (
+                MockResponse::json(200, json!({"ok": -100, "msg": "该账号已被封禁", "data": {}})),
+                ValidationOutcome::Banned,
+            ),
+        ];
+
+        for (response, expected) in cases {
+            let server = MockServer::start(vec![(CONFIG_PATH, response)]).await;
+            let result = validator(&server).validate_cookies(&cookies()).await;
+            match result {
+                Err(ValidationError::Rejected { outcome, .. }) => assert_eq!(outcome, expected),
+                other => panic!("期望 {:?},实际 {:?}", expected, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profile_uid_mismatch() {
+        let server = MockServer::start(vec![
+            (
+                CONFIG_PATH,
+                MockResponse::json(200, json!({"ok": 1, "data": {"login": true, "uid": "111"}})),
+            ),
+            (
+                PROFILE_PATH,
+                MockResponse::json(200, json!({"ok": 1, "data": {"user": {"idstr": "222"}}})),
+            ),
+        ])
+        .await;
+
+        let result = validator(&server).validate_cookies(&cookies()).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::UidExtractionFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slow_server_times_out() {
+        // 接受连接但从不响应
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+
+        let validator = HttpValidator::new()
+            .with_base_url(&format!("http://{}", addr))
+            .unwrap()
+            .with_timeout(Duration::from_millis(300));
+        let result = validator.validate_cookies(&cookies()).await;
+        assert!(matches!(result, Err(ValidationError::Timeout { .. })));
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server() {
+        // 绑定后立即释放端口,连接会被拒绝
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let validator = HttpValidator::new()
+            .with_base_url(&format!("http://{}", addr))
+            .unwrap()
+            .with_timeout(Duration::from_secs(2));
+
+        let result = validator.validate_cookies(&cookies()).await;
+        assert!(matches!(result, Err(ValidationError::RequestFailed(_))));
+    }
+}
@@ -0,0 +1,493 @@
+//! 账户租约服务
+//!
+//! 下游worker通过租约独占使用账户,避免多个worker同时使用同一账户。
+//! 租约记录在Redis中并带TTL,worker崩溃后租约到期自动释放。
+
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::account_lease::{least_recently_used_order, round_robin_order};
+use crate::models::{
+    AccountLease, AccountState, AuditEvent, AuditOperation, LeaseError, LeaseHolder, LeaseStrategy,
+    LeasedAccount, StorageError, TagMatch,
+};
+use crate::services::{AuditLog, RedisService};
+
+/// 默认冷却时间: 释放后30秒内不再发放同一账户
+pub const DEFAULT_LEASE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// 租约最长时长: 1天
+const MAX_LEASE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// 租约已失效、无法得知持有者时审计日志中的调用方
+const UNKNOWN_HOLDER: &str = "unknown";
+
+/// 原子获取租约的脚本
+///
+/// KEYS: 1=使用记录ZSet, 2=轮转游标, 之后每个候选3个key: Cookies、租约、冷却
+/// ARGV: 1=租约TTL(毫秒), 2=凭证, 3=持有者, 4=当前时间(毫秒), 之后为候选UID
+///
+/// 按传入顺序检查候选: Cookies存在、未被租用、不在冷却期的第一个账户被租出,
+/// 返回其UID;全部不可用时返回nil。
+const ACQUIRE_LEASE_SCRIPT: &str = r#"
+local candidates = (#KEYS - 2) / 3
+for i = 1, candidates do
+    local base = 2 + (i - 1) * 3
+    if redis.call('EXISTS', KEYS[base + 1]) == 1
+        and redis.call('EXISTS', KEYS[base + 2]) == 0
+        and redis.call('EXISTS', KEYS[base + 3]) == 0 then
+        local uid = ARGV[4 + i]
+        redis.call('HSET', KEYS[base + 2], 'token', ARGV[2], 'holder', ARGV[3], 'acquired_at', ARGV[4])
+        redis.call('PEXPIRE', KEYS[base + 2], ARGV[1])
+        redis.call('ZADD', KEYS[1], ARGV[4], uid)
+        redis.call('SET', KEYS[2], uid)
+        return uid
+    end
+end
+return false
+"#;
+
+/// 续租脚本
+///
+/// KEYS: 1=租约
+/// ARGV: 1=凭证, 2=新TTL(毫秒)
+///
+/// 凭证匹配时重置TTL并返回持有者,否则返回nil。
+const RENEW_LEASE_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], 'token') == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return redis.call('HGET', KEYS[1], 'holder')
+end
+return false
+"#;
+
+/// 释放脚本
+///
+/// KEYS: 1=租约, 2=冷却
+/// ARGV: 1=凭证 (空字符串表示强制释放), 2=冷却时间(毫秒)
+///
+/// 凭证匹配时删除租约并设置冷却标记,返回原持有者;否则返回nil。
+const RELEASE_LEASE_SCRIPT: &str = r#"
+local token = redis.call('HGET', KEYS[1], 'token')
+if not token or (ARGV[1] ~= '' and token ~= ARGV[1]) then
+    return false
+end
+local holder = redis.call('HGET', KEYS[1], 'holder')
+redis.call('DEL', KEYS[1])
+if tonumber(ARGV[2]) > 0 then
+    redis.call('SET', KEYS[2], '1', 'PX', ARGV[2])
+end
+return holder
+"#;
+
+/// 账户租约服务
+///
+/// 配置审计日志后,获取/续租/释放都会以持有者为调用方记录审计事件。
+pub struct LeaseService {
+    redis: Arc<RedisService>,
+    cooldown: Duration,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl LeaseService {
+    /// 创建服务,使用默认冷却时间
+    pub fn new(redis: Arc<RedisService>) -> Self {
+        Self {
+            redis,
+            cooldown: DEFAULT_LEASE_COOLDOWN,
+            audit: None,
+        }
+    }
+
+    /// 启用审计日志
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 设置释放后的冷却时间 (0表示不冷却)
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// 获取一个无人持有的账户
+    ///
+    /// # 参数
+    /// - `tag`: 只在拥有该标签的账户中选择,None表示所有账户 (只分配 `active` 状态的账户)
+    /// - `ttl`: 租约时长,到期未续租自动释放
+    /// - `strategy`: 选择策略
+    /// - `holder`: 持有者标识,用于展示
+    ///
+    /// # 错误
+    /// - `LeaseError::InvalidTtl`: TTL为0或超过1天
+    /// - `LeaseError::NoAccountAvailable`: 没有可租用的账户
+    /// - `LeaseError::Storage`: Redis操作失败
+    pub async fn acquire(
+        &self,
+        tag: Option<&str>,
+        ttl: Duration,
+        strategy: LeaseStrategy,
+        holder: &str,
+    ) -> Result<LeasedAccount, LeaseError> {
+        let result = self.acquire_inner(tag, ttl, strategy, holder).await;
+
+        let mut event = AuditEvent::new(AuditOperation::LeaseAcquire, holder).with_result(&result);
+        if let Ok(leased) = &result {
+            event = event.with_uid(&leased.lease.uid);
+        }
+        self.audit(event).await;
+
+        result
+    }
+
+    async fn acquire_inner(
+        &self,
+        tag: Option<&str>,
+        ttl: Duration,
+        strategy: LeaseStrategy,
+        holder: &str,
+    ) -> Result<LeasedAccount, LeaseError> {
+        let ttl_ms = validate_ttl(ttl)?;
+        let namespace = self.redis.namespace();
+
+        let candidates = match tag {
+            Some(tag) => {
+                self.redis
+                    .accounts_by_tags(&[tag.to_string()], TagMatch::All)
+                    .await?
+            }
+            None => self.redis.list_all_uids().await?,
+        };
+        // 失效、归档的账户不参与分配
+        let candidates = self
+            .redis
+            .filter_by_state(candidates, &[AccountState::Active])
+            .await?;
+        if candidates.is_empty() {
+            return Err(LeaseError::NoAccountAvailable(format!(
+                "没有匹配的正常账户 (标签: {:?})",
+                tag
+            )));
+        }
+
+        let mut conn = self.redis.connection().await?;
+
+        let ordered = match strategy {
+            LeaseStrategy::RoundRobin => {
+                let cursor: Option<String> = conn
+                    .get(namespace.lease_cursor_key())
+                    .await
+                    .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+                round_robin_order(candidates, cursor.as_deref())
+            }
+            LeaseStrategy::LeastRecentlyUsed => {
+                let mut pipe = redis::pipe();
+                for uid in &candidates {
+                    pipe.zscore(namespace.lease_usage_key(), uid);
+                }
+                let last_used: Vec<Option<i64>> = pipe
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+                least_recently_used_order(candidates, &last_used)
+            }
+        };
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+
+        let script = redis::Script::new(ACQUIRE_LEASE_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(namespace.lease_usage_key())
+            .key(namespace.lease_cursor_key());
+        for uid in &ordered {
+            invocation
+                .key(namespace.cookies_key(uid))
+                .key(namespace.lease_key(uid))
+                .key(namespace.lease_cooldown_key(uid));
+        }
+        invocation
+            .arg(ttl_ms)
+            .arg(&token)
+            .arg(holder)
+            .arg(now.timestamp_millis());
+        for uid in &ordered {
+            invocation.arg(uid);
+        }
+
+        let acquired: Option<String> = invocation
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        drop(conn);
+
+        let uid = acquired.ok_or_else(|| {
+            LeaseError::NoAccountAvailable(format!(
+                "{}个候选账户均被占用或处于冷却期",
+                ordered.len()
+            ))
+        })?;
+
+        let lease = AccountLease {
+            token,
+            uid: uid.clone(),
+            holder: holder.to_string(),
+            acquired_at: now,
+            expires_at: now + chrono::Duration::milliseconds(ttl_ms as i64),
+        };
+
+        // Cookies可能在加锁与读取之间过期,此时立即释放租约
+        let cookies = match self.redis.query_cookies(&uid).await {
+            Ok(cookies) => cookies,
+            Err(e) => {
+                let _ = self.release_inner(&uid, &lease.token).await;
+                return Err(e.into());
+            }
+        };
+
+        tracing::info!(
+            用户ID = %uid,
+            持有者 = %holder,
+            策略 = ?strategy,
+            租约时长 = ?ttl,
+            "已获取账户租约"
+        );
+
+        Ok(LeasedAccount { lease, cookies })
+    }
+
+    /// 续租
+    ///
+    /// # 错误
+    /// - `LeaseError::NotHeld`: 租约已过期或凭证不匹配
+    pub async fn renew(
+        &self,
+        uid: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<DateTime<Utc>, LeaseError> {
+        let ttl_ms = validate_ttl(ttl)?;
+        let mut conn = self.redis.connection().await?;
+
+        let renewed: Option<String> = redis::Script::new(RENEW_LEASE_SCRIPT)
+            .key(self.redis.namespace().lease_key(uid))
+            .arg(token)
+            .arg(ttl_ms)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        drop(conn);
+
+        let Some(holder) = renewed else {
+            tracing::warn!(用户ID = %uid, "续租失败: 租约已失效");
+            let error = LeaseError::NotHeld(uid.to_string());
+            self.audit(
+                AuditEvent::new(AuditOperation::LeaseRenew, UNKNOWN_HOLDER)
+                    .with_uid(uid)
+                    .failed(&error),
+            )
+            .await;
+            return Err(error);
+        };
+
+        self.audit(AuditEvent::new(AuditOperation::LeaseRenew, &holder).with_uid(uid))
+            .await;
+        tracing::debug!(用户ID = %uid, 租约时长 = ?ttl, "已续租");
+        Ok(Utc::now() + chrono::Duration::milliseconds(ttl_ms as i64))
+    }
+
+    /// 释放租约,账户进入冷却期
+    ///
+    /// # 错误
+    /// - `LeaseError::NotHeld`: 租约已过期或凭证不匹配
+    pub async fn release(&self, uid: &str, token: &str) -> Result<(), LeaseError> {
+        let result = self.release_inner(uid, token).await;
+        let event = match &result {
+            Ok(holder) => AuditEvent::new(AuditOperation::LeaseRelease, holder),
+            Err(e) => AuditEvent::new(AuditOperation::LeaseRelease, UNKNOWN_HOLDER).failed(e),
+        };
+        self.audit(event.with_uid(uid)).await;
+        result.map(|_| ())
+    }
+
+    /// 强制释放租约 (管理界面使用,不校验凭证)
+    ///
+    /// # 参数
+    /// - `caller`: 执行强制释放的一方,记录到审计日志
+    ///
+    /// # 错误
+    /// - `LeaseError::NotHeld`: 账户当前没有租约
+    pub async fn force_release(&self, uid: &str, caller: &str) -> Result<(), LeaseError> {
+        let result = self.release_inner(uid, "").await;
+        let mut event = AuditEvent::new(AuditOperation::LeaseRelease, caller)
+            .with_uid(uid)
+            .with_result(&result);
+        if let Ok(holder) = &result {
+            event = event.with_detail(format!("强制释放,原持有者: {}", holder));
+        }
+        self.audit(event).await;
+        result.map(|_| ())
+    }
+
+    /// 释放租约,返回原持有者
+    async fn release_inner(&self, uid: &str, token: &str) -> Result<String, LeaseError> {
+        let namespace = self.redis.namespace();
+        let mut conn = self.redis.connection().await?;
+
+        let released: Option<String> = redis::Script::new(RELEASE_LEASE_SCRIPT)
+            .key(namespace.lease_key(uid))
+            .key(namespace.lease_cooldown_key(uid))
+            .arg(token)
+            .arg(self.cooldown.as_millis() as u64)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let holder = released.ok_or_else(|| LeaseError::NotHeld(uid.to_string()))?;
+
+        tracing::info!(用户ID = %uid, 持有者 = %holder, 强制 = %token.is_empty(), "已释放账户租约");
+        Ok(holder)
+    }
+
+    async fn audit(&self, event: AuditEvent) {
+        if let Some(audit) = &self.audit {
+            audit.record(event).await;
+        }
+    }
+
+    /// 列出当前所有租约持有者 (按到期时间升序)
+    pub async fn list_holders(&self) -> Result<Vec<LeaseHolder>, LeaseError> {
+        let namespace = self.redis.namespace();
+        let mut conn = self.redis.connection().await?;
+
+        let keys: Vec<String> = redis::cmd("KEYS")
+            .arg(namespace.lease_pattern())
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.hgetall(key).pttl(key);
+        }
+        let rows: Vec<(HashMap<String, String>, i64)> = pipe
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let now = Utc::now();
+        let mut holders: Vec<LeaseHolder> = keys
+            .iter()
+            .zip(rows)
+            .filter_map(|(key, (fields, pttl))| {
+                // 读取期间到期的租约 (PTTL<0) 直接跳过
+                if pttl < 0 || fields.is_empty() {
+                    return None;
+                }
+                let uid = namespace.uid_from_lease_key(key)?.to_string();
+                let acquired_at = fields
+                    .get("acquired_at")
+                    .and_then(|ms| ms.parse::<i64>().ok())
+                    .and_then(DateTime::from_timestamp_millis)
+                    .unwrap_or(now);
+                Some(LeaseHolder {
+                    uid,
+                    holder: fields.get("holder").cloned().unwrap_or_default(),
+                    acquired_at,
+                    expires_at: now + chrono::Duration::milliseconds(pttl),
+                })
+            })
+            .collect();
+
+        holders.sort_by(|a, b| a.expires_at.cmp(&b.expires_at).then(a.uid.cmp(&b.uid)));
+        tracing::debug!(租约数量 = %holders.len(), "列出租约持有者");
+        Ok(holders)
+    }
+}
+
+/// 检查租约时长并转换为毫秒
+fn validate_ttl(ttl: Duration) -> Result<u64, LeaseError> {
+    if ttl.is_zero() || ttl > MAX_LEASE_TTL {
+        return Err(LeaseError::InvalidTtl(format!(
+            "{:?} (允许范围: 1毫秒 ~ {:?})",
+            ttl, MAX_LEASE_TTL
+        )));
+    }
+    Ok(ttl.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CookiesData, SnapshotSource, SnapshotValidation};
+    use crate::services::SaveMode;
+
+    #[test]
+    fn test_validate_ttl() {
+        assert!(validate_ttl(Duration::ZERO).is_err());
+        assert!(validate_ttl(Duration::from_secs(24 * 3600 + 1)).is_err());
+        assert_eq!(validate_ttl(Duration::from_secs(60)).unwrap(), 60_000);
+    }
+
+    async fn seed(redis: &RedisService, uid: &str) {
+        let cookies = HashMap::from([
+            ("SUB".to_string(), "x".to_string()),
+            ("SUBP".to_string(), "y".to_string()),
+        ]);
+        let data = CookiesData::new(uid.to_string(), cookies).with_namespace(redis.namespace());
+        redis
+            .save_cookies(
+                &data,
+                SnapshotSource::Manual,
+                SnapshotValidation::Unverified,
+                SaveMode::Overwrite,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_lease_is_exclusive_and_released_into_cooldown() {
+        let namespace = crate::models::KeyNamespace::new("lease-test").unwrap();
+        let redis = Arc::new(
+            RedisService::new("redis://localhost:6379")
+                .unwrap()
+                .with_namespace(namespace),
+        );
+        seed(&redis, "1").await;
+        let leases = LeaseService::new(redis.clone()).with_cooldown(Duration::from_secs(60));
+        let ttl = Duration::from_secs(30);
+
+        let first = leases
+            .acquire(None, ttl, LeaseStrategy::LeastRecentlyUsed, "worker-a")
+            .await
+            .unwrap();
+        assert_eq!(first.lease.uid, "1");
+
+        // 唯一的账户已被占用
+        assert!(matches!(
+            leases
+                .acquire(None, ttl, LeaseStrategy::RoundRobin, "worker-b")
+                .await,
+            Err(LeaseError::NoAccountAvailable(_))
+        ));
+        assert!(leases.renew("1", "wrong-token", ttl).await.is_err());
+        leases.renew("1", &first.lease.token, ttl).await.unwrap();
+        assert_eq!(leases.list_holders().await.unwrap()[0].holder, "worker-a");
+
+        // 释放后进入冷却期,仍不可获取
+        leases.release("1", &first.lease.token).await.unwrap();
+        assert!(leases
+            .acquire(None, ttl, LeaseStrategy::RoundRobin, "worker-b")
+            .await
+            .is_err());
+
+        redis.delete_cookies("1").await.unwrap();
+    }
+}
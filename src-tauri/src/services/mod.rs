@@ -2,8 +2,21 @@
 //!
 //! 包含所有业务逻辑服务:
 //! - `redis_service`: Redis存储服务,管理cookies持久化
+//! - `redis_pool`: 按部署方式 (单机/哨兵/集群) 创建的连接池
 //! - `weibo_api`: 微博API客户端,生成二维码和轮询状态
+//! - `cookie_validator`: Cookies验证器trait,按配置选择具体实现
 //! - `validation_service`: Cookies验证服务,调用Playwright验证有效性
+//! - `validation_worker`: 常驻的Playwright验证进程,崩溃后自动重启
+//! - `http_validator`: 纯Rust的Cookies验证,直接请求微博接口
+//! - `weibo_http`: 用已保存账户访问微博的HTTP客户端,供下游工具复用
+//! - `audit_service`: 审计日志,记录cookies的读写与租约操作
+//! - `account_watcher`: 账户变化监听,keyspace通知或轮询
+//! - `revalidation_service`: 后台定时重新验证所有账户
+//! - `refresh_service`: Cookies保活刷新,保存微博轮换后的cookies
+//! - `rate_limiter`: 面向微博请求的全局限速,按操作类型分别配置
+//! - `lease_service`: 账户租约,下游worker独占使用账户
+//! - `transfer_service`: Cookies导入导出,与cookies.txt/storageState等格式互转
+//! - `spool_service`: Redis不可用时暂存扫码结果,恢复后自动写回
 //!
 //! # 设计原则
 //!
@@ -40,8 +53,8 @@
 //! # 使用示例
 //!
 //! ```no_run
-//! use weibo_login::services::{RedisService, WeiboApiClient, ValidationService};
-//! use weibo_login::models::CookiesData;
+//! use weibo_login::services::{RedisService, SaveMode, WeiboApiClient, ValidationService};
+//! use weibo_login::models::{CookiesData, SnapshotSource, SnapshotValidation};
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! // 初始化服务
@@ -60,7 +73,9 @@
 //!     // 保存到Redis
 //!     let cookies_data = CookiesData::new(uid, cookies)
 //!         .with_screen_name(screen_name);
-//!     redis.save_cookies(&cookies_data).await?;
+//!     redis
+//!         .save_cookies(&cookies_data, SnapshotSource::Manual, SnapshotValidation::Verified, SaveMode::IfNewer)
+//!         .await?;
 //! }
 //! # Ok(())
 //! # }
@@ -74,13 +89,26 @@ pub mod weibo_login_service_poc;
 #[cfg(feature = "rust-browser-poc")]
 pub mod websocket_server_poc;
 
+pub mod account_watcher;
+pub mod audit_service;
 pub mod config_service;
+pub mod cookie_validator;
 pub mod dependency_checker;
+pub mod http_validator;
 pub mod installer_service;
+pub mod lease_service;
+pub mod rate_limiter;
+pub mod redis_pool;
 pub mod redis_service;
+pub mod refresh_service;
+pub mod revalidation_service;
 pub mod session_manager;
+pub mod spool_service;
+pub mod transfer_service;
 pub mod validation_service;
+pub mod validation_worker;
 pub mod weibo_api;
+pub mod weibo_http;
 
 // 重导出常用类型,简化外部引用
 #[cfg(feature = "rust-browser-poc")]
@@ -90,10 +118,28 @@ pub use weibo_login_service_poc::WeiboLoginService as WeiboLoginServicePoc;
 #[cfg(feature = "rust-browser-poc")]
 pub use websocket_server_poc::WebSocketServer as WebSocketServerPoc;
 
+pub use account_watcher::{AccountWatcher, WatchStatus};
+pub use audit_service::AuditLog;
 pub use config_service::ConfigService;
+pub use cookie_validator::{
+    CookieValidator, LoginCheck, LoginValidationPolicy, NoopValidator, RateLimitedValidator,
+    ValidationCancellations, ValidatorConfig, ValidatorKind,
+};
 pub use dependency_checker::DependencyChecker;
+pub use http_validator::HttpValidator;
 pub use installer_service::InstallerService;
-pub use redis_service::RedisService;
+pub use lease_service::LeaseService;
+pub use rate_limiter::{RateLimiter, RateLimiterConfig, RateOperation};
+pub use redis_pool::{RedisConnection, RedisPool};
+pub use redis_service::{RedisService, SaveMode, SaveOutcome, SaveWinner};
+pub use refresh_service::{RefreshReport, RefreshService};
+pub use revalidation_service::{RevalidationConfig, RevalidationService};
 pub use session_manager::SessionManager;
+pub use spool_service::SpoolService;
+pub use transfer_service::{
+    ExportReport, ExportScope, ImportEntry, ImportReport, ImportStatus, TransferService,
+};
 pub use validation_service::ValidationService;
+pub use validation_worker::ValidationWorker;
 pub use weibo_api::WeiboApiClient;
+pub use weibo_http::{LoginStatus, ProfileInfo, WeiboHttpClient};
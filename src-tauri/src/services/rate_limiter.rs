@@ -0,0 +1,433 @@
+//! 对微博请求的全局限速
+//!
+//! 批量验证、生成二维码、保活刷新都会请求微博,点得再快也不能全部立刻发出,
+//! 否则整个出口IP可能被风控。所有面向微博的操作都先从这里取得令牌:
+//! 每类操作一个令牌桶,容量即允许的突发数量,令牌按配置的速率补充。
+//!
+//! 令牌不足时排队等待;预计等待超过上限则立即返回 [`RateLimited`],
+//! 其中的 `retry_after` 是这次请求实际需要等待的时间。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::models::{ApiError, ValidationError};
+
+/// 默认最长排队时间
+pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// 受限速的操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateOperation {
+    /// 生成二维码
+    QrCode,
+
+    /// Cookies验证 (Playwright或HTTP)
+    Validation,
+
+    /// 保活刷新
+    Refresh,
+}
+
+impl RateOperation {
+    pub const ALL: [RateOperation; 3] = [Self::QrCode, Self::Validation, Self::Refresh];
+
+    /// 日志与配置中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::QrCode => "qrcode",
+            Self::Validation => "validation",
+            Self::Refresh => "refresh",
+        }
+    }
+
+    /// 配置该操作限速的环境变量
+    fn env_key(&self) -> &'static str {
+        match self {
+            Self::QrCode => "RATE_LIMIT_QRCODE",
+            Self::Validation => "RATE_LIMIT_VALIDATION",
+            Self::Refresh => "RATE_LIMIT_REFRESH",
+        }
+    }
+
+    /// 默认限速
+    fn default_limit(&self) -> RateLimit {
+        match self {
+            Self::QrCode => RateLimit::new(6, Duration::from_secs(60)),
+            Self::Validation => RateLimit::new(30, Duration::from_secs(60)),
+            Self::Refresh => RateLimit::new(10, Duration::from_secs(60)),
+        }
+    }
+}
+
+/// 限速: 每 `per` 时间内最多 `requests` 次,允许一次性用完
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self { requests, per }
+    }
+
+    /// 每秒补充的令牌数
+    fn rate(&self) -> f64 {
+        self.requests as f64 / self.per.as_secs_f64()
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    /// 解析 `次数/秒数`,如 `30/60` 表示每分钟30次
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的限速: {} (格式: 次数/秒数,如 30/60)", value);
+        let (requests, per) = value.trim().split_once('/').ok_or_else(invalid)?;
+        let requests: u32 = requests.trim().parse().map_err(|_| invalid())?;
+        let per: u64 = per.trim().parse().map_err(|_| invalid())?;
+        if requests == 0 || per == 0 {
+            return Err(invalid());
+        }
+        Ok(Self::new(requests, Duration::from_secs(per)))
+    }
+}
+
+/// 限速配置
+///
+/// 读取环境变量:
+/// - RATE_LIMIT_QRCODE: 生成二维码 (默认: 6/60)
+/// - RATE_LIMIT_VALIDATION: Cookies验证 (默认: 30/60)
+/// - RATE_LIMIT_REFRESH: 保活刷新 (默认: 10/60)
+/// - RATE_LIMIT_MAX_WAIT_SECS: 最长排队时间,0表示不排队 (默认: 30)
+///
+/// 限速写作 `次数/秒数`,`off` 表示该操作不限速。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimiterConfig {
+    /// 各操作的限速,None表示不限速
+    pub limits: HashMap<RateOperation, Option<RateLimit>>,
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimiterConfig {
+    fn default() -> Self {
+        Self {
+            limits: RateOperation::ALL
+                .iter()
+                .map(|op| (*op, Some(op.default_limit())))
+                .collect(),
+            max_wait: DEFAULT_MAX_WAIT,
+        }
+    }
+}
+
+impl RateLimiterConfig {
+    /// 从环境变量读取
+    ///
+    /// # 错误
+    /// 限速或排队时间格式无效时返回说明
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// 从任意键值来源读取 (环境变量或测试中的固定值)
+    ///
+    /// # 错误
+    /// 同 [`from_env`](Self::from_env)
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let var = |key: &str| lookup(key).unwrap_or_default();
+        let mut config = Self::default();
+
+        for op in RateOperation::ALL {
+            match var(op.env_key()).trim() {
+                "" => {}
+                "off" => {
+                    config.limits.insert(op, None);
+                }
+                value => {
+                    let limit = value
+                        .parse::<RateLimit>()
+                        .map_err(|e| format!("{}: {}", op.env_key(), e))?;
+                    config.limits.insert(op, Some(limit));
+                }
+            }
+        }
+
+        match var("RATE_LIMIT_MAX_WAIT_SECS").trim() {
+            "" => {}
+            value => {
+                let secs = value.parse::<u64>().map_err(|_| {
+                    format!(
+                        "无效的 RATE_LIMIT_MAX_WAIT_SECS: {} (应为非负整数秒)",
+                        value
+                    )
+                })?;
+                config.max_wait = Duration::from_secs(secs);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// 令牌不足,且排队时间会超过上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    pub operation: RateOperation,
+    /// 这次请求需要等待的时间
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    /// 向上取整的等待秒数,至少1秒
+    pub fn retry_after_secs(&self) -> u64 {
+        (self.retry_after.as_secs_f64().ceil() as u64).max(1)
+    }
+}
+
+impl From<RateLimited> for ApiError {
+    fn from(err: RateLimited) -> Self {
+        ApiError::RateLimitExceeded {
+            retry_after: Some(err.retry_after_secs()),
+        }
+    }
+}
+
+impl From<RateLimited> for ValidationError {
+    fn from(err: RateLimited) -> Self {
+        ValidationError::RateLimitExceeded {
+            retry_after: Some(err.retry_after_secs()),
+        }
+    }
+}
+
+/// 令牌桶
+///
+/// 令牌数可以为负: 排队中的请求已预订了未来的令牌,
+/// 后来的请求据此算出排在它们之后需要等待多久。
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 全局限速器
+pub struct RateLimiter {
+    config: RateLimiterConfig,
+    buckets: Mutex<HashMap<RateOperation, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimiterConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 不限速的限速器 (测试与工具使用)
+    pub fn unlimited() -> Self {
+        Self::new(RateLimiterConfig {
+            limits: HashMap::new(),
+            max_wait: Duration::ZERO,
+        })
+    }
+
+    /// 取得一个令牌,必要时排队
+    ///
+    /// 预计等待不超过 `max_wait` 时预订令牌并等待;否则不预订,立即返回。
+    /// 等待期间被取消的请求不会归还已预订的令牌。
+    ///
+    /// # 错误
+    /// 需要等待的时间超过上限,`retry_after` 为届时可用的等待时间
+    pub async fn acquire(&self, operation: RateOperation) -> Result<(), RateLimited> {
+        let wait = self.reserve(operation)?;
+        if !wait.is_zero() {
+            tracing::debug!(
+                操作 = %operation.as_str(),
+                等待毫秒 = %wait.as_millis(),
+                "请求排队等待限速令牌"
+            );
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// 预订令牌,返回需要等待的时间
+    fn reserve(&self, operation: RateOperation) -> Result<Duration, RateLimited> {
+        let Some(limit) = self.config.limits.get(&operation).copied().flatten() else {
+            return Ok(Duration::ZERO);
+        };
+
+        let now = Instant::now();
+        let capacity = limit.requests as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(operation).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.rate()).min(capacity);
+        bucket.updated = now;
+
+        let wait = if bucket.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - bucket.tokens) / limit.rate())
+        };
+        if wait > self.config.max_wait {
+            tracing::warn!(
+                操作 = %operation.as_str(),
+                需等待秒 = %wait.as_secs_f64(),
+                "请求过于频繁,已被本地限速"
+            );
+            return Err(RateLimited {
+                operation,
+                retry_after: wait,
+            });
+        }
+
+        bucket.tokens -= 1.0;
+        Ok(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(limit: RateLimit, max_wait: Duration) -> RateLimiter {
+        RateLimiter::new(RateLimiterConfig {
+            limits: HashMap::from([(RateOperation::Validation, Some(limit))]),
+            max_wait,
+        })
+    }
+
+    #[test]
+    fn test_parse_rate_limit() {
+        assert_eq!(
+            "30/60".parse::<RateLimit>(),
+            Ok(RateLimit::new(30, Duration::from_secs(60)))
+        );
+        assert_eq!(
+            " 1 / 5 ".parse::<RateLimit>(),
+            Ok(RateLimit::new(1, Duration::from_secs(5)))
+        );
+        for invalid in ["30", "0/60", "30/0", "a/b"] {
+            assert!(invalid.parse::<RateLimit>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_config_from_lookup() {
+        let config = |pairs: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            RateLimiterConfig::from_lookup(|key| vars.get(key).cloned())
+        };
+
+        assert_eq!(config(&[]), Ok(RateLimiterConfig::default()));
+
+        let parsed = config(&[
+            ("RATE_LIMIT_QRCODE", "off"),
+            ("RATE_LIMIT_VALIDATION", "5/10"),
+            ("RATE_LIMIT_MAX_WAIT_SECS", "0"),
+        ])
+        .unwrap();
+        assert_eq!(parsed.limits[&RateOperation::QrCode], None);
+        assert_eq!(
+            parsed.limits[&RateOperation::Validation],
+            Some(RateLimit::new(5, Duration::from_secs(10)))
+        );
+        assert_eq!(
+            parsed.limits[&RateOperation::Refresh],
+            Some(RateOperation::Refresh.default_limit())
+        );
+        assert_eq!(parsed.max_wait, Duration::ZERO);
+
+        assert!(config(&[("RATE_LIMIT_REFRESH", "10")]).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_then_queue() {
+        // 每10秒2次: 每5秒补充一个令牌
+        let limiter = limiter(
+            RateLimit::new(2, Duration::from_secs(10)),
+            Duration::from_secs(10),
+        );
+        let start = Instant::now();
+
+        limiter.acquire(RateOperation::Validation).await.unwrap();
+        limiter.acquire(RateOperation::Validation).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire(RateOperation::Validation).await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_after_is_accurate() {
+        let limiter = limiter(RateLimit::new(1, Duration::from_secs(10)), Duration::ZERO);
+
+        limiter.acquire(RateOperation::Validation).await.unwrap();
+        let err = limiter
+            .acquire(RateOperation::Validation)
+            .await
+            .unwrap_err();
+        assert_eq!(err.retry_after, Duration::from_secs(10));
+
+        tokio::time::advance(Duration::from_millis(7500)).await;
+        let err = limiter
+            .acquire(RateOperation::Validation)
+            .await
+            .unwrap_err();
+        assert_eq!(err.retry_after, Duration::from_millis(2500));
+        assert_eq!(err.retry_after_secs(), 3);
+        assert!(matches!(
+            ApiError::from(err),
+            ApiError::RateLimitExceeded {
+                retry_after: Some(3)
+            }
+        ));
+
+        // 被拒绝的请求不占用令牌
+        tokio::time::advance(Duration::from_millis(2500)).await;
+        limiter.acquire(RateOperation::Validation).await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queued_requests_count_against_later_ones() {
+        let limiter = limiter(
+            RateLimit::new(1, Duration::from_secs(10)),
+            Duration::from_secs(15),
+        );
+
+        limiter.acquire(RateOperation::Validation).await.unwrap();
+        // 第二个请求预订了10秒后的令牌,第三个需要等20秒,超过上限
+        assert_eq!(
+            limiter.reserve(RateOperation::Validation),
+            Ok(Duration::from_secs(10))
+        );
+        let err = limiter.reserve(RateOperation::Validation).unwrap_err();
+        assert_eq!(err.retry_after, Duration::from_secs(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unlimited_operations() {
+        let limiter = limiter(RateLimit::new(1, Duration::from_secs(60)), Duration::ZERO);
+        for _ in 0..100 {
+            limiter.acquire(RateOperation::QrCode).await.unwrap();
+        }
+        let limiter = RateLimiter::unlimited();
+        for _ in 0..100 {
+            limiter.acquire(RateOperation::Validation).await.unwrap();
+        }
+    }
+}
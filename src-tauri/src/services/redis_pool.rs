@@ -0,0 +1,519 @@
+//! Redis连接池
+//!
+//! 按部署方式 (单机/哨兵/集群) 创建连接池,对上层统一暴露 [`RedisConnection`]。
+//! `RedisConnection` 实现 `redis::aio::ConnectionLike`,`AsyncCommands`、
+//! `Script::invoke_async`、`Pipeline::query_async` 都可以直接使用,
+//! 业务代码无需关心背后是哪种部署。
+//!
+//! 哨兵模式下主从切换后,池中指向旧主节点的连接仍能PING通 (它已降为从节点),
+//! 但写命令会收到 `READONLY`。这类连接在归还时被丢弃,下一次取连接时重新向哨兵解析主节点。
+//!
+//! 集群连接本身是多路复用的,内部维护到各节点的连接并处理重定向,
+//! 所有调用方共享同一个连接,不再经过连接池。这样自定义TLS证书也能传给集群客户端。
+
+use deadpool_redis::{sentinel, Runtime};
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::cluster_routing::{get_slot, Route, RoutingInfo, SingleNodeRoutingInfo, SlotAddr};
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+use redis::{
+    ClientTlsConfig, Cmd, ErrorKind, FromRedisValue, Pipeline, RedisConnectionInfo, RedisError,
+    RedisFuture, RedisResult, TlsCertificates, TlsMode, Value,
+};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+use crate::models::{RedisConfig, RedisTopology, StorageError};
+
+/// 按部署方式区分的连接池
+pub enum RedisPool {
+    Standalone {
+        pool: deadpool_redis::Pool,
+        /// Pub/Sub订阅需要专用连接,不能占用连接池
+        client: redis::Client,
+    },
+    Sentinel {
+        pool: sentinel::Pool,
+        sentinel_urls: Vec<String>,
+        master_name: String,
+        node_info: SentinelNodeConnectionInfo,
+    },
+    Cluster {
+        client: ClusterClient,
+        /// 首次使用时建立,失败时下次重试
+        connection: OnceCell<ClusterConnection>,
+    },
+}
+
+impl RedisPool {
+    /// 单机连接池
+    ///
+    /// # 错误
+    /// 返回 `StorageError::RedisConnectionFailed` 如果URL无效
+    pub fn from_url(redis_url: &str) -> Result<Self, StorageError> {
+        let pool = deadpool_redis::Config::from_url(redis_url)
+            .create_pool(Some(Runtime::Tokio1))
+            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+
+        Ok(Self::Standalone { pool, client })
+    }
+
+    /// 按配置的部署方式创建连接池 (不会立即建立连接)
+    ///
+    /// # 错误
+    /// 返回 `StorageError::RedisConnectionFailed` 如果配置不完整或节点地址无效
+    pub fn from_config(config: &RedisConfig) -> Result<Self, StorageError> {
+        config
+            .validate()
+            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+
+        match &config.topology {
+            RedisTopology::Standalone => {
+                let client = standalone_client(config)
+                    .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+                // 连接信息中已带有TLS证书,交给连接池原样使用
+                let manager = deadpool_redis::Manager::new(client.get_connection_info().clone())
+                    .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+                let pool = deadpool_redis::Pool::builder(manager)
+                    .runtime(Runtime::Tokio1)
+                    .build()
+                    .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+
+                Ok(Self::Standalone { pool, client })
+            }
+            RedisTopology::Sentinel { master_name, .. } => {
+                let sentinel_urls = config.node_urls();
+                let node_info = sentinel_node_info(config);
+                let manager = sentinel::Manager::new(
+                    sentinel_urls.iter().map(String::as_str).collect(),
+                    master_name.clone(),
+                    Some(node_info.clone()),
+                    sentinel::SentinelServerType::Master,
+                )
+                .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+                let pool = sentinel::Pool::builder(manager)
+                    .runtime(Runtime::Tokio1)
+                    .build()
+                    .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+
+                Ok(Self::Sentinel {
+                    pool,
+                    sentinel_urls,
+                    master_name: master_name.clone(),
+                    node_info,
+                })
+            }
+            RedisTopology::Cluster { .. } => {
+                let client = cluster_client(config)
+                    .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+
+                Ok(Self::Cluster {
+                    client,
+                    connection: OnceCell::new(),
+                })
+            }
+        }
+    }
+
+    /// 从连接池获取连接
+    pub async fn get(&self) -> Result<RedisConnection, StorageError> {
+        let inner = match self {
+            Self::Standalone { pool, .. } => pool.get().await.map(PooledConnection::Standalone),
+            Self::Sentinel { pool, .. } => pool.get().await.map(PooledConnection::Sentinel),
+            Self::Cluster { client, connection } => {
+                let conn = connection
+                    .get_or_try_init(|| client.get_async_connection())
+                    .await
+                    .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+                Ok(PooledConnection::Cluster(conn.clone()))
+            }
+        }
+        .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+
+        Ok(RedisConnection {
+            inner: Some(inner),
+            discard: false,
+        })
+    }
+
+    /// 是否为集群模式
+    pub fn is_cluster(&self) -> bool {
+        matches!(self, Self::Cluster { .. })
+    }
+
+    /// 连接的数据库编号 (集群固定为0)
+    pub fn database(&self) -> i64 {
+        match self {
+            Self::Standalone { client, .. } => client.get_connection_info().redis.db,
+            Self::Sentinel { node_info, .. } => node_info
+                .redis_connection_info
+                .as_ref()
+                .map(|info| info.db)
+                .unwrap_or(0),
+            Self::Cluster { .. } => 0,
+        }
+    }
+
+    /// 建立Pub/Sub专用连接
+    ///
+    /// 哨兵模式每次都重新解析主节点。集群的keyspace通知只在key所在节点发布,
+    /// 不支持订阅,调用方应退回轮询。
+    pub async fn pubsub(&self) -> Result<redis::aio::PubSub, StorageError> {
+        let client = match self {
+            Self::Standalone { client, .. } => client.clone(),
+            Self::Sentinel {
+                sentinel_urls,
+                master_name,
+                node_info,
+                ..
+            } => Sentinel::build(sentinel_urls.iter().map(String::as_str).collect())
+                .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?
+                .async_master_for(master_name, Some(node_info))
+                .await
+                .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?,
+            Self::Cluster { .. } => {
+                return Err(StorageError::CommandFailed(
+                    "集群模式不支持keyspace通知订阅".to_string(),
+                ))
+            }
+        };
+
+        client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))
+    }
+}
+
+enum PooledConnection {
+    Standalone(deadpool_redis::Connection),
+    Sentinel(sentinel::Connection),
+    Cluster(ClusterConnection),
+}
+
+/// 连接池中的连接
+///
+/// 归还时若曾收到 `READONLY` (连到了已降级的旧主节点),连接被移出连接池而不是放回。
+pub struct RedisConnection {
+    /// 仅在drop时取出
+    inner: Option<PooledConnection>,
+    discard: bool,
+}
+
+impl RedisConnection {
+    fn parts(&mut self) -> (&mut PooledConnection, &mut bool) {
+        let inner = self
+            .inner
+            .as_mut()
+            .expect("connection is only taken on drop");
+        (inner, &mut self.discard)
+    }
+}
+
+/// 写命令发到了从节点: 主从切换后旧连接的典型错误
+fn is_stale_master(error: &RedisError) -> bool {
+    error.kind() == ErrorKind::ReadOnly || error.to_string().contains("READONLY")
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let (inner, discard) = self.parts();
+        let fut = match inner {
+            PooledConnection::Standalone(conn) => conn.req_packed_command(cmd),
+            PooledConnection::Sentinel(conn) => conn.req_packed_command(cmd),
+            PooledConnection::Cluster(conn) => conn.req_packed_command(cmd),
+        };
+        Box::pin(async move {
+            let result = fut.await;
+            if result.as_ref().is_err_and(is_stale_master) {
+                *discard = true;
+            }
+            result
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        let (inner, discard) = self.parts();
+        let fut = match inner {
+            PooledConnection::Standalone(conn) => conn.req_packed_commands(cmd, offset, count),
+            PooledConnection::Sentinel(conn) => conn.req_packed_commands(cmd, offset, count),
+            PooledConnection::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+        };
+        Box::pin(async move {
+            let result = fut.await;
+            if result.as_ref().is_err_and(is_stale_master) {
+                *discard = true;
+            }
+            result
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        match &self.inner {
+            Some(PooledConnection::Standalone(conn)) => conn.get_db(),
+            Some(PooledConnection::Sentinel(conn)) => conn.get_db(),
+            Some(PooledConnection::Cluster(conn)) => conn.get_db(),
+            None => 0,
+        }
+    }
+}
+
+impl Drop for RedisConnection {
+    fn drop(&mut self) {
+        if !self.discard {
+            return;
+        }
+        // take() 把连接移出连接池,随后直接关闭
+        match self.inner.take() {
+            Some(PooledConnection::Standalone(conn)) => {
+                drop(deadpool_redis::Connection::take(conn))
+            }
+            Some(PooledConnection::Sentinel(conn)) => drop(sentinel::Connection::take(conn)),
+            // 集群连接自行处理节点切换,无需移除
+            Some(PooledConnection::Cluster(_)) | None => return,
+        }
+        tracing::warn!("连接收到READONLY,可能发生了主从切换,已从连接池移除");
+    }
+}
+
+/// 哨兵模式下连接主节点使用的用户名、密码、数据库与TLS模式
+fn sentinel_node_info(config: &RedisConfig) -> SentinelNodeConnectionInfo {
+    SentinelNodeConnectionInfo {
+        tls_mode: config.tls.as_ref().map(|tls| {
+            if tls.insecure {
+                TlsMode::Insecure
+            } else {
+                TlsMode::Secure
+            }
+        }),
+        redis_connection_info: Some(RedisConnectionInfo {
+            db: config.database.unwrap_or(0) as i64,
+            username: config.username.clone(),
+            password: config.password.clone(),
+            ..Default::default()
+        }),
+    }
+}
+
+/// 读取配置中的TLS证书文件
+///
+/// 未启用TLS或未指定任何证书时返回None,使用系统信任库。
+fn tls_certificates(config: &RedisConfig) -> RedisResult<Option<TlsCertificates>> {
+    let Some(tls) = config.tls.as_ref().filter(|tls| tls.has_custom_certs()) else {
+        return Ok(None);
+    };
+
+    let read = |path: &str| {
+        std::fs::read(path).map_err(|e| {
+            RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "TLS证书文件读取失败",
+                format!("{}: {}", path, e),
+            ))
+        })
+    };
+
+    let client_tls = match (&tls.client_cert, &tls.client_key) {
+        (Some(cert), Some(key)) => Some(ClientTlsConfig {
+            client_cert: read(cert)?,
+            client_key: read(key)?,
+        }),
+        _ => None,
+    };
+    let root_cert = tls.ca_cert.as_deref().map(read).transpose()?;
+
+    Ok(Some(TlsCertificates {
+        client_tls,
+        root_cert,
+    }))
+}
+
+/// 单机客户端 (TCP、TLS或unix socket)
+fn standalone_client(config: &RedisConfig) -> RedisResult<redis::Client> {
+    match tls_certificates(config)? {
+        Some(certs) => redis::Client::build_with_tls(config.to_connection_url(), certs),
+        None => redis::Client::open(config.to_connection_url()),
+    }
+}
+
+/// 集群客户端,节点URL中带有用户名与密码
+fn cluster_client(config: &RedisConfig) -> RedisResult<ClusterClient> {
+    let builder = redis::cluster::ClusterClientBuilder::new(config.node_urls());
+    match tls_certificates(config)? {
+        Some(certs) => builder.certs(certs).build(),
+        None => builder.build(),
+    }
+}
+
+/// 连接测试的原始结果
+pub struct RedisProbe {
+    /// PING响应,正常为 `PONG`
+    pub pong: String,
+    /// 从发起连接到收到PONG的耗时
+    pub latency: Duration,
+    /// `INFO` 输出
+    pub info: Option<String>,
+    /// `CONFIG GET save` 的值
+    pub save_config: Option<String>,
+    /// 账户Cookies key数量
+    pub cookies_keys: Option<usize>,
+}
+
+/// 连接测试使用的直连连接
+enum ProbeConnection {
+    Single(MultiplexedConnection),
+    /// 命令发往命名空间所在槽位的主节点: 所有key都在这个槽位上
+    Cluster {
+        conn: ClusterConnection,
+        routing: RoutingInfo,
+    },
+}
+
+impl ProbeConnection {
+    async fn query<T: FromRedisValue>(&mut self, cmd: &Cmd) -> RedisResult<T> {
+        match self {
+            Self::Single(conn) => cmd.query_async(conn).await,
+            Self::Cluster { conn, routing } => {
+                redis::from_owned_redis_value(conn.route_command(cmd, routing.clone()).await?)
+            }
+        }
+    }
+}
+
+/// 不经连接池建立一次连接,PING并收集健康信息 (连接测试用)
+///
+/// 只有建立连接与PING的错误会返回;`INFO`、`CONFIG` 等命令可能被ACL或托管服务禁用,
+/// 失败时对应字段为None。
+pub async fn probe(config: &RedisConfig) -> RedisResult<RedisProbe> {
+    let namespace = config.namespace().map_err(|e| {
+        RedisError::from((ErrorKind::InvalidClientConfig, "key前缀非法", e.to_string()))
+    })?;
+
+    let start = Instant::now();
+    let mut conn = match &config.topology {
+        RedisTopology::Standalone => {
+            ProbeConnection::Single(standalone_client(config)?.get_multiplexed_async_connection().await?)
+        }
+        RedisTopology::Sentinel { master_name, .. } => {
+            let client = Sentinel::build(config.node_urls())?
+                .async_master_for(master_name, Some(&sentinel_node_info(config)))
+                .await?;
+            ProbeConnection::Single(client.get_multiplexed_async_connection().await?)
+        }
+        RedisTopology::Cluster { .. } => {
+            let slot = get_slot(namespace.cookies_key("").as_bytes());
+            ProbeConnection::Cluster {
+                conn: cluster_client(config)?.get_async_connection().await?,
+                routing: RoutingInfo::SingleNode(SingleNodeRoutingInfo::SpecificNode(Route::new(
+                    slot,
+                    SlotAddr::Master,
+                ))),
+            }
+        }
+    };
+    let pong: String = conn.query(&redis::cmd("PING")).await?;
+    let latency = start.elapsed();
+
+    let info = conn
+        .query::<String>(&redis::cmd("INFO"))
+        .await
+        .inspect_err(|e| tracing::warn!(错误 = %e, "INFO命令失败,健康报告不完整"))
+        .ok();
+    // 返回 ["save", "<规则>"]
+    let save_config = conn
+        .query::<Vec<String>>(redis::cmd("CONFIG").arg("GET").arg("save"))
+        .await
+        .inspect_err(|e| tracing::warn!(错误 = %e, "CONFIG GET save失败,无法确认RDB配置"))
+        .ok()
+        .and_then(|reply| reply.into_iter().nth(1));
+    let cookies_keys = conn
+        .query::<Vec<String>>(redis::cmd("KEYS").arg(namespace.cookies_pattern()))
+        .await
+        .inspect_err(|e| tracing::warn!(错误 = %e, "统计账户key失败"))
+        .ok()
+        .map(|keys| keys.len());
+
+    Ok(RedisProbe {
+        pong,
+        latency,
+        info,
+        save_config,
+        cookies_keys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RedisNode, RedisTls};
+
+    #[test]
+    fn test_pools_are_created_lazily_for_every_topology() {
+        let nodes = vec![RedisNode::new("127.0.0.1", 1)];
+
+        let standalone = RedisPool::from_config(&RedisConfig::default().with_database(3)).unwrap();
+        assert_eq!(standalone.database(), 3);
+        assert!(!standalone.is_cluster());
+
+        let sentinel = RedisPool::from_config(
+            &RedisConfig::sentinel("mymaster", nodes.clone()).with_database(2),
+        )
+        .unwrap();
+        assert_eq!(sentinel.database(), 2);
+
+        let cluster = RedisPool::from_config(&RedisConfig::cluster(nodes)).unwrap();
+        assert!(cluster.is_cluster());
+        assert_eq!(cluster.database(), 0);
+
+        assert!(RedisPool::from_config(&RedisConfig::cluster(vec![])).is_err());
+
+        let socket = RedisPool::from_config(&RedisConfig::unix_socket("/tmp/redis.sock").with_database(1))
+            .unwrap();
+        assert_eq!(socket.database(), 1);
+    }
+
+    #[test]
+    fn test_missing_tls_certificate_is_reported() {
+        let config = RedisConfig::default().with_tls(RedisTls {
+            ca_cert: Some("/nonexistent/redis-ca.pem".to_string()),
+            ..Default::default()
+        });
+        let Err(err) = tls_certificates(&config) else {
+            panic!("证书文件不存在时应报错");
+        };
+        assert_eq!(err.kind(), ErrorKind::InvalidClientConfig);
+        assert!(err.to_string().contains("/nonexistent/redis-ca.pem"));
+        assert!(RedisPool::from_config(&config).is_err());
+
+        // 只启用TLS、使用系统信任库时不读取文件
+        let system_roots = RedisConfig::default().with_tls(RedisTls::default());
+        assert!(tls_certificates(&system_roots).unwrap().is_none());
+        assert!(RedisPool::from_config(&system_roots).is_ok());
+    }
+
+    #[test]
+    fn test_readonly_errors_mark_stale_master() {
+        let readonly = RedisError::from((
+            ErrorKind::ReadOnly,
+            "You can't write against a read only replica.",
+        ));
+        assert!(is_stale_master(&readonly));
+
+        let script = RedisError::from((
+            ErrorKind::ExtensionError,
+            "READONLY You can't write against a read only replica. script: abc",
+        ));
+        assert!(is_stale_master(&script));
+
+        let refused = RedisError::from((ErrorKind::IoError, "Connection refused"));
+        assert!(!is_stale_master(&refused));
+    }
+}
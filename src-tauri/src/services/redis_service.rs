@@ -1,15 +1,434 @@
-use deadpool_redis::{Config, Pool, Runtime};
 use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::models::{CookiesData, StorageError};
+use crate::models::{
+    AccountState, AccountStatus, AccountSummary, CookiesData, CookiesSnapshot, KeyNamespace,
+    RedisConfig, SnapshotSource, SnapshotValidation, StorageError, TagCount, TagMatch,
+    ValidationOutcome, ValidationRecord,
+};
+use crate::services::redis_pool::{RedisConnection, RedisPool};
+
+/// Cookies数据过期时间: 30天
+const EXPIRE_SECONDS: i64 = 30 * 24 * 3600;
+
+/// 默认保留的历史快照数量
+pub const DEFAULT_HISTORY_LIMIT: usize = 10;
+
+/// 回收站默认保留期: 7天
+pub const DEFAULT_TRASH_RETENTION_SECONDS: i64 = 7 * 24 * 3600;
+
+/// 账户摘要读取的Hash字段 (不含cookies值)
+const SUMMARY_FIELDS: [&str; 9] = [
+    "fetched_at",
+    "screen_name",
+    "state",
+    "state_changed_at",
+    "state_reason",
+    "purge_at",
+    "last_outcome",
+    "last_outcome_at",
+    "validation",
+];
+
+/// 原子保存脚本
+///
+/// KEYS: 1=cookies hash, 2=历史列表, 3=版本计数器, 4=账户标签
+/// ARGV: 1=模式, 2=cookies JSON, 3=fetched_at, 4=validated_at,
+///       5=screen_name (空串表示不更新), 6=TTL秒数, 7=快照JSON, 8=历史保留数量, 9=当前时间,
+///       10=验证结果 (空串表示未验证), 11=保存时的验证方式
+///
+/// 写入新Cookies总是让账户回到 `active` 状态 (包括回收站中的账户)。
+/// `if_unchanged` 模式只在账户仍为 `active` 且 `fetched_at` 与传入值相同时写入,不改变状态。
+/// 旧的验证结果描述的是旧Cookies: 写入本地验证过的Cookies时记为本次结果,否则清除。
+/// 验证方式 (`validation`) 总是随Cookies一起替换。
+///
+/// 返回: {是否已存在, 是否写入, 新版本号, 当前fetched_at}
+const SAVE_COOKIES_SCRIPT: &str = r#"
+local exists = redis.call('EXISTS', KEYS[1])
+local incoming = tonumber(ARGV[3])
+
+if ARGV[1] == 'if_newer' and exists == 1 then
+    local current = tonumber(redis.call('HGET', KEYS[1], 'fetched_at') or '0') or 0
+    if current > incoming then
+        return {exists, 0, 0, current}
+    end
+end
+
+if ARGV[1] == 'if_unchanged' then
+    local current = tonumber(redis.call('HGET', KEYS[1], 'fetched_at') or '')
+    local state = redis.call('HGET', KEYS[1], 'state') or 'active'
+    if exists == 0 or current ~= incoming or state ~= 'active' then
+        return {exists, 0, 0, current or 0}
+    end
+end
+
+redis.call('HSET', KEYS[1], 'cookies', ARGV[2], 'fetched_at', ARGV[3], 'validated_at', ARGV[4],
+    'validation', ARGV[11])
+if ARGV[5] ~= '' then
+    redis.call('HSET', KEYS[1], 'screen_name', ARGV[5])
+end
+if ARGV[10] == '' then
+    redis.call('HDEL', KEYS[1], 'last_outcome', 'last_outcome_at')
+else
+    redis.call('HSET', KEYS[1], 'last_outcome', ARGV[10], 'last_outcome_at', ARGV[9])
+end
+redis.call('EXPIRE', KEYS[1], ARGV[6])
+
+if redis.call('HGET', KEYS[1], 'state') ~= 'active' then
+    redis.call('HSET', KEYS[1], 'state', 'active', 'state_changed_at', ARGV[9])
+    redis.call('HDEL', KEYS[1], 'state_reason', 'purge_at', 'previous_state', 'expires_at_before_trash')
+    redis.call('PERSIST', KEYS[4])
+end
+
+local version = redis.call('INCR', KEYS[3])
+local snapshot = cjson.decode(ARGV[7])
+snapshot['version'] = version
+redis.call('LPUSH', KEYS[2], cjson.encode(snapshot))
+redis.call('LTRIM', KEYS[2], 0, tonumber(ARGV[8]) - 1)
+redis.call('EXPIRE', KEYS[2], ARGV[6])
+redis.call('EXPIRE', KEYS[3], ARGV[6])
+
+return {exists, 1, version, incoming}
+"#;
+
+/// 移入回收站脚本
+///
+/// KEYS: 1=cookies hash, 2=历史列表, 3=版本计数器, 4=账户标签,
+///       5=租约, 6=租约冷却, 7=租约使用记录
+/// ARGV: 1=当前时间, 2=保留秒数, 3=UID
+///
+/// 记录删除前的状态和原有过期时间以便恢复,所有数据key的TTL改为保留期,
+/// 到期由Redis自动清除。租约立即释放。已在回收站中的账户保持原清除期限。
+///
+/// 返回: 清除期限 (Unix秒),账户不存在返回nil
+const TRASH_ACCOUNT_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return false
+end
+local state = redis.call('HGET', KEYS[1], 'state') or 'active'
+if state == 'deleted_pending_purge' then
+    return redis.call('HGET', KEYS[1], 'purge_at')
+end
+
+local now = tonumber(ARGV[1])
+local ttl = redis.call('TTL', KEYS[1])
+local expires_at = ''
+if ttl > 0 then
+    expires_at = tostring(now + ttl)
+end
+local purge_at = tostring(now + tonumber(ARGV[2]))
+
+redis.call('HSET', KEYS[1], 'state', 'deleted_pending_purge', 'state_changed_at', ARGV[1],
+    'purge_at', purge_at, 'previous_state', state, 'expires_at_before_trash', expires_at)
+for i = 1, 4 do
+    redis.call('EXPIRE', KEYS[i], ARGV[2])
+end
+redis.call('DEL', KEYS[5], KEYS[6])
+redis.call('ZREM', KEYS[7], ARGV[3])
+return purge_at
+"#;
+
+/// 从回收站恢复脚本
+///
+/// KEYS: 1=cookies hash, 2=历史列表, 3=版本计数器, 4=账户标签
+/// ARGV: 1=当前时间, 2=默认TTL秒数
+///
+/// 恢复删除前的状态。正常账户恢复原有过期时间;若原有效期已在回收站期间过去,
+/// 改为标记 `invalid` 而不是让它立即消失。失效和归档账户不设TTL。
+///
+/// 返回: {是否恢复, 恢复后(或当前)的状态},账户不存在返回nil
+const RESTORE_ACCOUNT_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return false
+end
+local state = redis.call('HGET', KEYS[1], 'state') or 'active'
+if state ~= 'deleted_pending_purge' then
+    return {0, state}
+end
+
+local now = tonumber(ARGV[1])
+local previous = redis.call('HGET', KEYS[1], 'previous_state') or 'active'
+local expires_at = tonumber(redis.call('HGET', KEYS[1], 'expires_at_before_trash') or '')
+redis.call('HDEL', KEYS[1], 'purge_at', 'previous_state', 'expires_at_before_trash')
+
+if previous == 'active' and expires_at and expires_at <= now then
+    previous = 'invalid'
+    redis.call('HSET', KEYS[1], 'state_reason', '在回收站期间超过了Cookies有效期')
+end
+redis.call('HSET', KEYS[1], 'state', previous, 'state_changed_at', ARGV[1])
+
+for i = 1, 3 do
+    if previous ~= 'active' then
+        redis.call('PERSIST', KEYS[i])
+    elseif expires_at then
+        redis.call('EXPIREAT', KEYS[i], expires_at)
+    else
+        redis.call('EXPIRE', KEYS[i], ARGV[2])
+    end
+end
+redis.call('PERSIST', KEYS[4])
+return {1, previous}
+"#;
+
+/// 修改账户状态脚本 (不含回收站)
+///
+/// KEYS: 1=cookies hash, 2=历史列表, 3=版本计数器
+/// ARGV: 1=目标状态, 2=当前时间, 3=原因 (空串表示清除), 4=默认TTL秒数
+///
+/// `invalid`/`archived` 移除TTL,避免被标记的账户悄悄过期;
+/// 从其他状态回到 `active` 时重新设置默认TTL。
+///
+/// 返回: {是否修改, 修改前的状态},账户不存在返回nil
+const SET_ACCOUNT_STATE_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return false
+end
+local current = redis.call('HGET', KEYS[1], 'state') or 'active'
+if current == 'deleted_pending_purge' then
+    return {0, current}
+end
+
+if current ~= ARGV[1] then
+    redis.call('HSET', KEYS[1], 'state', ARGV[1], 'state_changed_at', ARGV[2])
+end
+if ARGV[3] == '' then
+    redis.call('HDEL', KEYS[1], 'state_reason')
+else
+    redis.call('HSET', KEYS[1], 'state_reason', ARGV[3])
+end
+
+for i = 1, 3 do
+    if ARGV[1] ~= 'active' then
+        redis.call('PERSIST', KEYS[i])
+    elseif current ~= 'active' then
+        redis.call('EXPIRE', KEYS[i], ARGV[4])
+    end
+end
+return {1, current}
+"#;
+
+/// 记录验证结果脚本
+///
+/// KEYS: 1=cookies hash, 2=历史列表, 3=版本计数器
+/// ARGV: 1=验证结果, 2=验证时间, 3=是否标记失效 ('1'/空串), 4=失效原因
+///
+/// 结果为 `valid` 时同时更新 `validated_at`。
+/// 结果说明账户已无法使用时,`active` 账户被标记为 `invalid` 并移除TTL,
+/// 避免悄悄过期;归档和回收站中的账户保持原状。
+///
+/// 返回: 0=账户不存在, 1=已记录, 2=已记录并标记失效
+const RECORD_VALIDATION_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+redis.call('HSET', KEYS[1], 'last_outcome', ARGV[1], 'last_outcome_at', ARGV[2])
+if ARGV[1] == 'valid' then
+    redis.call('HSET', KEYS[1], 'validated_at', ARGV[2])
+end
+
+local state = redis.call('HGET', KEYS[1], 'state') or 'active'
+if ARGV[3] ~= '1' or state ~= 'active' then
+    return 1
+end
+redis.call('HSET', KEYS[1], 'state', 'invalid', 'state_changed_at', ARGV[2],
+    'state_reason', ARGV[4])
+for i = 1, 3 do
+    redis.call('PERSIST', KEYS[i])
+end
+return 2
+"#;
+
+/// 完成保存后验证脚本
+///
+/// KEYS: 1=cookies hash
+/// ARGV: 1=保存时的fetched_at, 2=验证方式, 3=验证结果 (空串表示不记录), 4=验证时间
+///
+/// 只有账户仍是那次保存的Cookies且仍在等待验证时才写入:
+/// 期间重新登录或导入的Cookies有自己的验证方式,不能被旧的验证结论覆盖。
+///
+/// 返回: 是否写入
+const FINISH_PENDING_VALIDATION_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], 'validation') ~= 'pending'
+    or redis.call('HGET', KEYS[1], 'fetched_at') ~= ARGV[1] then
+    return 0
+end
+redis.call('HSET', KEYS[1], 'validation', ARGV[2])
+if ARGV[3] ~= '' then
+    redis.call('HSET', KEYS[1], 'last_outcome', ARGV[3], 'last_outcome_at', ARGV[4])
+    if ARGV[3] == 'valid' then
+        redis.call('HSET', KEYS[1], 'validated_at', ARGV[4])
+    end
+end
+return 1
+"#;
+
+/// 迁移单个key的脚本
+///
+/// KEYS: 1=源key, 2=目标key
+/// ARGV: 1=是否删除源key ("1"/"0")
+///
+/// 返回: 1=已复制,0=目标已存在而跳过,2=目标中的快照更新而保留源key,-1=源key已消失。
+///
+/// 目标已存在时一般不覆盖,删除源key时照常删除。账户Cookies (带 `fetched_at` 的Hash)
+/// 两边都存在时比较获取时间: 源更新则覆盖目标,目标更新则保留源key不删除,
+/// 切换前后各自写入的新Cookies都不会丢失。
+/// DUMP/RESTORE保留数据类型和剩余TTL。
+const MIGRATE_KEY_SCRIPT: &str = r#"
+local source_type = redis.call('TYPE', KEYS[1])['ok']
+if source_type == 'none' then
+    return -1
+end
+if redis.call('EXISTS', KEYS[2]) == 1 then
+    local incoming = nil
+    local current = nil
+    if source_type == 'hash' and redis.call('TYPE', KEYS[2])['ok'] == 'hash' then
+        incoming = tonumber(redis.call('HGET', KEYS[1], 'fetched_at') or '')
+        current = tonumber(redis.call('HGET', KEYS[2], 'fetched_at') or '')
+    end
+    if incoming == nil or current == nil then
+        if ARGV[1] == '1' then
+            redis.call('DEL', KEYS[1])
+        end
+        return 0
+    end
+    if incoming <= current then
+        return 2
+    end
+end
+local payload = redis.call('DUMP', KEYS[1])
+local ttl = redis.call('PTTL', KEYS[1])
+if ttl < 0 then
+    ttl = 0
+end
+redis.call('RESTORE', KEYS[2], ttl, payload, 'REPLACE')
+if ARGV[1] == '1' then
+    redis.call('DEL', KEYS[1])
+end
+return 1
+"#;
+
+/// 迁移时每批SCAN的key数量
+const MIGRATE_SCAN_COUNT: usize = 200;
+
+/// key前缀迁移报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyMigrationReport {
+    /// 源前缀
+    pub from_prefix: String,
+
+    /// 目标前缀
+    pub to_prefix: String,
+
+    /// 扫描到的源key数量
+    pub scanned: usize,
+
+    /// 成功复制的key数量
+    pub copied: usize,
+
+    /// 目标已存在而跳过的key数量
+    pub skipped_existing: usize,
+
+    /// 目标中的Cookies更新而保留的源key数量 (不会被删除)
+    #[serde(default)]
+    pub kept_source: usize,
+
+    /// 扫描后、复制前已被删除或过期的key数量
+    pub vanished: usize,
+
+    /// 是否删除了源key
+    pub deleted_source: bool,
+}
+
+/// `notify-keyspace-events` 是否覆盖账户变化
+///
+/// 需要 `K` (keyspace频道),以及 `g` (DEL/EXPIRE/RENAME)、`h` (HSET) 和 `x` (过期),
+/// `A` 是 `g$lshzxetd` 的别名。
+///
+/// # 示例
+/// ```
+/// use weibo_login::services::redis_service::keyspace_flags_cover_accounts;
+///
+/// assert!(keyspace_flags_cover_accounts("KA"));
+/// assert!(keyspace_flags_cover_accounts("Kghx"));
+/// assert!(!keyspace_flags_cover_accounts("Ex"));
+/// assert!(!keyspace_flags_cover_accounts(""));
+/// ```
+pub fn keyspace_flags_cover_accounts(flags: &str) -> bool {
+    flags.contains('K')
+        && (flags.contains('A') || ['g', 'h', 'x'].iter().all(|flag| flags.contains(*flag)))
+}
+
+/// 写入模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveMode {
+    /// 无条件覆盖 (如回滚到旧版本)
+    Overwrite,
+
+    /// 比较并设置: 仅当已存数据的 `fetched_at` 不晚于本次写入时才覆盖
+    IfNewer,
+
+    /// 比较并设置: 仅当账户仍为 `active` 且已存数据的 `fetched_at` 与本次写入相同时才覆盖
+    ///
+    /// 用于在已存数据基础上修改后写回 (如保活刷新): 读取之后有任何新的登录、
+    /// 回滚或状态变更,本次写入即被放弃。
+    IfUnchanged,
+}
+
+impl SaveMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SaveMode::Overwrite => "overwrite",
+            SaveMode::IfNewer => "if_newer",
+            SaveMode::IfUnchanged => "if_unchanged",
+        }
+    }
+}
+
+/// 并发写入时胜出的一方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveWinner {
+    /// 本次写入生效
+    Incoming,
+
+    /// 已存数据更新,本次写入被放弃
+    Existing,
+}
+
+/// 保存结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveOutcome {
+    /// 实际写入的key
+    pub redis_key: String,
+
+    /// 写入前key是否已存在
+    pub is_overwrite: bool,
+
+    /// 胜出的一方
+    pub winner: SaveWinner,
+
+    /// 新快照版本号 (本次写入被放弃时为None)
+    pub version: Option<u64>,
+
+    /// 写入后Redis中生效数据的获取时间
+    pub current_fetched_at: Option<chrono::DateTime<chrono::Utc>>,
+}
 
 /// Redis服务
 ///
 /// 管理连接池,提供Cookies存储/查询/删除操作。
 /// 职责单一:仅处理数据持久化,不涉及业务逻辑。
 pub struct RedisService {
-    pool: Pool,
+    /// 连接池: 单机、哨兵或集群
+    pool: RedisPool,
+    /// 每个UID保留的历史快照数量
+    history_limit: usize,
+    /// key命名空间: 所有key由此生成
+    namespace: KeyNamespace,
+    /// 回收站保留秒数: 删除后多久彻底清除
+    trash_retention_seconds: i64,
 }
 
 impl RedisService {
@@ -30,18 +449,133 @@ impl RedisService {
     /// # }
     /// ```
     pub fn new(redis_url: &str) -> Result<Self, StorageError> {
-        let config = Config::from_url(redis_url);
-        let pool = config.create_pool(Some(Runtime::Tokio1)).map_err(|e| {
+        let pool = RedisPool::from_url(redis_url).map_err(|e| {
             tracing::error!(
                 Redis连接URL = %redis_url,
                 错误 = %e,
                 "创建Redis连接池失败"
             );
-            StorageError::RedisConnectionFailed(e.to_string())
+            e
         })?;
 
         tracing::info!(Redis连接URL = %redis_url, "Redis连接池创建成功");
-        Ok(Self { pool })
+        Ok(Self::with_pool(pool))
+    }
+
+    /// 按配置初始化 (单机、哨兵或集群)
+    ///
+    /// key命名空间取自配置;集群模式下自动带hash-tag。
+    ///
+    /// # 错误
+    /// 返回 `StorageError::RedisConnectionFailed` 如果配置不完整或连接池创建失败
+    ///
+    /// # 示例
+    /// ```no_run
+    /// use weibo_login::models::{RedisConfig, RedisNode};
+    /// use weibo_login::services::RedisService;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = RedisConfig::sentinel("mymaster", vec![RedisNode::new("10.0.0.1", 26379)]);
+    /// let service = RedisService::from_config(&config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_config(config: &RedisConfig) -> Result<Self, StorageError> {
+        let namespace = config
+            .namespace()
+            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+        let pool = RedisPool::from_config(config).map_err(|e| {
+            tracing::error!(
+                Redis配置 = %config.summary_for_logging(),
+                错误 = %e,
+                "创建Redis连接池失败"
+            );
+            e
+        })?;
+
+        tracing::info!(Redis配置 = %config.summary_for_logging(), "Redis连接池创建成功");
+        Ok(Self::with_pool(pool).with_namespace(namespace))
+    }
+
+    fn with_pool(pool: RedisPool) -> Self {
+        Self {
+            pool,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            namespace: KeyNamespace::default(),
+            trash_retention_seconds: DEFAULT_TRASH_RETENTION_SECONDS,
+        }
+    }
+
+    /// 设置历史快照保留数量 (构建器模式)
+    ///
+    /// 最少保留1个版本,否则回滚无从谈起。
+    pub fn with_history_limit(mut self, history_limit: usize) -> Self {
+        self.history_limit = history_limit.max(1);
+        self
+    }
+
+    /// 设置key命名空间 (构建器模式)
+    ///
+    /// 默认命名空间为 `weibo`,与历史版本的key布局一致。
+    pub fn with_namespace(mut self, namespace: KeyNamespace) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// 设置回收站保留期 (构建器模式)
+    ///
+    /// 最短1秒,删除的账户在保留期内可以恢复。
+    pub fn with_trash_retention(mut self, retention: std::time::Duration) -> Self {
+        self.trash_retention_seconds = (retention.as_secs() as i64).max(1);
+        self
+    }
+
+    /// 当前key命名空间
+    pub fn namespace(&self) -> &KeyNamespace {
+        &self.namespace
+    }
+
+    /// 从连接池获取连接
+    ///
+    /// 供同一Redis上的其他服务 (租约、审计等) 复用连接池。
+    pub async fn connection(&self) -> Result<RedisConnection, StorageError> {
+        self.pool.get().await
+    }
+
+    /// 当前连接的数据库编号 (keyspace通知频道名需要)
+    pub fn database(&self) -> i64 {
+        self.pool.database()
+    }
+
+    /// 建立Pub/Sub专用连接
+    ///
+    /// 集群模式不支持,返回错误。
+    pub async fn pubsub(&self) -> Result<redis::aio::PubSub, StorageError> {
+        self.pool.pubsub().await
+    }
+
+    /// 服务端是否开启了账户变化所需的keyspace通知
+    ///
+    /// 读取 `notify-keyspace-events` 配置。托管Redis常禁用CONFIG命令,
+    /// 此时返回错误,调用方应退回轮询。集群模式同样返回错误。
+    pub async fn keyspace_notifications_enabled(&self) -> Result<bool, StorageError> {
+        if self.pool.is_cluster() {
+            return Err(StorageError::CommandFailed(
+                "集群模式不支持keyspace通知订阅".to_string(),
+            ));
+        }
+
+        let mut conn = self.connection().await?;
+
+        let config: Vec<String> = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("notify-keyspace-events")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        Ok(config
+            .get(1)
+            .is_some_and(|flags| keyspace_flags_cover_accounts(flags)))
     }
 
     /// 准备Redis字段数据
@@ -61,73 +595,179 @@ impl RedisService {
     ///
     /// Redis数据结构:
     /// - 类型: Hash
-    /// - Key: `weibo:cookies:{uid}`
+    /// - Key: `{prefix}:cookies:{uid}` (由服务的命名空间决定,忽略 `cookies_data.redis_key`)
     /// - Fields: `cookies`, `fetched_at`, `validated_at`, `screen_name`
     /// - TTL: 30天
     ///
+    /// 同时追加一个历史快照到 `{prefix}:history:{uid}`,见 [`Self::list_history`]。
+    ///
+    /// 所有写入 (字段、TTL、历史快照) 由一个Lua脚本在服务端原子完成,
+    /// 单次往返,不会出现缺少TTL或昵称的中间状态。
+    ///
     /// # 参数
     /// - `cookies_data`: 待保存的cookies数据
+    /// - `source`: 快照来源
+    /// - `validation`: 保存前的验证结果
+    /// - `mode`: 写入模式,`IfNewer` 时较旧的登录不会覆盖较新的数据,
+    ///   `IfUnchanged` 时读取之后被修改过的数据不会被覆盖
     ///
     /// # 返回值
-    /// - `Ok(true)`: 覆盖了已存在的数据
-    /// - `Ok(false)`: 新建数据
+    /// `SaveOutcome`,包含是否覆盖、哪一方胜出以及新快照版本号
     ///
     /// # 错误
     /// 返回 `StorageError` 如果Redis操作失败
-    pub async fn save_cookies(&self, cookies_data: &CookiesData) -> Result<bool, StorageError> {
-        let mut conn = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
-
-        // 检查是否已存在
-        let exists: bool = conn
-            .exists(&cookies_data.redis_key)
-            .await
-            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+    pub async fn save_cookies(
+        &self,
+        cookies_data: &CookiesData,
+        source: SnapshotSource,
+        validation: SnapshotValidation,
+        mode: SaveMode,
+    ) -> Result<SaveOutcome, StorageError> {
+        let mut conn = self.connection().await?;
 
         // 准备字段数据
         let (cookies_json, fetched_at_str, validated_at_str) =
             Self::prepare_redis_fields(cookies_data)?;
 
-        // 保存基础字段
-        let fields = vec![
-            ("cookies", cookies_json.as_str()),
-            ("fetched_at", fetched_at_str.as_str()),
-            ("validated_at", validated_at_str.as_str()),
-        ];
+        // 快照版本号由脚本内的INCR决定,此处先占位
+        let snapshot = CookiesSnapshot::from_cookies_data(0, cookies_data, source, validation);
+        let snapshot_json = serde_json::to_string(&snapshot)
+            .map_err(|e| StorageError::SerializationError(e.to_string()))?;
 
-        redis::pipe()
-            .atomic()
-            .hset_multiple(&cookies_data.redis_key, &fields)
-            .ignore()
-            .query_async::<()>(&mut *conn)
-            .await
-            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        let redis_key = self.namespace.cookies_key(&cookies_data.uid);
 
-        // 保存昵称(可选)
-        if let Some(ref screen_name) = cookies_data.screen_name {
-            conn.hset::<_, _, _, ()>(&cookies_data.redis_key, "screen_name", screen_name)
+        let (exists, applied, version, current_fetched_at): (i64, i64, u64, i64) =
+            redis::Script::new(SAVE_COOKIES_SCRIPT)
+                .key(&redis_key)
+                .key(self.namespace.history_key(&cookies_data.uid))
+                .key(self.namespace.history_seq_key(&cookies_data.uid))
+                .key(self.namespace.account_tags_key(&cookies_data.uid))
+                .arg(mode.as_str())
+                .arg(&cookies_json)
+                .arg(&fetched_at_str)
+                .arg(&validated_at_str)
+                .arg(cookies_data.screen_name.as_deref().unwrap_or(""))
+                .arg(EXPIRE_SECONDS)
+                .arg(&snapshot_json)
+                .arg(self.history_limit)
+                .arg(chrono::Utc::now().timestamp())
+                .arg(match validation {
+                    SnapshotValidation::Verified => ValidationOutcome::Valid.as_str(),
+                    SnapshotValidation::TrustedServer
+                    | SnapshotValidation::Unverified
+                    | SnapshotValidation::Pending
+                    | SnapshotValidation::Mismatch => "",
+                })
+                .arg(validation.as_str())
+                .invoke_async(&mut conn)
                 .await
                 .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let outcome = SaveOutcome {
+            redis_key,
+            is_overwrite: exists == 1,
+            winner: if applied == 1 {
+                SaveWinner::Incoming
+            } else {
+                SaveWinner::Existing
+            },
+            version: (applied == 1).then_some(version),
+            current_fetched_at: chrono::DateTime::from_timestamp(current_fetched_at, 0),
+        };
+
+        match outcome.winner {
+            SaveWinner::Incoming => tracing::info!(
+                用户ID = %cookies_data.uid,
+                Redis键 = %outcome.redis_key,
+                是否覆盖 = %outcome.is_overwrite,
+                历史版本 = %version,
+                来源 = ?source,
+                Cookies样本 = %cookies_data.sample_for_logging(),
+                "Cookies已保存到Redis"
+            ),
+            SaveWinner::Existing => tracing::warn!(
+                用户ID = %cookies_data.uid,
+                Redis键 = %outcome.redis_key,
+                来源 = ?source,
+                写入获取时间 = %fetched_at_str,
+                已存获取时间 = %current_fetched_at,
+                "已存在更新的Cookies,放弃本次写入"
+            ),
         }
 
-        // 设置30天过期
-        const EXPIRE_SECONDS: i64 = 30 * 24 * 3600;
-        conn.expire::<_, ()>(&cookies_data.redis_key, EXPIRE_SECONDS)
+        Ok(outcome)
+    }
+
+    /// 列出历史快照
+    ///
+    /// # 返回值
+    /// 按版本号降序排列的快照 (最新在前),包含cookies值,
+    /// 调用方负责在返回前端前转换为摘要。
+    ///
+    /// # 错误
+    /// - `StorageError::SerializationError`: 快照JSON损坏
+    pub async fn list_history(&self, uid: &str) -> Result<Vec<CookiesSnapshot>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let raw: Vec<String> = conn
+            .lrange(self.namespace.history_key(uid), 0, -1)
             .await
             .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
 
-        tracing::info!(
-            用户ID = %cookies_data.uid,
-            Redis键 = %cookies_data.redis_key,
-            是否覆盖 = %exists,
-            Cookies样本 = %cookies_data.sample_for_logging(),
-            "Cookies已保存到Redis"
-        );
+        let snapshots = raw
+            .iter()
+            .map(|json| {
+                serde_json::from_str::<CookiesSnapshot>(json)
+                    .map_err(|e| StorageError::SerializationError(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tracing::debug!(用户ID = %uid, 快照数量 = %snapshots.len(), "读取Cookies历史");
+        Ok(snapshots)
+    }
+
+    /// 获取指定版本的快照
+    ///
+    /// # 错误
+    /// - `StorageError::NotFound`: 版本不存在或已被裁剪
+    pub async fn get_snapshot(
+        &self,
+        uid: &str,
+        version: u64,
+    ) -> Result<CookiesSnapshot, StorageError> {
+        self.list_history(uid)
+            .await?
+            .into_iter()
+            .find(|snapshot| snapshot.version == version)
+            .ok_or_else(|| StorageError::NotFound(format!("{}@v{}", uid, version)))
+    }
+
+    /// 回滚到指定历史版本
+    ///
+    /// 将快照内容重新写入 `{prefix}:cookies:{uid}`,并以 `Rollback` 来源追加新快照。
+    /// 回滚本身也是一个版本,因此可以再次回滚撤销。
+    ///
+    /// # 返回值
+    /// 回滚后的 `CookiesData`
+    pub async fn rollback_to(&self, uid: &str, version: u64) -> Result<CookiesData, StorageError> {
+        let snapshot = self.get_snapshot(uid, version).await?;
+
+        let mut cookies_data = CookiesData::new(snapshot.uid.clone(), snapshot.cookies)
+            .with_namespace(&self.namespace);
+        cookies_data.fetched_at = snapshot.fetched_at;
+        cookies_data.validated_at = snapshot.validated_at;
+        cookies_data.screen_name = snapshot.screen_name;
+
+        self.save_cookies(
+            &cookies_data,
+            SnapshotSource::Rollback,
+            snapshot.validation,
+            SaveMode::Overwrite,
+        )
+        .await?;
 
-        Ok(exists)
+        tracing::info!(用户ID = %uid, 目标版本 = %version, "Cookies已回滚");
+        Ok(cookies_data)
     }
 
     /// 查询Cookies
@@ -139,17 +779,13 @@ impl RedisService {
     /// 完整的 `CookiesData` 结构
     ///
     /// # 错误
-    /// - `StorageError::NotFound`: UID不存在
+    /// - `StorageError::NotFound`: UID不存在或账户在回收站中
     /// - `StorageError::SerializationError`: 数据格式错误
     /// - `StorageError::RedisConnectionFailed`: 连接失败
     pub async fn query_cookies(&self, uid: &str) -> Result<CookiesData, StorageError> {
-        let mut conn = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+        let mut conn = self.connection().await?;
 
-        let redis_key = format!("weibo:cookies:{}", uid);
+        let redis_key = self.namespace.cookies_key(uid);
 
         // 检查是否存在
         let exists: bool = conn
@@ -168,6 +804,12 @@ impl RedisService {
             .await
             .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
 
+        // 回收站中的账户对调用方不可见,只能恢复或清除
+        if AccountStatus::from_fields(&data).state.is_trashed() {
+            tracing::warn!(用户ID = %uid, "账户在回收站中");
+            return Err(StorageError::NotFound(uid.to_string()));
+        }
+
         // 反序列化cookies
         let cookies: HashMap<String, String> = serde_json::from_str(
             data.get("cookies")
@@ -207,7 +849,224 @@ impl RedisService {
         Ok(cookies_data)
     }
 
-    /// 删除Cookies
+    /// 将账户移入回收站
+    ///
+    /// 数据保留到清除期限,期间可用 [`Self::restore_account`] 恢复,
+    /// 到期后由Redis自动删除。持有的租约立即失效。
+    ///
+    /// # 返回值
+    /// 清除期限;UID不存在时返回None (幂等操作)
+    pub async fn trash_account(
+        &self,
+        uid: &str,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let purge_at: Option<i64> = redis::Script::new(TRASH_ACCOUNT_SCRIPT)
+            .key(self.namespace.cookies_key(uid))
+            .key(self.namespace.history_key(uid))
+            .key(self.namespace.history_seq_key(uid))
+            .key(self.namespace.account_tags_key(uid))
+            .key(self.namespace.lease_key(uid))
+            .key(self.namespace.lease_cooldown_key(uid))
+            .key(self.namespace.lease_usage_key())
+            .arg(chrono::Utc::now().timestamp())
+            .arg(self.trash_retention_seconds)
+            .arg(uid)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let purge_at = purge_at.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+        match purge_at {
+            Some(purge_at) => {
+                tracing::info!(用户ID = %uid, 清除期限 = %purge_at, "账户已移入回收站")
+            }
+            None => tracing::debug!(用户ID = %uid, "账户不存在,无需删除"),
+        }
+        Ok(purge_at)
+    }
+
+    /// 从回收站恢复账户
+    ///
+    /// # 返回值
+    /// 恢复后的状态 (通常为删除前的状态)
+    ///
+    /// # 错误
+    /// - `StorageError::NotFound`: 账户不存在或已被清除
+    /// - `StorageError::InvalidState`: 账户不在回收站中
+    pub async fn restore_account(&self, uid: &str) -> Result<AccountState, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let result: Option<(i64, String)> = redis::Script::new(RESTORE_ACCOUNT_SCRIPT)
+            .key(self.namespace.cookies_key(uid))
+            .key(self.namespace.history_key(uid))
+            .key(self.namespace.history_seq_key(uid))
+            .key(self.namespace.account_tags_key(uid))
+            .arg(chrono::Utc::now().timestamp())
+            .arg(EXPIRE_SECONDS)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let (restored, state) = result.ok_or_else(|| StorageError::NotFound(uid.to_string()))?;
+        if restored == 0 {
+            return Err(StorageError::InvalidState(format!(
+                "账户 {} 不在回收站中 (当前状态: {})",
+                uid, state
+            )));
+        }
+
+        let state = AccountState::parse(&state).unwrap_or_default();
+        tracing::info!(用户ID = %uid, 恢复后状态 = ?state, "账户已从回收站恢复");
+        Ok(state)
+    }
+
+    /// 修改账户状态
+    ///
+    /// 用于标记失效 (验证失败)、归档或重新启用账户。
+    /// 移入回收站请使用 [`Self::trash_account`]。
+    ///
+    /// # 参数
+    /// - `state`: 目标状态,不能是 `DeletedPendingPurge`
+    /// - `reason`: 状态原因,None表示清除原有原因
+    ///
+    /// # 返回值
+    /// 修改前的状态
+    ///
+    /// # 错误
+    /// - `StorageError::NotFound`: 账户不存在
+    /// - `StorageError::InvalidState`: 目标是回收站,或账户在回收站中
+    pub async fn set_account_state(
+        &self,
+        uid: &str,
+        state: AccountState,
+        reason: Option<&str>,
+    ) -> Result<AccountState, StorageError> {
+        if state.is_trashed() {
+            return Err(StorageError::InvalidState(
+                "移入回收站请使用删除操作".to_string(),
+            ));
+        }
+
+        let mut conn = self.connection().await?;
+
+        let result: Option<(i64, String)> = redis::Script::new(SET_ACCOUNT_STATE_SCRIPT)
+            .key(self.namespace.cookies_key(uid))
+            .key(self.namespace.history_key(uid))
+            .key(self.namespace.history_seq_key(uid))
+            .arg(state.as_str())
+            .arg(chrono::Utc::now().timestamp())
+            .arg(reason.unwrap_or(""))
+            .arg(EXPIRE_SECONDS)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let (changed, previous) = result.ok_or_else(|| StorageError::NotFound(uid.to_string()))?;
+        if changed == 0 {
+            return Err(StorageError::InvalidState(format!(
+                "账户 {} 在回收站中,请先恢复",
+                uid
+            )));
+        }
+
+        let previous = AccountState::parse(&previous).unwrap_or_default();
+        tracing::info!(
+            用户ID = %uid,
+            原状态 = ?previous,
+            新状态 = ?state,
+            原因 = ?reason,
+            "账户状态已修改"
+        );
+        Ok(previous)
+    }
+
+    /// 记录账户最近一次验证的结果
+    ///
+    /// 写入 `last_outcome` / `last_outcome_at`,结果为 `Valid` 时同时更新 `validated_at`。
+    /// 不修改Cookies。结果为过期、封禁或锁定时,正常状态的账户被标记为 `Invalid`
+    /// (保留数据,不会自动过期);其他状态的账户不受影响。
+    ///
+    /// # 错误
+    /// - `StorageError::NotFound`: 账户不存在
+    pub async fn record_validation(
+        &self,
+        uid: &str,
+        outcome: ValidationOutcome,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), StorageError> {
+        let mut conn = self.connection().await?;
+
+        let reason = format!("验证未通过: {}", outcome.as_str());
+        let recorded: i64 = redis::Script::new(RECORD_VALIDATION_SCRIPT)
+            .key(self.namespace.cookies_key(uid))
+            .key(self.namespace.history_key(uid))
+            .key(self.namespace.history_seq_key(uid))
+            .arg(outcome.as_str())
+            .arg(at.timestamp())
+            .arg(if outcome.invalidates_account() { "1" } else { "" })
+            .arg(&reason)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        match recorded {
+            0 => return Err(StorageError::NotFound(uid.to_string())),
+            2 => tracing::warn!(
+                用户ID = %uid,
+                验证结果 = %outcome.as_str(),
+                "验证未通过,账户已标记为失效"
+            ),
+            _ => tracing::debug!(用户ID = %uid, 验证结果 = %outcome.as_str(), "验证结果已记录"),
+        }
+        Ok(())
+    }
+
+    /// 写入保存后验证的结论
+    ///
+    /// 扫码登录采用异步验证策略时,Cookies先以 `Pending` 保存,
+    /// 后台验证完成后调用此方法把验证方式改为 `Verified` / `Mismatch` / `Unverified`,
+    /// 有确定结果时同时记录到 `last_outcome`。
+    ///
+    /// # 参数
+    /// - `fetched_at`: 保存的Cookies的获取时间,用于确认账户没有被更新的Cookies替换
+    /// - `outcome`: 验证结果,无法判断时为None
+    ///
+    /// # 返回
+    /// 是否写入 (账户已不存在、已被替换或不在等待验证时为false)
+    pub async fn finish_pending_validation(
+        &self,
+        uid: &str,
+        fetched_at: chrono::DateTime<chrono::Utc>,
+        validation: SnapshotValidation,
+        outcome: Option<ValidationOutcome>,
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let applied: i64 = redis::Script::new(FINISH_PENDING_VALIDATION_SCRIPT)
+            .key(self.namespace.cookies_key(uid))
+            .arg(fetched_at.timestamp())
+            .arg(validation.as_str())
+            .arg(outcome.map(|outcome| outcome.as_str()).unwrap_or(""))
+            .arg(at.timestamp())
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        tracing::debug!(
+            用户ID = %uid,
+            验证方式 = %validation.as_str(),
+            已写入 = %(applied == 1),
+            "保存后验证已完成"
+        );
+        Ok(applied == 1)
+    }
+
+    /// 永久删除账户
+    ///
+    /// 不经过回收站,立即删除 (用于清除回收站中的账户)。
     ///
     /// # 参数
     /// - `uid`: 微博用户ID
@@ -216,16 +1075,36 @@ impl RedisService {
     /// 返回 `StorageError` 如果Redis操作失败
     ///
     /// # 注意
-    /// 即使UID不存在,也返回成功 (幂等操作)
+    /// - 即使UID不存在,也返回成功 (幂等操作)
+    /// - 历史快照、标签和租约记录一并删除
     pub async fn delete_cookies(&self, uid: &str) -> Result<(), StorageError> {
-        let mut conn = self
-            .pool
-            .get()
+        let mut conn = self.connection().await?;
+
+        let redis_key = self.namespace.cookies_key(uid);
+        let account_tags_key = self.namespace.account_tags_key(uid);
+
+        // 从各标签集合中移除该账户
+        let tags: Vec<String> = conn
+            .smembers(&account_tags_key)
             .await
-            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
 
-        let redis_key = format!("weibo:cookies:{}", uid);
-        conn.del::<_, ()>(&redis_key)
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for tag in &tags {
+            pipe.srem(self.namespace.tag_key(tag), uid).ignore();
+        }
+        pipe.del(&[
+            redis_key.clone(),
+            self.namespace.history_key(uid),
+            self.namespace.history_seq_key(uid),
+            account_tags_key,
+            self.namespace.lease_key(uid),
+            self.namespace.lease_cooldown_key(uid),
+        ])
+        .ignore();
+        pipe.zrem(self.namespace.lease_usage_key(), uid).ignore();
+        pipe.query_async::<()>(&mut conn)
             .await
             .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
 
@@ -233,39 +1112,481 @@ impl RedisService {
         Ok(())
     }
 
-    /// 列出所有已保存的UID
+    /// 列出所有未删除账户的UID
     ///
-    /// 扫描所有 `weibo:cookies:*` key,提取UID列表。
+    /// 扫描所有 `{prefix}:cookies:*` key,提取UID列表,回收站中的账户不包含在内。
     /// 用于账户管理界面展示。
     ///
     /// # 注意
     /// 使用 `KEYS` 命令。对于微博 cookies 场景（数量通常 <1000），性能影响可忽略。
     /// 若需处理大量数据，可改用 SCAN 迭代器实现。
     pub async fn list_all_uids(&self) -> Result<Vec<String>, StorageError> {
-        let mut conn = self
-            .pool
-            .get()
-            .await
-            .map_err(|e| StorageError::RedisConnectionFailed(e.to_string()))?;
+        let uids: Vec<String> = self
+            .list_accounts(&[
+                AccountState::Active,
+                AccountState::Invalid,
+                AccountState::Archived,
+            ])
+            .await?
+            .into_iter()
+            .map(|account| account.uid)
+            .collect();
+
+        tracing::debug!(uid数量 = %uids.len(), "从Redis列出所有UID");
+        Ok(uids)
+    }
+
+    /// 按状态列出账户
+    ///
+    /// # 参数
+    /// - `states`: 要包含的状态,为空时返回所有账户 (含回收站)
+    ///
+    /// # 返回值
+    /// 按UID排序的账户摘要,不含cookies值
+    pub async fn list_accounts(
+        &self,
+        states: &[AccountState],
+    ) -> Result<Vec<AccountSummary>, StorageError> {
+        let mut conn = self.connection().await?;
 
-        let pattern = "weibo:cookies:*";
         let keys: Vec<String> = redis::cmd("KEYS")
-            .arg(pattern)
-            .query_async(&mut *conn)
+            .arg(self.namespace.cookies_pattern())
+            .query_async(&mut conn)
             .await
             .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
 
         let uids: Vec<String> = keys
             .iter()
-            .filter_map(|key| key.strip_prefix("weibo:cookies:").map(String::from))
+            .filter_map(|key| self.namespace.uid_from_cookies_key(key).map(String::from))
+            .collect();
+
+        let mut accounts: Vec<AccountSummary> = self
+            .account_summaries(&mut conn, uids)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter(|account| states.is_empty() || states.contains(&account.status.state))
             .collect();
+        accounts.sort_by(|a, b| a.uid.cmp(&b.uid));
 
         tracing::debug!(
             key数量 = %keys.len(),
-            uid数量 = %uids.len(),
-            "从Redis列出所有UID"
+            状态 = ?states,
+            账户数量 = %accounts.len(),
+            "按状态列出账户"
         );
-        Ok(uids)
+        Ok(accounts)
+    }
+
+    /// 获取账户状态
+    ///
+    /// # 错误
+    /// - `StorageError::NotFound`: 账户不存在
+    pub async fn account_status(&self, uid: &str) -> Result<AccountStatus, StorageError> {
+        self.account_summary(uid)
+            .await?
+            .map(|account| account.status)
+            .ok_or_else(|| StorageError::NotFound(uid.to_string()))
+    }
+
+    /// 获取单个账户摘要,账户不存在时返回None
+    pub async fn account_summary(&self, uid: &str) -> Result<Option<AccountSummary>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        Ok(self
+            .account_summaries(&mut conn, vec![uid.to_string()])
+            .await?
+            .pop()
+            .flatten())
+    }
+
+    /// 保留处于指定状态的UID (保持原有顺序,不存在的UID被丢弃)
+    pub async fn filter_by_state(
+        &self,
+        uids: Vec<String>,
+        states: &[AccountState],
+    ) -> Result<Vec<String>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        Ok(self
+            .account_summaries(&mut conn, uids)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter(|account| states.contains(&account.status.state))
+            .map(|account| account.uid)
+            .collect())
+    }
+
+    /// 批量读取账户摘要 (单次往返)
+    ///
+    /// 与输入一一对应,key已不存在的UID对应None。
+    async fn account_summaries(
+        &self,
+        conn: &mut RedisConnection,
+        uids: Vec<String>,
+    ) -> Result<Vec<Option<AccountSummary>>, StorageError> {
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for uid in &uids {
+            let key = self.namespace.cookies_key(uid);
+            pipe.cmd("HMGET").arg(&key).arg(&SUMMARY_FIELDS[..]);
+            pipe.ttl(key);
+        }
+        let replies: Vec<redis::Value> = pipe
+            .query_async(conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let mut rows = Vec::with_capacity(uids.len());
+        for reply in replies.chunks(2) {
+            let [fields, ttl] = reply else {
+                return Err(StorageError::CommandFailed("HMGET/TTL应答数量不匹配".to_string()));
+            };
+            let fields: Vec<Option<String>> = redis::from_redis_value(fields)
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+            let ttl: i64 = redis::from_redis_value(ttl)
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+            rows.push((fields, ttl));
+        }
+
+        Ok(uids
+            .into_iter()
+            .zip(rows)
+            .map(|(uid, (row, ttl))| {
+                let fields: HashMap<String, String> = SUMMARY_FIELDS
+                    .iter()
+                    .zip(row)
+                    .filter_map(|(name, value)| value.map(|value| (name.to_string(), value)))
+                    .collect();
+
+                // 每个已保存的账户都有fetched_at,缺失说明key已过期或被删除
+                let fetched_at = fields
+                    .get("fetched_at")
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))?;
+
+                Some(AccountSummary {
+                    status: AccountStatus::from_fields(&fields),
+                    last_validation: ValidationRecord::from_fields(&fields),
+                    validation: fields
+                        .get("validation")
+                        .and_then(|s| SnapshotValidation::parse(s)),
+                    expires_at: (ttl > 0)
+                        .then(|| chrono::DateTime::from_timestamp(now + ttl, 0))
+                        .flatten(),
+                    screen_name: fields.get("screen_name").cloned(),
+                    fetched_at,
+                    uid,
+                })
+            })
+            .collect())
+    }
+
+    /// 为账户添加标签
+    ///
+    /// 标签独立存放在Set中,不在Cookies Hash里,重新登录覆盖Cookies不会影响标签。
+    ///
+    /// # 返回值
+    /// 账户添加后的全部标签 (已排序)
+    ///
+    /// # 错误
+    /// - `StorageError::NotFound`: 账户不存在
+    pub async fn add_tags(&self, uid: &str, tags: &[String]) -> Result<Vec<String>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let exists: bool = conn
+            .exists(self.namespace.cookies_key(uid))
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        if !exists {
+            return Err(StorageError::NotFound(uid.to_string()));
+        }
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .sadd(self.namespace.account_tags_key(uid), tags)
+            .ignore()
+            .sadd(self.namespace.tag_index_key(), tags)
+            .ignore();
+        for tag in tags {
+            pipe.sadd(self.namespace.tag_key(tag), uid).ignore();
+        }
+        pipe.query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        tracing::info!(用户ID = %uid, 标签 = ?tags, "已添加账户标签");
+        self.get_tags(uid).await
+    }
+
+    /// 移除账户标签
+    ///
+    /// 标签不再有任何账户时,从标签索引中删除。
+    ///
+    /// # 返回值
+    /// 账户剩余的全部标签 (已排序)
+    pub async fn remove_tags(&self, uid: &str, tags: &[String]) -> Result<Vec<String>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .srem(self.namespace.account_tags_key(uid), tags)
+            .ignore();
+        for tag in tags {
+            pipe.srem(self.namespace.tag_key(tag), uid).ignore();
+        }
+        pipe.query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        for tag in tags {
+            let remaining: usize = conn
+                .scard(self.namespace.tag_key(tag))
+                .await
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+            if remaining == 0 {
+                conn.srem::<_, _, ()>(self.namespace.tag_index_key(), tag)
+                    .await
+                    .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+            }
+        }
+
+        tracing::info!(用户ID = %uid, 标签 = ?tags, "已移除账户标签");
+        self.get_tags(uid).await
+    }
+
+    /// 获取账户的全部标签 (已排序)
+    pub async fn get_tags(&self, uid: &str) -> Result<Vec<String>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let mut tags: Vec<String> = conn
+            .smembers(self.namespace.account_tags_key(uid))
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        tags.sort();
+        Ok(tags)
+    }
+
+    /// 按标签筛选账户
+    ///
+    /// - `TagMatch::All`: SINTER,同时拥有所有标签
+    /// - `TagMatch::Any`: SUNION,拥有任一标签
+    ///
+    /// Cookies已过期的账户会被过滤掉,并顺带从标签集合中清理。
+    ///
+    /// # 返回值
+    /// 匹配的UID (已排序)
+    pub async fn accounts_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+    ) -> Result<Vec<String>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let tag_keys: Vec<String> = tags.iter().map(|tag| self.namespace.tag_key(tag)).collect();
+        let uids: Vec<String> = match mode {
+            TagMatch::All => conn.sinter(&tag_keys).await,
+            TagMatch::Any => conn.sunion(&tag_keys).await,
+        }
+        .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let (mut live, stale) = self.partition_live_uids(&mut conn, uids).await?;
+        if !stale.is_empty() {
+            self.prune_stale_members(&mut conn, &stale).await?;
+        }
+
+        live.sort();
+        tracing::debug!(标签 = ?tags, 模式 = ?mode, 账户数量 = %live.len(), "按标签筛选账户");
+        Ok(live)
+    }
+
+    /// 统计每个标签下的账户数量
+    ///
+    /// 只计入Cookies仍然存在的账户,结果按数量降序、标签名升序排列。
+    pub async fn tag_counts(&self) -> Result<Vec<TagCount>, StorageError> {
+        let mut conn = self.connection().await?;
+
+        let tags: Vec<String> = conn
+            .smembers(self.namespace.tag_index_key())
+            .await
+            .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+        let mut counts = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let uids: Vec<String> = conn
+                .smembers(self.namespace.tag_key(&tag))
+                .await
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+            let (live, stale) = self.partition_live_uids(&mut conn, uids).await?;
+            if !stale.is_empty() {
+                self.prune_stale_members(&mut conn, &stale).await?;
+            }
+            counts.push(TagCount {
+                tag,
+                count: live.len(),
+            });
+        }
+
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(counts)
+    }
+
+    /// 按Cookies key是否存在,将UID分为存活与失效两组
+    ///
+    /// 回收站中的账户两组都不属于: 不计入筛选结果,但标签保留以便恢复。
+    async fn partition_live_uids(
+        &self,
+        conn: &mut RedisConnection,
+        uids: Vec<String>,
+    ) -> Result<(Vec<String>, Vec<String>), StorageError> {
+        let mut live = Vec::new();
+        let mut stale = Vec::new();
+
+        let summaries = self.account_summaries(conn, uids.clone()).await?;
+        for (uid, summary) in uids.into_iter().zip(summaries) {
+            match summary {
+                None => stale.push(uid),
+                Some(account) if account.status.state.is_trashed() => {}
+                Some(_) => live.push(uid),
+            }
+        }
+
+        Ok((live, stale))
+    }
+
+    /// 清理Cookies已过期账户的标签
+    async fn prune_stale_members(
+        &self,
+        conn: &mut RedisConnection,
+        uids: &[String],
+    ) -> Result<(), StorageError> {
+        for uid in uids {
+            let account_tags_key = self.namespace.account_tags_key(uid);
+            let tags: Vec<String> = conn
+                .smembers(&account_tags_key)
+                .await
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+            let mut pipe = redis::pipe();
+            for tag in &tags {
+                pipe.srem(self.namespace.tag_key(tag), uid).ignore();
+            }
+            pipe.del(&account_tags_key).ignore();
+            pipe.query_async::<()>(conn)
+                .await
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+        }
+
+        tracing::debug!(失效账户数量 = %uids.len(), "已清理过期账户的标签");
+        Ok(())
+    }
+
+    /// 将一个命名空间下的所有key迁移到另一个命名空间
+    ///
+    /// 零停机迁移流程:
+    /// 1. `delete_source = false` 运行一次: 复制全部key,旧前缀继续可用
+    /// 2. 保存新的 `REDIS_KEY_PREFIX` 并重启应用,切换到新前缀
+    /// 3. `delete_source = true` 再运行一次: 补齐切换前新写入的key并清理旧key
+    ///
+    /// 每个key由Lua脚本原子复制 (DUMP/RESTORE,保留TTL),目标已存在时不覆盖,
+    /// 因此可以重复执行。两边都有同一账户的Cookies时保留较新的一份,
+    /// 目标更新时源key不会被删除。
+    ///
+    /// 前缀可以包含 `:`,只迁移本命名空间已知类型的key,
+    /// 嵌套的命名空间 (如 `weibo` 下的 `weibo:staging:*`) 不受影响。
+    ///
+    /// # 错误
+    /// - `StorageError::CommandFailed`: 前缀互相嵌套、集群模式,或Redis命令失败
+    pub async fn migrate_namespace(
+        &self,
+        from: &KeyNamespace,
+        to: &KeyNamespace,
+        delete_source: bool,
+    ) -> Result<KeyMigrationReport, StorageError> {
+        if from.overlaps(to) {
+            return Err(StorageError::CommandFailed(format!(
+                "前缀 {} 与 {} 互相嵌套,无法迁移",
+                from.prefix(),
+                to.prefix()
+            )));
+        }
+
+        // 不同前缀的hash-tag不同,源key与目标key落在不同槽位,复制脚本无法执行
+        if self.pool.is_cluster() {
+            return Err(StorageError::CommandFailed(
+                "集群模式下不同前缀位于不同槽位,不支持前缀迁移".to_string(),
+            ));
+        }
+
+        let mut conn = self.connection().await?;
+
+        let mut report = KeyMigrationReport {
+            from_prefix: from.prefix().to_string(),
+            to_prefix: to.prefix().to_string(),
+            deleted_source: delete_source,
+            ..Default::default()
+        };
+
+        let script = redis::Script::new(MIGRATE_KEY_SCRIPT);
+        let pattern = from.all_keys_pattern();
+        let mut cursor: u64 = 0;
+
+        loop {
+            let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(MIGRATE_SCAN_COUNT)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+            for key in keys {
+                let Some(target_key) = from.rebase_key(&key, to) else {
+                    continue;
+                };
+                report.scanned += 1;
+
+                let result: i64 = script
+                    .key(&key)
+                    .key(&target_key)
+                    .arg(if delete_source { "1" } else { "0" })
+                    .invoke_async(&mut conn)
+                    .await
+                    .map_err(|e| StorageError::CommandFailed(e.to_string()))?;
+
+                match result {
+                    1 => report.copied += 1,
+                    0 => report.skipped_existing += 1,
+                    2 => report.kept_source += 1,
+                    _ => report.vanished += 1,
+                }
+            }
+
+            cursor = next_cursor;
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        tracing::info!(
+            源前缀 = %report.from_prefix,
+            目标前缀 = %report.to_prefix,
+            扫描数量 = %report.scanned,
+            复制数量 = %report.copied,
+            跳过数量 = %report.skipped_existing,
+            保留源key数量 = %report.kept_source,
+            删除源key = %delete_source,
+            "Redis key前缀迁移完成"
+        );
+
+        Ok(report)
     }
 }
 
@@ -286,8 +1607,17 @@ mod tests {
             .with_screen_name("测试用户".to_string());
 
         // 保存
-        let is_overwrite = service.save_cookies(&cookies_data).await.unwrap();
-        assert!(!is_overwrite);
+        let outcome = service
+            .save_cookies(
+                &cookies_data,
+                SnapshotSource::Manual,
+                SnapshotValidation::Verified,
+                SaveMode::IfNewer,
+            )
+            .await
+            .unwrap();
+        assert!(!outcome.is_overwrite);
+        assert_eq!(outcome.winner, SaveWinner::Incoming);
 
         // 查询
         let retrieved = service.query_cookies("test_uid_123").await.unwrap();
@@ -299,6 +1629,338 @@ mod tests {
         service.delete_cookies("test_uid_123").await.unwrap();
     }
 
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_history_and_rollback() {
+        let service = RedisService::new("redis://localhost:6379")
+            .unwrap()
+            .with_history_limit(3);
+        let uid = "test_uid_history";
+
+        for value in ["v1", "v2", "v3", "v4"] {
+            let mut cookies = HashMap::new();
+            cookies.insert("SUB".to_string(), value.to_string());
+            cookies.insert("SUBP".to_string(), "subp".to_string());
+            let cookies_data = CookiesData::new(uid.to_string(), cookies);
+            service
+                .save_cookies(
+                    &cookies_data,
+                    SnapshotSource::QrLogin,
+                    SnapshotValidation::TrustedServer,
+                    SaveMode::IfNewer,
+                )
+                .await
+                .unwrap();
+        }
+
+        // 只保留最近3个版本,最新在前
+        let history = service.list_history(uid).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history[0].version > history[1].version);
+
+        // 回滚到最旧的保留版本
+        let oldest = history.last().unwrap().version;
+        let restored = service.rollback_to(uid, oldest).await.unwrap();
+        assert_eq!(restored.cookies.get("SUB"), Some(&"v2".to_string()));
+
+        let history = service.list_history(uid).await.unwrap();
+        assert_eq!(history[0].source, SnapshotSource::Rollback);
+
+        service.delete_cookies(uid).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_older_login_never_overwrites_newer() {
+        let service = RedisService::new("redis://localhost:6379").unwrap();
+        let uid = "test_uid_cas";
+
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "newer".to_string());
+        cookies.insert("SUBP".to_string(), "subp".to_string());
+        let newer = CookiesData::new(uid.to_string(), cookies.clone());
+
+        let mut older = CookiesData::new(uid.to_string(), cookies);
+        older.cookies.insert("SUB".to_string(), "older".to_string());
+        older.fetched_at = newer.fetched_at - chrono::Duration::seconds(60);
+
+        service
+            .save_cookies(&newer, SnapshotSource::QrLogin, SnapshotValidation::TrustedServer, SaveMode::IfNewer)
+            .await
+            .unwrap();
+        let outcome = service
+            .save_cookies(&older, SnapshotSource::QrLogin, SnapshotValidation::TrustedServer, SaveMode::IfNewer)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.winner, SaveWinner::Existing);
+        assert!(outcome.version.is_none());
+        let stored = service.query_cookies(uid).await.unwrap();
+        assert_eq!(stored.cookies.get("SUB"), Some(&"newer".to_string()));
+
+        // 覆盖模式无视时间先后
+        let outcome = service
+            .save_cookies(&older, SnapshotSource::Rollback, SnapshotValidation::Unverified, SaveMode::Overwrite)
+            .await
+            .unwrap();
+        assert_eq!(outcome.winner, SaveWinner::Incoming);
+
+        service.delete_cookies(uid).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_migrate_namespace() {
+        let from = KeyNamespace::new("test_migrate_from").unwrap();
+        let to = KeyNamespace::new("test_migrate_to").unwrap();
+        let old = RedisService::new("redis://localhost:6379")
+            .unwrap()
+            .with_namespace(from.clone());
+        let new = RedisService::new("redis://localhost:6379")
+            .unwrap()
+            .with_namespace(to.clone());
+
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "sub".to_string());
+        cookies.insert("SUBP".to_string(), "subp".to_string());
+        let cookies_data = CookiesData::new("uid_migrate".to_string(), cookies);
+        old.save_cookies(&cookies_data, SnapshotSource::Manual, SnapshotValidation::Verified, SaveMode::IfNewer)
+            .await
+            .unwrap();
+
+        // 第一轮: 仅复制
+        let report = old.migrate_namespace(&from, &to, false).await.unwrap();
+        assert!(report.copied >= 3);
+        assert!(old.query_cookies("uid_migrate").await.is_ok());
+        assert!(new.query_cookies("uid_migrate").await.is_ok());
+
+        // 第二轮: 目标已存在,只清理源key
+        let report = old.migrate_namespace(&from, &to, true).await.unwrap();
+        assert_eq!(report.copied, 0);
+        assert!(old.list_all_uids().await.unwrap().is_empty());
+        assert_eq!(new.list_history("uid_migrate").await.unwrap().len(), 1);
+
+        new.delete_cookies("uid_migrate").await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_migrate_namespace_keeps_newer_cookies() {
+        let from = KeyNamespace::new("test_migrate_newer").unwrap();
+        let to = KeyNamespace::new("test_migrate_newer_to").unwrap();
+        let nested = KeyNamespace::new("test_migrate_newer:tenant").unwrap();
+        let service = |ns: &KeyNamespace| {
+            RedisService::new("redis://localhost:6379")
+                .unwrap()
+                .with_namespace(ns.clone())
+        };
+        let (old, new, tenant) = (service(&from), service(&to), service(&nested));
+
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "sub".to_string());
+        cookies.insert("SUBP".to_string(), "subp".to_string());
+        let older = CookiesData::new("uid_newer".to_string(), cookies.clone());
+        let mut newer = CookiesData::new("uid_newer".to_string(), cookies.clone());
+        newer.fetched_at = older.fetched_at + chrono::Duration::seconds(60);
+        async fn save(service: &RedisService, data: &CookiesData) {
+            service
+                .save_cookies(data, SnapshotSource::Manual, SnapshotValidation::Verified, SaveMode::Overwrite)
+                .await
+                .unwrap();
+        }
+
+        // 源更新: 覆盖目标
+        save(&old, &newer).await;
+        save(&new, &older).await;
+        save(&tenant, &older).await;
+        let report = old.migrate_namespace(&from, &to, true).await.unwrap();
+        assert_eq!(new.query_cookies("uid_newer").await.unwrap().fetched_at, newer.fetched_at);
+        assert!(old.query_cookies("uid_newer").await.is_err());
+        assert_eq!(report.kept_source, 0);
+        // 嵌套的命名空间不受影响
+        assert!(tenant.query_cookies("uid_newer").await.is_ok());
+
+        // 目标更新: 保留源key
+        save(&old, &older).await;
+        let report = old.migrate_namespace(&from, &to, true).await.unwrap();
+        assert_eq!(report.kept_source, 1);
+        assert_eq!(new.query_cookies("uid_newer").await.unwrap().fetched_at, newer.fetched_at);
+        assert!(old.query_cookies("uid_newer").await.is_ok());
+
+        for service in [old, new, tenant] {
+            service.delete_cookies("uid_newer").await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_tags_survive_relogin() {
+        let service = RedisService::new("redis://localhost:6379").unwrap();
+
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "sub".to_string());
+        cookies.insert("SUBP".to_string(), "subp".to_string());
+        for uid in ["test_tag_a", "test_tag_b"] {
+            let cookies_data = CookiesData::new(uid.to_string(), cookies.clone());
+            service
+                .save_cookies(&cookies_data, SnapshotSource::Manual, SnapshotValidation::Verified, SaveMode::IfNewer)
+                .await
+                .unwrap();
+        }
+
+        service
+            .add_tags("test_tag_a", &["t_proj".to_string(), "t_shared".to_string()])
+            .await
+            .unwrap();
+        service.add_tags("test_tag_b", &["t_shared".to_string()]).await.unwrap();
+
+        // 重新登录覆盖Cookies
+        let relogin = CookiesData::new("test_tag_a".to_string(), cookies);
+        service
+            .save_cookies(&relogin, SnapshotSource::QrLogin, SnapshotValidation::TrustedServer, SaveMode::IfNewer)
+            .await
+            .unwrap();
+        assert_eq!(service.get_tags("test_tag_a").await.unwrap(), vec!["t_proj", "t_shared"]);
+
+        let both = ["t_proj".to_string(), "t_shared".to_string()];
+        assert_eq!(service.accounts_by_tags(&both, TagMatch::All).await.unwrap(), vec!["test_tag_a"]);
+        assert_eq!(service.accounts_by_tags(&both, TagMatch::Any).await.unwrap().len(), 2);
+
+        service.delete_cookies("test_tag_a").await.unwrap();
+        service.delete_cookies("test_tag_b").await.unwrap();
+        assert!(service.accounts_by_tags(&both, TagMatch::Any).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_trash_restore_and_state_flags() {
+        let service = RedisService::new("redis://localhost:6379").unwrap();
+        let uid = "test_trash_uid";
+
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "sub".to_string());
+        cookies.insert("SUBP".to_string(), "subp".to_string());
+        let data = CookiesData::new(uid.to_string(), cookies);
+        service
+            .save_cookies(&data, SnapshotSource::Manual, SnapshotValidation::Verified, SaveMode::Overwrite)
+            .await
+            .unwrap();
+
+        // 标记失效: 不再自动过期,也不出现在租约候选中
+        service.set_account_state(uid, AccountState::Invalid, Some("验证失败")).await.unwrap();
+        let status = service.account_status(uid).await.unwrap();
+        assert_eq!(status.state, AccountState::Invalid);
+        assert_eq!(status.state_reason.as_deref(), Some("验证失败"));
+        let mut conn = service.connection().await.unwrap();
+        let ttl: i64 = conn.ttl(service.namespace().cookies_key(uid)).await.unwrap();
+        assert_eq!(ttl, -1);
+        assert!(service
+            .filter_by_state(vec![uid.to_string()], &[AccountState::Active])
+            .await
+            .unwrap()
+            .is_empty());
+
+        // 删除进入回收站: 查询不可见,列表可按状态筛选
+        let purge_at = service.trash_account(uid).await.unwrap();
+        assert!(purge_at.is_some());
+        assert!(matches!(service.query_cookies(uid).await, Err(StorageError::NotFound(_))));
+        assert!(!service.list_all_uids().await.unwrap().contains(&uid.to_string()));
+        let trashed = service.list_accounts(&[AccountState::DeletedPendingPurge]).await.unwrap();
+        assert!(trashed.iter().any(|account| account.uid == uid));
+        assert!(matches!(
+            service.set_account_state(uid, AccountState::Active, None).await,
+            Err(StorageError::InvalidState(_))
+        ));
+
+        // 恢复到删除前的状态
+        assert_eq!(service.restore_account(uid).await.unwrap(), AccountState::Invalid);
+        assert!(matches!(service.restore_account(uid).await, Err(StorageError::InvalidState(_))));
+
+        // 重新保存回到active
+        service
+            .save_cookies(&data, SnapshotSource::Manual, SnapshotValidation::Verified, SaveMode::Overwrite)
+            .await
+            .unwrap();
+        let status = service.account_status(uid).await.unwrap();
+        assert_eq!(status.state, AccountState::Active);
+        assert_eq!(status.state_reason, None);
+
+        // 验证结果: 保存已验证的Cookies记为valid,之后的失败覆盖它
+        let last_validation = |accounts: Vec<AccountSummary>| {
+            accounts
+                .into_iter()
+                .find(|account| account.uid == uid)
+                .and_then(|account| account.last_validation)
+                .map(|record| record.outcome)
+        };
+        let active = service.list_accounts(&[AccountState::Active]).await.unwrap();
+        assert_eq!(last_validation(active), Some(ValidationOutcome::Valid));
+        service
+            .record_validation(uid, ValidationOutcome::Banned, chrono::Utc::now())
+            .await
+            .unwrap();
+        // 封禁的账户被标记为失效,不再随TTL过期
+        let invalid = service.list_accounts(&[AccountState::Invalid]).await.unwrap();
+        assert_eq!(last_validation(invalid), Some(ValidationOutcome::Banned));
+        assert!(matches!(
+            service
+                .record_validation("nonexistent_uid", ValidationOutcome::Valid, chrono::Utc::now())
+                .await,
+            Err(StorageError::NotFound(_))
+        ));
+
+        service.delete_cookies(uid).await.unwrap();
+        assert!(service.restore_account(uid).await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_finish_pending_validation() {
+        let service = RedisService::new("redis://localhost:6379").unwrap();
+        let uid = "test_pending_uid";
+        let validation = |accounts: Vec<AccountSummary>| {
+            accounts
+                .into_iter()
+                .find(|account| account.uid == uid)
+                .map(|account| (account.validation, account.last_validation.map(|r| r.outcome)))
+        };
+
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "sub".to_string());
+        let data = CookiesData::new(uid.to_string(), cookies.clone());
+        service
+            .save_cookies(&data, SnapshotSource::QrLogin, SnapshotValidation::Pending, SaveMode::Overwrite)
+            .await
+            .unwrap();
+        let active = service.list_accounts(&[AccountState::Active]).await.unwrap();
+        assert_eq!(validation(active), Some((Some(SnapshotValidation::Pending), None)));
+
+        // 保存的Cookies已被替换: 旧的验证结论不写入
+        let stale = data.fetched_at - chrono::Duration::seconds(10);
+        assert!(!service
+            .finish_pending_validation(uid, stale, SnapshotValidation::Verified, Some(ValidationOutcome::Valid), chrono::Utc::now())
+            .await
+            .unwrap());
+
+        assert!(service
+            .finish_pending_validation(uid, data.fetched_at, SnapshotValidation::Mismatch, Some(ValidationOutcome::Expired), chrono::Utc::now())
+            .await
+            .unwrap());
+        let active = service.list_accounts(&[AccountState::Active]).await.unwrap();
+        assert_eq!(
+            validation(active),
+            Some((Some(SnapshotValidation::Mismatch), Some(ValidationOutcome::Expired)))
+        );
+
+        // 只写入一次
+        assert!(!service
+            .finish_pending_validation(uid, data.fetched_at, SnapshotValidation::Verified, Some(ValidationOutcome::Valid), chrono::Utc::now())
+            .await
+            .unwrap());
+
+        service.delete_cookies(uid).await.unwrap();
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_delete_nonexistent() {
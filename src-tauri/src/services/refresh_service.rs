@@ -0,0 +1,613 @@
+//! Cookies保活刷新
+//!
+//! 微博会在访问时轮换部分cookies,长期不用的会话也会提前失效。
+//! 刷新操作带着已保存的cookies访问一个需要登录的轻量接口 (`/ajax/config`),
+//! 收集响应中的 `Set-Cookie`,与原cookies合并后作为新版本保存 (来源 `refresh`),
+//! 账户因此保持活跃,不需要重新扫码。
+//!
+//! 后台任务按间隔逐个刷新所有账户;前端也可以对单个账户立即刷新。
+
+use chrono::Utc;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::{
+    AccountState, AuditEvent, AuditOperation, CookiesData, RefreshError, SnapshotSource,
+    SnapshotValidation, ValidationError, ValidationOutcome,
+};
+use crate::services::rate_limiter::{RateLimiter, RateOperation};
+use crate::services::weibo_http::{WeiboHttpClient, DEFAULT_BASE_URL, DEFAULT_TIMEOUT};
+use crate::services::{AuditLog, RedisService, SaveMode, SaveWinner};
+
+/// 默认刷新间隔: 24小时
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// 后台刷新时相邻两个账户之间的间隔,避免集中请求
+const ACCOUNT_SPACING: Duration = Duration::from_secs(3);
+
+/// 一次刷新的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshReport {
+    pub uid: String,
+
+    /// 值发生变化或新增的cookie名称 (已排序)
+    pub rotated: Vec<String>,
+
+    /// 被服务端删除的cookie名称 (已排序)
+    pub removed: Vec<String>,
+
+    /// 保存的新版本号,cookies未变化或已被更新的登录取代时为None
+    pub version: Option<u64>,
+
+    /// 刷新期间账户被更新 (新的登录、回滚或状态变更),刷新结果被丢弃
+    pub superseded: bool,
+}
+
+/// Cookies保活刷新服务
+///
+/// 配置审计日志后,每次刷新 (手动或后台) 都以 `refresh` 为调用方记录一条保存事件。
+pub struct RefreshService {
+    redis: Arc<RedisService>,
+    base_url: Url,
+    timeout: Duration,
+    interval: Option<Duration>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl RefreshService {
+    /// 创建访问 weibo.com 的刷新服务,使用默认刷新间隔
+    pub fn new(redis: Arc<RedisService>) -> Self {
+        Self {
+            redis,
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("默认地址合法"),
+            timeout: DEFAULT_TIMEOUT,
+            interval: Some(DEFAULT_REFRESH_INTERVAL),
+            rate_limiter: None,
+            audit: None,
+        }
+    }
+
+    /// 设置站点地址 (构建器模式),用于测试时指向本地服务器
+    ///
+    /// # 错误
+    /// 地址无法解析时返回 `ValidationError::InvalidFormat`
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self, ValidationError> {
+        self.base_url = Url::parse(base_url)
+            .map_err(|e| ValidationError::InvalidFormat(format!("无效的刷新地址: {}", e)))?;
+        Ok(self)
+    }
+
+    /// 设置后台刷新间隔 (构建器模式),None表示只手动刷新
+    pub fn with_interval(mut self, interval: Option<Duration>) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 每次访问微博前经过全局限速器 (构建器模式)
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// 启用审计日志
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 从环境变量 `REFRESH_INTERVAL_SECS` 读取后台刷新间隔
+    ///
+    /// 未设置时使用默认间隔 (24小时),0表示不启用后台刷新。
+    ///
+    /// # 错误
+    /// 值不是非负整数时返回说明
+    pub fn interval_from_env() -> Result<Option<Duration>, String> {
+        Self::interval_from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// 从任意键值来源读取后台刷新间隔 (环境变量或测试中的固定值)
+    ///
+    /// # 错误
+    /// 同 [`interval_from_env`](Self::interval_from_env)
+    pub fn interval_from_lookup(
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Option<Duration>, String> {
+        match lookup("REFRESH_INTERVAL_SECS").unwrap_or_default().trim() {
+            "" => Ok(Some(DEFAULT_REFRESH_INTERVAL)),
+            value => match value.parse::<u64>() {
+                Ok(0) => Ok(None),
+                Ok(secs) => Ok(Some(Duration::from_secs(secs))),
+                Err(_) => Err(format!(
+                    "无效的 REFRESH_INTERVAL_SECS: {} (应为非负整数秒)",
+                    value
+                )),
+            },
+        }
+    }
+
+    /// 刷新账户的cookies
+    ///
+    /// 有cookie被轮换或删除时保存为新版本: 保留原有的获取时间,验证方式记为未验证
+    /// (合并后的cookies没有经过验证)。写入是针对读取时 `fetched_at` 的比较并设置
+    /// (`IfUnchanged`): 刷新期间出现了新的登录、回滚或状态变更时放弃写入,报告 `superseded`。
+    /// 没有变化时只记录一次有效验证。会话已失效时记录验证结果并返回错误,不修改cookies。
+    ///
+    /// # 错误
+    /// - `RefreshError::Storage`: 账户不存在或Redis操作失败
+    /// - `RefreshError::Validation`: 会话已失效、网络错误或超时
+    pub async fn refresh(&self, uid: &str) -> Result<RefreshReport, RefreshError> {
+        let result = self.refresh_inner(uid).await;
+
+        let mut event = AuditEvent::new(AuditOperation::Save, "refresh")
+            .with_uid(uid)
+            .with_result(&result);
+        if let Ok(report) = &result {
+            let changes = format!("轮换 {:?},删除 {:?}", report.rotated, report.removed);
+            event = event.with_detail(match report.version {
+                Some(version) => format!("{},历史版本 {}", changes, version),
+                None if report.superseded => format!("{},刷新期间账户已被更新,未保存", changes),
+                None => "cookies未轮换,未保存".to_string(),
+            });
+        }
+        if let Some(audit) = &self.audit {
+            audit.record(event).await;
+        }
+
+        result
+    }
+
+    async fn refresh_inner(&self, uid: &str) -> Result<RefreshReport, RefreshError> {
+        let stored = self.redis.query_cookies(uid).await?;
+
+        let updates = match self.fetch_updates(&stored).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                let outcome = ValidationOutcome::from_error(&e);
+                if outcome.is_conclusive() {
+                    if let Err(record_err) =
+                        self.redis.record_validation(uid, outcome, Utc::now()).await
+                    {
+                        tracing::warn!(用户ID = %uid, 错误 = %record_err, "验证结果记录失败");
+                    }
+                }
+                tracing::warn!(用户ID = %uid, 错误 = %e, "Cookies刷新失败");
+                return Err(e.into());
+            }
+        };
+
+        let (merged, rotated, removed) = merge_cookies(&stored.cookies, updates);
+        let mut report = RefreshReport {
+            uid: uid.to_string(),
+            rotated,
+            removed,
+            version: None,
+            superseded: false,
+        };
+
+        if report.rotated.is_empty() && report.removed.is_empty() {
+            self.redis
+                .record_validation(uid, ValidationOutcome::Valid, Utc::now())
+                .await?;
+            tracing::info!(用户ID = %uid, "会话有效,cookies未轮换");
+            return Ok(report);
+        }
+
+        // 保留读取时的fetched_at作为比较并设置的依据
+        let mut refreshed = stored;
+        refreshed.cookies = merged;
+        refreshed.validate().map_err(RefreshError::Validation)?;
+
+        let outcome = self
+            .redis
+            .save_cookies(
+                &refreshed,
+                SnapshotSource::Refresh,
+                SnapshotValidation::Unverified,
+                SaveMode::IfUnchanged,
+            )
+            .await?;
+        report.version = outcome.version;
+        report.superseded = outcome.winner == SaveWinner::Existing;
+
+        if report.superseded {
+            tracing::info!(
+                用户ID = %uid,
+                轮换 = ?report.rotated,
+                删除 = ?report.removed,
+                "刷新期间账户已被更新 (superseded),放弃刷新结果"
+            );
+            return Ok(report);
+        }
+
+        tracing::info!(
+            用户ID = %uid,
+            轮换 = ?report.rotated,
+            删除 = ?report.removed,
+            历史版本 = ?report.version,
+            "Cookies已刷新"
+        );
+        Ok(report)
+    }
+
+    /// 后台刷新循环 (不会返回,随应用退出)
+    ///
+    /// 每隔 `interval` 逐个刷新所有正常状态的账户 (失效、归档和回收站中的账户不刷新),
+    /// 单个账户失败不影响其他账户。未配置间隔时直接返回。
+    pub async fn run(self: Arc<Self>) {
+        let Some(interval) = self.interval else {
+            tracing::info!("后台Cookies刷新未启用");
+            return;
+        };
+        tracing::info!(间隔秒 = %interval.as_secs(), "后台Cookies刷新已启动");
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let uids: Vec<String> = match self.redis.list_accounts(&[AccountState::Active]).await {
+                Ok(accounts) => accounts.into_iter().map(|account| account.uid).collect(),
+                Err(e) => {
+                    tracing::warn!(错误 = %e, "无法列出账户,等待下一轮刷新");
+                    continue;
+                }
+            };
+
+            let mut refreshed = 0;
+            for uid in &uids {
+                if self.refresh(uid).await.is_ok() {
+                    refreshed += 1;
+                }
+                tokio::time::sleep(ACCOUNT_SPACING).await;
+            }
+            tracing::info!(账户数量 = %uids.len(), 成功 = %refreshed, "后台Cookies刷新完成");
+        }
+    }
+
+    /// 带着cookies访问登录状态接口,返回服务端下发的cookie变化
+    ///
+    /// 值为None表示该cookie被服务端删除。
+    async fn fetch_updates(
+        &self,
+        stored: &CookiesData,
+    ) -> Result<Vec<(String, Option<String>)>, ValidationError> {
+        let client = WeiboHttpClient::with_site(stored, self.base_url.as_str(), self.timeout)?;
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(RateOperation::Refresh).await?;
+        }
+
+        let (status, set_cookies) = client.check_login().await?;
+        if status.uid != stored.uid {
+            return Err(ValidationError::UidExtractionFailed(format!(
+                "Cookies属于账户 {},不是 {}",
+                status.uid, stored.uid
+            )));
+        }
+
+        Ok(set_cookies
+            .iter()
+            .filter_map(|header| parse_set_cookie(header))
+            .collect())
+    }
+}
+
+/// 解析一条 `Set-Cookie`,返回 (名称, 值),值为None表示删除
+///
+/// 只关心名称和值: 域名、路径等属性由保存的cookies统一决定。
+/// 空值、`deleted`、`Max-Age<=0` 或过期时间已过都视为删除。
+///
+/// # 示例
+/// ```
+/// use weibo_login::services::refresh_service::parse_set_cookie;
+///
+/// assert_eq!(
+///     parse_set_cookie("SUB=new_value; Path=/; Domain=.weibo.com; HttpOnly"),
+///     Some(("SUB".to_string(), Some("new_value".to_string())))
+/// );
+/// assert_eq!(
+///     parse_set_cookie("ALF=deleted; Max-Age=0; Path=/"),
+///     Some(("ALF".to_string(), None))
+/// );
+/// assert_eq!(parse_set_cookie("malformed"), None);
+/// ```
+pub fn parse_set_cookie(header: &str) -> Option<(String, Option<String>)> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let (name, value) = (name.trim(), value.trim());
+    if name.is_empty() {
+        return None;
+    }
+
+    let expired = parts.any(|attribute| {
+        let (key, attr_value) = attribute.split_once('=').unwrap_or((attribute, ""));
+        match key.trim().to_ascii_lowercase().as_str() {
+            "max-age" => attr_value.trim().parse::<i64>().is_ok_and(|secs| secs <= 0),
+            "expires" => chrono::DateTime::parse_from_rfc2822(attr_value.trim())
+                .is_ok_and(|expires| expires < Utc::now()),
+            _ => false,
+        }
+    });
+
+    let removed = expired || value.is_empty() || value == "deleted";
+    Some((name.to_string(), (!removed).then(|| value.to_string())))
+}
+
+/// 合并服务端下发的变化,返回 (合并后的cookies, 轮换的名称, 删除的名称)
+fn merge_cookies(
+    stored: &HashMap<String, String>,
+    updates: Vec<(String, Option<String>)>,
+) -> (HashMap<String, String>, Vec<String>, Vec<String>) {
+    let mut merged = stored.clone();
+    let mut rotated = BTreeSet::new();
+    let mut removed = BTreeSet::new();
+
+    for (name, value) in updates {
+        match value {
+            Some(value) => {
+                if merged.get(&name) != Some(&value) {
+                    removed.remove(&name);
+                    rotated.insert(name.clone());
+                    merged.insert(name, value);
+                }
+            }
+            None => {
+                if merged.remove(&name).is_some() {
+                    rotated.remove(&name);
+                    removed.insert(name);
+                }
+            }
+        }
+    }
+
+    (
+        merged,
+        rotated.into_iter().collect(),
+        removed.into_iter().collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AuditQuery, StorageError};
+    use crate::services::weibo_http::{CONFIG_PATH, USER_AGENT};
+    use crate::utils::mock_http::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn cookies() -> HashMap<String, String> {
+        HashMap::from([
+            ("SUB".to_string(), "sub_old".to_string()),
+            ("SUBP".to_string(), "subp_value".to_string()),
+            ("ALF".to_string(), "alf_value".to_string()),
+        ])
+    }
+
+    fn account(uid: &str) -> CookiesData {
+        CookiesData::new(uid.to_string(), cookies())
+    }
+
+    fn service(server: &MockServer) -> RefreshService {
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        RefreshService::new(redis)
+            .with_base_url(&server.url())
+            .unwrap()
+    }
+
+    fn logged_in(uid: &str) -> MockResponse {
+        MockResponse::json(200, json!({"ok": 1, "data": {"login": true, "uid": uid}}))
+    }
+
+    #[test]
+    fn test_interval_from_lookup() {
+        let interval = |value: Option<&str>| {
+            RefreshService::interval_from_lookup(|key| {
+                assert_eq!(key, "REFRESH_INTERVAL_SECS");
+                value.map(str::to_string)
+            })
+        };
+
+        assert_eq!(interval(None), Ok(Some(DEFAULT_REFRESH_INTERVAL)));
+        assert_eq!(interval(Some(" ")), Ok(Some(DEFAULT_REFRESH_INTERVAL)));
+        assert_eq!(interval(Some("0")), Ok(None));
+        assert_eq!(interval(Some("3600")), Ok(Some(Duration::from_secs(3600))));
+        assert!(interval(Some("-1")).is_err());
+    }
+
+    #[test]
+    fn test_merge_cookies() {
+        let updates = vec![
+            ("SUB".to_string(), Some("sub_new".to_string())),
+            ("SUBP".to_string(), Some("subp_value".to_string())),
+            ("XSRF-TOKEN".to_string(), Some("token".to_string())),
+            ("ALF".to_string(), None),
+            ("UNKNOWN".to_string(), None),
+        ];
+        let (merged, rotated, removed) = merge_cookies(&cookies(), updates);
+
+        assert_eq!(merged["SUB"], "sub_new");
+        assert_eq!(merged["XSRF-TOKEN"], "token");
+        assert!(!merged.contains_key("ALF"));
+        assert_eq!(rotated, vec!["SUB", "XSRF-TOKEN"]);
+        assert_eq!(removed, vec!["ALF"]);
+    }
+
+    #[test]
+    fn test_parse_set_cookie_expiry() {
+        assert_eq!(
+            parse_set_cookie("SRT=x; expires=Thu, 01 Jan 1970 00:00:00 GMT; path=/"),
+            Some(("SRT".to_string(), None))
+        );
+        assert_eq!(
+            parse_set_cookie("SRT=x; Expires=Fri, 01 Jan 2100 00:00:00 GMT"),
+            Some(("SRT".to_string(), Some("x".to_string())))
+        );
+        assert_eq!(
+            parse_set_cookie("SRT=; Path=/"),
+            Some(("SRT".to_string(), None))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_updates_collects_set_cookie() {
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            logged_in("123")
+                .with_header("Set-Cookie", "SUB=sub_new; Path=/; Domain=.weibo.com")
+                .with_header("Set-Cookie", "ALF=deleted; Max-Age=0"),
+        )])
+        .await;
+
+        let updates = service(&server)
+            .fetch_updates(&account("123"))
+            .await
+            .unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                ("SUB".to_string(), Some("sub_new".to_string())),
+                ("ALF".to_string(), None),
+            ]
+        );
+
+        // 带着全部已保存的cookies与一致的User-Agent
+        let request = &server.requests()[0];
+        let mut sent: Vec<&str> = request.headers["cookie"].split("; ").collect();
+        sent.sort_unstable();
+        assert_eq!(
+            sent,
+            vec!["ALF=alf_value", "SUB=sub_old", "SUBP=subp_value"]
+        );
+        assert_eq!(request.headers["user-agent"], USER_AGENT);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_updates_rejects_dead_session() {
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            MockResponse::text(302, "")
+                .with_header("Location", "https://passport.weibo.com/sso/signin"),
+        )])
+        .await;
+        let result = service(&server).fetch_updates(&account("123")).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::Rejected {
+                outcome: ValidationOutcome::Expired,
+                ..
+            })
+        ));
+
+        let server = MockServer::start(vec![(CONFIG_PATH, logged_in("456"))]).await;
+        let result = service(&server).fetch_updates(&account("123")).await;
+        assert!(matches!(
+            result,
+            Err(ValidationError::UidExtractionFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_refresh_saves_rotated_cookies() {
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            logged_in("test_refresh_uid").with_header("Set-Cookie", "SUB=sub_new; Path=/"),
+        )])
+        .await;
+        let service = service(&server);
+        let uid = "test_refresh_uid";
+        let mut original =
+            CookiesData::new(uid.to_string(), cookies()).with_screen_name("刷新".into());
+        original.fetched_at -= chrono::Duration::hours(1);
+        service
+            .redis
+            .save_cookies(
+                &original,
+                SnapshotSource::Manual,
+                SnapshotValidation::Verified,
+                SaveMode::Overwrite,
+            )
+            .await
+            .unwrap();
+
+        let report = service.refresh(uid).await.unwrap();
+        assert_eq!(report.rotated, vec!["SUB"]);
+        assert!(report.version.is_some());
+
+        let saved = service.redis.query_cookies(uid).await.unwrap();
+        assert_eq!(saved.cookies["SUB"], "sub_new");
+        assert_eq!(saved.screen_name.as_deref(), Some("刷新"));
+        assert_eq!(
+            saved.fetched_at.timestamp(),
+            original.fetched_at.timestamp(),
+            "刷新保留原有的获取时间"
+        );
+        let history = service.redis.list_history(uid).await.unwrap();
+        assert_eq!(history[0].source, SnapshotSource::Refresh);
+        assert_eq!(history[0].validation, SnapshotValidation::Unverified);
+
+        service.redis.delete_cookies(uid).await.unwrap();
+        assert!(matches!(
+            service.refresh(uid).await,
+            Err(RefreshError::Storage(StorageError::NotFound(_)))
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_refresh_keeps_state_of_inactive_account() {
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            logged_in("test_refresh_archived_uid").with_header("Set-Cookie", "SUB=sub_new; Path=/"),
+        )])
+        .await;
+        let service = service(&server);
+        let audit = Arc::new(AuditLog::new(service.redis.clone()));
+        let service = service.with_audit(audit.clone());
+        let uid = "test_refresh_archived_uid";
+        service
+            .redis
+            .save_cookies(
+                &CookiesData::new(uid.to_string(), cookies()),
+                SnapshotSource::Manual,
+                SnapshotValidation::Verified,
+                SaveMode::Overwrite,
+            )
+            .await
+            .unwrap();
+        service
+            .redis
+            .set_account_state(uid, AccountState::Archived, None)
+            .await
+            .unwrap();
+
+        // 归档的账户不会被刷新写回激活
+        let report = service.refresh(uid).await.unwrap();
+        assert!(report.superseded);
+        assert_eq!(report.version, None);
+        let status = service.redis.account_status(uid).await.unwrap();
+        assert_eq!(status.state, AccountState::Archived);
+        assert_eq!(
+            service.redis.query_cookies(uid).await.unwrap().cookies["SUB"],
+            "sub_old"
+        );
+
+        // 未写入时只记录说明,不记录版本
+        let page = audit
+            .page(&AuditQuery {
+                uid: Some(uid.to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let entry = &page.entries[0];
+        assert_eq!(entry.operation, AuditOperation::Save);
+        assert_eq!(entry.caller, "refresh");
+        assert!(entry.detail.as_deref().unwrap().contains("未保存"));
+
+        service.redis.delete_cookies(uid).await.unwrap();
+    }
+}
@@ -0,0 +1,734 @@
+//! 后台重新验证
+//!
+//! `validated_at` 只在保存时写入,账户放久了就不知道是否还能用。
+//! 后台任务按固定间隔把所有未删除的账户重新验证一遍,记录验证结果
+//! (见 [`RedisService::record_validation`],过期、封禁或锁定的账户随之标记为失效),
+//! 并推送进度与汇总事件:
+//! - `revalidation_progress`: 每验证完一个账户推送一次
+//! - `revalidation_finished`: 一轮结束后推送汇总
+//!
+//! 前端也可以随时对单个账户、某个标签或全部账户立即发起一轮,
+//! 或对选中的一组账户批量验证 (可中途取消,开始和完成都推送 `validation_progress`)。
+//! 后台与手动发起的验证共享同一个并发上限,不会叠加出更多的浏览器页面。
+
+use chrono::Utc;
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{RwLock, Semaphore};
+
+use crate::models::{
+    RevalidationProgress, RevalidationScope, RevalidationStatus, RevalidationSummary,
+    RevalidationTrigger, StorageError, TagMatch, ValidationOutcome, ValidationStage,
+};
+use crate::services::{CookieValidator, RedisService};
+
+/// 默认验证间隔: 12小时
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(12 * 3600);
+
+/// 默认并发数
+pub const DEFAULT_CONCURRENCY: usize = 2;
+
+/// 默认随机延迟上限
+pub const DEFAULT_JITTER: Duration = Duration::from_secs(5);
+
+/// 应用启动后首轮验证前的等待时间,避开启动时的其他初始化
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// 重新验证配置
+///
+/// 读取环境变量:
+/// - REVALIDATION_INTERVAL_SECS: 后台验证间隔,秒,0表示不启用 (默认: 43200)
+/// - REVALIDATION_CONCURRENCY: 同时验证的账户数 (默认: 2)
+/// - REVALIDATION_JITTER_SECS: 每个账户验证前的随机延迟上限,秒 (默认: 5)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevalidationConfig {
+    /// 后台验证间隔,None表示只接受手动发起
+    pub interval: Option<Duration>,
+    pub concurrency: usize,
+    pub jitter: Duration,
+}
+
+impl Default for RevalidationConfig {
+    fn default() -> Self {
+        Self {
+            interval: Some(DEFAULT_INTERVAL),
+            concurrency: DEFAULT_CONCURRENCY,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+impl RevalidationConfig {
+    /// 从环境变量读取
+    ///
+    /// # 错误
+    /// 数值无法解析,或并发数为0时返回说明
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// 从任意键值来源读取 (环境变量或测试中的固定值)
+    ///
+    /// # 错误
+    /// 同 [`from_env`](Self::from_env)
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let var = |key: &str| lookup(key).unwrap_or_default();
+        let secs = |key: &str| -> Result<Option<u64>, String> {
+            match var(key).trim() {
+                "" => Ok(None),
+                value => value
+                    .parse::<u64>()
+                    .map(Some)
+                    .map_err(|_| format!("无效的 {}: {} (应为非负整数秒)", key, value)),
+            }
+        };
+
+        let defaults = Self::default();
+        let interval = match secs("REVALIDATION_INTERVAL_SECS")? {
+            None => defaults.interval,
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+        };
+        let concurrency = match var("REVALIDATION_CONCURRENCY").trim() {
+            "" => defaults.concurrency,
+            value => match value.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return Err(format!(
+                        "无效的 REVALIDATION_CONCURRENCY: {} (应为正整数)",
+                        value
+                    ))
+                }
+            },
+        };
+        let jitter = secs("REVALIDATION_JITTER_SECS")?
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.jitter);
+
+        Ok(Self {
+            interval,
+            concurrency,
+            jitter,
+        })
+    }
+}
+
+/// 调度与最近结果
+struct Schedule {
+    next_run_at: Option<chrono::DateTime<Utc>>,
+    last_summary: Option<RevalidationSummary>,
+}
+
+/// 后台重新验证服务
+pub struct RevalidationService {
+    redis: Arc<RedisService>,
+    validator: Arc<dyn CookieValidator>,
+    config: RevalidationConfig,
+    /// 所有轮次共享的并发上限
+    permits: Semaphore,
+    /// 进行中的轮数
+    running: AtomicUsize,
+    schedule: RwLock<Schedule>,
+}
+
+/// 进行中的一轮: 丢弃 (完成、失败或调用方放弃) 时从计数中减去
+struct RunningGuard<'a>(&'a AtomicUsize);
+
+impl<'a> RunningGuard<'a> {
+    fn new(running: &'a AtomicUsize) -> Self {
+        running.fetch_add(1, Ordering::SeqCst);
+        Self(running)
+    }
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl RevalidationService {
+    pub fn new(
+        redis: Arc<RedisService>,
+        validator: Arc<dyn CookieValidator>,
+        config: RevalidationConfig,
+    ) -> Self {
+        Self {
+            redis,
+            validator,
+            permits: Semaphore::new(config.concurrency.max(1)),
+            config,
+            running: AtomicUsize::new(0),
+            schedule: RwLock::new(Schedule {
+                next_run_at: None,
+                last_summary: None,
+            }),
+        }
+    }
+
+    /// 当前状态
+    pub async fn status(&self) -> RevalidationStatus {
+        let schedule = self.schedule.read().await;
+        RevalidationStatus {
+            interval_secs: self.config.interval.map(|interval| interval.as_secs()),
+            concurrency: self.config.concurrency,
+            running: self.running.load(Ordering::SeqCst),
+            next_run_at: schedule.next_run_at,
+            last_summary: schedule.last_summary.clone(),
+        }
+    }
+
+    /// 验证一批账户,每完成一个调用一次 `on_progress`
+    ///
+    /// 每个账户: 读取Cookies → 验证 → 记录结果。网络错误、超时等无法判断的结果
+    /// 只计入汇总,不覆盖已记录的结果。验证期间不占用Redis连接。
+    ///
+    /// # 错误
+    /// 无法列出范围内的账户时返回 `StorageError`;单个账户的失败只体现在汇总中
+    pub async fn revalidate(
+        &self,
+        scope: RevalidationScope,
+        trigger: RevalidationTrigger,
+        on_progress: impl Fn(&RevalidationProgress) + Sync,
+    ) -> Result<RevalidationSummary, StorageError> {
+        let run_id = uuid::Uuid::new_v4().to_string();
+        self.execute(run_id, scope, trigger, std::future::pending(), |progress| {
+            if progress.stage == ValidationStage::Finished {
+                on_progress(progress);
+            }
+        })
+        .await
+    }
+
+    /// 批量验证 (前端发起,可中途取消)
+    ///
+    /// 与 [`revalidate`](Self::revalidate) 相同,但每个账户开始和完成时都调用 `on_progress`。
+    /// `cancelled` 完成时停止: 进行中的验证被放弃,尚未开始的账户不再验证,
+    /// 返回的汇总只包含已完成的账户并标记 `cancelled`。
+    ///
+    /// # 参数
+    /// - `run_id`: 本轮ID,出现在进度与汇总中
+    /// - `cancelled`: 取消信号
+    pub async fn validate_batch(
+        &self,
+        run_id: String,
+        scope: RevalidationScope,
+        cancelled: impl Future<Output = ()>,
+        on_progress: impl Fn(&RevalidationProgress) + Sync,
+    ) -> Result<RevalidationSummary, StorageError> {
+        self.execute(
+            run_id,
+            scope,
+            RevalidationTrigger::Manual,
+            cancelled,
+            on_progress,
+        )
+        .await
+    }
+
+    /// 验证范围内的账户,直到全部完成或 `cancelled` 完成
+    async fn execute(
+        &self,
+        run_id: String,
+        scope: RevalidationScope,
+        trigger: RevalidationTrigger,
+        cancelled: impl Future<Output = ()>,
+        on_progress: impl Fn(&RevalidationProgress) + Sync,
+    ) -> Result<RevalidationSummary, StorageError> {
+        let uids = self.resolve(&scope).await?;
+        let mut summary = RevalidationSummary::start(run_id, trigger, scope, uids.len());
+
+        tracing::info!(
+            验证ID = %summary.run_id,
+            发起方式 = ?trigger,
+            范围 = ?summary.scope,
+            账户数量 = %uids.len(),
+            "开始重新验证账户"
+        );
+        let _running = RunningGuard::new(&self.running);
+
+        // 开始事件在各账户的验证中发出,完成数由汇总循环更新
+        let completed = AtomicUsize::new(0);
+        let (run_id, total) = (summary.run_id.clone(), summary.total);
+        let on_started = |uid: &str| {
+            on_progress(&RevalidationProgress {
+                run_id: run_id.clone(),
+                uid: uid.to_string(),
+                stage: ValidationStage::Started,
+                outcome: None,
+                message: None,
+                duration_ms: None,
+                completed: completed.load(Ordering::Relaxed),
+                total,
+            })
+        };
+
+        let mut results = stream::iter(uids)
+            .map(|uid| self.revalidate_one(uid, &on_started))
+            .buffer_unordered(self.config.concurrency.max(1));
+        let collect = async {
+            while let Some((uid, outcome, message, duration)) = results.next().await {
+                summary.record(&uid, outcome);
+                completed.store(summary.completed(), Ordering::Relaxed);
+                on_progress(&RevalidationProgress {
+                    run_id: summary.run_id.clone(),
+                    uid,
+                    stage: ValidationStage::Finished,
+                    outcome,
+                    message,
+                    duration_ms: Some(duration.as_millis() as u64),
+                    completed: summary.completed(),
+                    total: summary.total,
+                });
+            }
+        };
+        tokio::select! {
+            () = collect => {}
+            () = cancelled => {
+                tracing::info!(验证ID = %summary.run_id, "重新验证已被取消");
+                summary.cancelled = true;
+            }
+        }
+        drop(results);
+        summary.finish();
+
+        tracing::info!(
+            验证ID = %summary.run_id,
+            账户数量 = %summary.total,
+            有效 = %summary.valid,
+            未通过 = %summary.failed.len(),
+            无法判断 = %summary.inconclusive,
+            跳过 = %summary.skipped,
+            已取消 = %summary.cancelled,
+            "重新验证完成"
+        );
+
+        self.schedule.write().await.last_summary = Some(summary.clone());
+        Ok(summary)
+    }
+
+    /// 后台定时验证循环 (不会返回,随应用退出)
+    ///
+    /// 启动后稍等片刻开始首轮,之后每隔 `interval` 验证全部账户。
+    /// 未配置间隔时直接返回,只能手动发起。
+    pub async fn run(self: Arc<Self>, app: AppHandle) {
+        let Some(interval) = self.config.interval else {
+            tracing::info!("后台重新验证未启用");
+            return;
+        };
+        tracing::info!(
+            间隔秒 = %interval.as_secs(),
+            并发数 = %self.config.concurrency,
+            "后台重新验证已启动"
+        );
+
+        let mut delay = STARTUP_DELAY;
+        loop {
+            self.schedule.write().await.next_run_at = chrono::Duration::from_std(delay)
+                .ok()
+                .map(|delay| Utc::now() + delay);
+            tokio::time::sleep(delay).await;
+            delay = interval;
+
+            let result = self
+                .revalidate(
+                    RevalidationScope::All,
+                    RevalidationTrigger::Scheduled,
+                    |progress| emit_progress(&app, progress),
+                )
+                .await;
+            match result {
+                Ok(summary) => emit_finished(&app, &summary),
+                Err(e) => tracing::warn!(错误 = %e, "后台重新验证失败,等待下一轮"),
+            }
+        }
+    }
+
+    /// 范围内的账户
+    async fn resolve(&self, scope: &RevalidationScope) -> Result<Vec<String>, StorageError> {
+        match scope {
+            RevalidationScope::All => self.redis.list_all_uids().await,
+            RevalidationScope::Uid { uid } => Ok(vec![uid.clone()]),
+            RevalidationScope::Uids { uids } => Ok(uids.clone()),
+            RevalidationScope::Tag { tag } => {
+                self.redis
+                    .accounts_by_tags(std::slice::from_ref(tag), TagMatch::Any)
+                    .await
+            }
+        }
+    }
+
+    /// 验证单个账户,返回 (UID, 结果, 原因, 耗时)
+    ///
+    /// 取得并发名额后调用 `on_started`,耗时从此时算起。
+    async fn revalidate_one(
+        &self,
+        uid: String,
+        on_started: &(impl Fn(&str) + Sync),
+    ) -> (String, Option<ValidationOutcome>, Option<String>, Duration) {
+        let Ok(_permit) = self.permits.acquire().await else {
+            return (
+                uid,
+                None,
+                Some("验证服务已关闭".to_string()),
+                Duration::ZERO,
+            );
+        };
+        tokio::time::sleep(self.jitter()).await;
+        on_started(&uid);
+        let started = Instant::now();
+
+        // 读取完即归还连接,验证可能持续数十秒
+        let cookies = match self.redis.query_cookies(&uid).await {
+            Ok(cookies) => cookies,
+            Err(e) => {
+                tracing::debug!(用户ID = %uid, 错误 = %e, "账户无法读取,跳过重新验证");
+                return (uid, None, Some(e.to_string()), started.elapsed());
+            }
+        };
+
+        let (outcome, message) = match self.validator.validate_cookies(&cookies.cookies).await {
+            Ok((validated_uid, _)) if validated_uid == uid => (ValidationOutcome::Valid, None),
+            Ok((validated_uid, _)) => (
+                ValidationOutcome::Unknown,
+                Some(format!("Cookies属于其他账户: {}", validated_uid)),
+            ),
+            Err(e) => (ValidationOutcome::from_error(&e), Some(e.to_string())),
+        };
+
+        if outcome.is_conclusive() {
+            if let Err(e) = self
+                .redis
+                .record_validation(&uid, outcome, Utc::now())
+                .await
+            {
+                tracing::warn!(用户ID = %uid, 错误 = %e, "验证结果记录失败");
+            }
+        }
+        tracing::debug!(用户ID = %uid, 验证结果 = %outcome.as_str(), "账户重新验证完成");
+
+        (uid, Some(outcome), message, started.elapsed())
+    }
+
+    /// 随机延迟,避免同一时刻发出一串请求
+    fn jitter(&self) -> Duration {
+        let max_millis = self.config.jitter.as_millis() as u64;
+        if max_millis == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(fastrand::u64(0..=max_millis))
+    }
+}
+
+/// 推送单个账户的验证进度
+pub fn emit_progress(app: &AppHandle, progress: &RevalidationProgress) {
+    let _ = app.emit_all("revalidation_progress", progress);
+}
+
+/// 推送批量验证中单个账户的开始与完成
+pub fn emit_validation_progress(app: &AppHandle, progress: &RevalidationProgress) {
+    let _ = app.emit_all("validation_progress", progress);
+}
+
+/// 推送一轮验证的汇总
+pub fn emit_finished(app: &AppHandle, summary: &RevalidationSummary) {
+    let _ = app.emit_all("revalidation_finished", summary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CookiesData, SnapshotSource, SnapshotValidation};
+    use crate::services::{NoopValidator, SaveMode};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_config_from_lookup() {
+        let config = |pairs: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            RevalidationConfig::from_lookup(|key| vars.get(key).cloned())
+        };
+
+        assert_eq!(config(&[]), Ok(RevalidationConfig::default()));
+
+        let parsed = config(&[
+            ("REVALIDATION_INTERVAL_SECS", "0"),
+            ("REVALIDATION_CONCURRENCY", "4"),
+            ("REVALIDATION_JITTER_SECS", "0"),
+        ])
+        .unwrap();
+        assert_eq!(parsed.interval, None);
+        assert_eq!(parsed.concurrency, 4);
+        assert_eq!(parsed.jitter, Duration::ZERO);
+
+        assert!(config(&[("REVALIDATION_CONCURRENCY", "0")]).is_err());
+        assert!(config(&[("REVALIDATION_INTERVAL_SECS", "soon")]).is_err());
+    }
+
+    #[test]
+    fn test_jitter_within_bounds() {
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        let service = RevalidationService::new(
+            redis,
+            Arc::new(NoopValidator::rejecting()),
+            RevalidationConfig {
+                jitter: Duration::from_millis(50),
+                ..RevalidationConfig::default()
+            },
+        );
+        for _ in 0..100 {
+            assert!(service.jitter() <= Duration::from_millis(50));
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_revalidate_records_outcome() {
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        let uid = "test_revalidate_uid";
+        let cookies = HashMap::from([
+            ("SUB".to_string(), "sub".to_string()),
+            ("SUBP".to_string(), "subp".to_string()),
+        ]);
+        redis
+            .save_cookies(
+                &CookiesData::new(uid.to_string(), cookies),
+                SnapshotSource::Manual,
+                SnapshotValidation::Unverified,
+                SaveMode::Overwrite,
+            )
+            .await
+            .unwrap();
+
+        let service = RevalidationService::new(
+            redis.clone(),
+            Arc::new(NoopValidator::rejecting()),
+            RevalidationConfig {
+                jitter: Duration::ZERO,
+                ..RevalidationConfig::default()
+            },
+        );
+        let progress = Mutex::new(Vec::new());
+        let summary = service
+            .revalidate(
+                RevalidationScope::Uid {
+                    uid: uid.to_string(),
+                },
+                RevalidationTrigger::Manual,
+                |event| progress.lock().unwrap().push(event.clone()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(summary.failed, vec![uid]);
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].outcome, Some(ValidationOutcome::Expired));
+        assert_eq!(progress[0].completed, 1);
+
+        let account = redis.account_summary(uid).await.unwrap().unwrap();
+        assert_eq!(
+            account.last_validation.map(|record| record.outcome),
+            Some(ValidationOutcome::Expired)
+        );
+        assert_eq!(service.status().await.running, 0);
+
+        redis.delete_cookies(uid).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_expired_account_marked_invalid() {
+        use crate::models::AccountState;
+        use redis::AsyncCommands;
+
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        let uid = "test_revalidate_invalid_uid";
+        let cookies = HashMap::from([
+            ("SUB".to_string(), "sub".to_string()),
+            ("SUBP".to_string(), "subp".to_string()),
+        ]);
+        redis
+            .save_cookies(
+                &CookiesData::new(uid.to_string(), cookies),
+                SnapshotSource::Manual,
+                SnapshotValidation::Unverified,
+                SaveMode::Overwrite,
+            )
+            .await
+            .unwrap();
+
+        let service = RevalidationService::new(
+            redis.clone(),
+            Arc::new(NoopValidator::rejecting()),
+            RevalidationConfig {
+                jitter: Duration::ZERO,
+                ..RevalidationConfig::default()
+            },
+        );
+        service
+            .revalidate(
+                RevalidationScope::Uid {
+                    uid: uid.to_string(),
+                },
+                RevalidationTrigger::Scheduled,
+                |_| {},
+            )
+            .await
+            .unwrap();
+
+        // 过期的账户被标记为失效并保留,不会随TTL悄悄消失
+        let status = redis.account_status(uid).await.unwrap();
+        assert_eq!(status.state, AccountState::Invalid);
+        assert!(status.state_reason.is_some());
+        let mut conn = redis.connection().await.unwrap();
+        let ttl: i64 = conn.ttl(redis.namespace().cookies_key(uid)).await.unwrap();
+        assert_eq!(ttl, -1);
+
+        redis.delete_cookies(uid).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_validate_batch_cancelled_before_start() {
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        let service = RevalidationService::new(
+            redis,
+            Arc::new(NoopValidator::rejecting()),
+            RevalidationConfig {
+                jitter: Duration::ZERO,
+                ..RevalidationConfig::default()
+            },
+        );
+        let progress = Mutex::new(Vec::new());
+        let summary = service
+            .validate_batch(
+                "batch".to_string(),
+                RevalidationScope::Uids {
+                    uids: vec!["1".to_string(), "2".to_string()],
+                },
+                std::future::ready(()),
+                |event| progress.lock().unwrap().push(event.clone()),
+            )
+            .await
+            .unwrap();
+
+        assert!(summary.cancelled);
+        assert_eq!(summary.run_id, "batch");
+        assert_eq!(summary.total, 2);
+        assert_eq!(summary.completed(), 0);
+        assert!(progress
+            .into_inner()
+            .unwrap()
+            .iter()
+            .all(|event| event.stage == ValidationStage::Started));
+        assert_eq!(service.status().await.running, 0);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_batch_is_not_counted_as_running() {
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        let service = RevalidationService::new(
+            redis,
+            Arc::new(NoopValidator::rejecting()),
+            RevalidationConfig::default(),
+        );
+        // 占满并发名额,这一轮停在等待名额处
+        let permits = service
+            .permits
+            .acquire_many(service.config.concurrency as u32)
+            .await
+            .unwrap();
+
+        let mut batch = Box::pin(service.validate_batch(
+            "abandoned".to_string(),
+            RevalidationScope::Uids {
+                uids: vec!["1".to_string()],
+            },
+            std::future::pending(),
+            |_| {},
+        ));
+        tokio::select! {
+            _ = &mut batch => panic!("名额被占满时不应完成"),
+            () = tokio::time::sleep(Duration::from_millis(50)) => {}
+        }
+        assert_eq!(service.status().await.running, 1);
+
+        // 调用方放弃 (如命令被取消) 后不再计为进行中
+        drop(batch);
+        assert_eq!(service.status().await.running, 0);
+        drop(permits);
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Redis实例
+    async fn test_validate_batch_reports_each_stage() {
+        let redis = Arc::new(RedisService::new("redis://localhost:6379").unwrap());
+        let uid = "test_batch_uid";
+        let cookies = HashMap::from([("SUB".to_string(), "sub".to_string())]);
+        redis
+            .save_cookies(
+                &CookiesData::new(uid.to_string(), cookies),
+                SnapshotSource::Manual,
+                SnapshotValidation::Unverified,
+                SaveMode::Overwrite,
+            )
+            .await
+            .unwrap();
+
+        let service = RevalidationService::new(
+            redis.clone(),
+            Arc::new(NoopValidator::accepting(uid, "批量")),
+            RevalidationConfig {
+                jitter: Duration::ZERO,
+                ..RevalidationConfig::default()
+            },
+        );
+        let progress = Mutex::new(Vec::new());
+        let summary = service
+            .validate_batch(
+                "batch".to_string(),
+                RevalidationScope::Uids {
+                    uids: vec![uid.to_string(), "test_batch_missing".to_string()],
+                },
+                std::future::pending(),
+                |event| progress.lock().unwrap().push(event.clone()),
+            )
+            .await
+            .unwrap();
+
+        assert!(!summary.cancelled);
+        assert_eq!(summary.valid, 1);
+        assert_eq!(summary.skipped, 1);
+
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(progress.len(), 4);
+        let stages = |uid: &str| -> Vec<ValidationStage> {
+            progress
+                .iter()
+                .filter(|event| event.uid == uid)
+                .map(|event| event.stage)
+                .collect()
+        };
+        assert_eq!(
+            stages(uid),
+            vec![ValidationStage::Started, ValidationStage::Finished]
+        );
+        let finished = progress
+            .iter()
+            .find(|event| event.uid == uid && event.stage == ValidationStage::Finished)
+            .unwrap();
+        assert_eq!(finished.outcome, Some(ValidationOutcome::Valid));
+        assert!(finished.duration_ms.is_some());
+        assert_eq!(progress.last().unwrap().completed, 2);
+
+        redis.delete_cookies(uid).await.unwrap();
+    }
+}
@@ -0,0 +1,546 @@
+//! 本地暂存队列
+//!
+//! 扫码确认后保存Redis失败时,刚拿到的Cookies不能丢: 先加密写入本地队列,
+//! 后台按退避间隔重试,Redis恢复后自动写回,用户不必重新扫码。
+//!
+//! 存储布局 (`{配置目录}/微博登录助手/spool/`):
+//! - `spool.key`: 32字节随机密钥,仅当前用户可读
+//! - `{id}.spool`: 每个待保存条目一个文件,`magic(8) + nonce(24) + 密文`
+//!
+//! 写入先落到临时文件并fsync,再原子重命名,断电不会留下半个条目。
+//! 密钥与队列放在同一目录,防的是备份、同步盘、日志收集等途径带出明文cookies,
+//! 不防能读取用户目录的本机攻击者。
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, Notify};
+
+use crate::models::{
+    AuditEvent, AuditOperation, CookiesData, SnapshotSource, SnapshotValidation, SpoolEntry,
+    SpoolEntrySummary, SpoolError, SpoolFlushReport, SpoolStatus, StorageError,
+};
+use crate::services::{AuditLog, RedisService, SaveMode, SaveWinner};
+use crate::utils::private_file::create_private;
+
+/// 条目文件魔数
+const MAGIC: &[u8; 8] = b"WBSPOOL1";
+
+/// 密钥文件名
+const KEY_FILE: &str = "spool.key";
+
+/// 条目文件扩展名
+const ENTRY_EXT: &str = "spool";
+
+/// 无法解密的条目改用的扩展名
+const CORRUPT_EXT: &str = "corrupt";
+
+/// Redis拒绝保存 (非连接错误) 的条目改用的扩展名
+const FAILED_EXT: &str = "failed";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+/// 首次重试前的等待时间
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+
+/// 重试间隔上限
+pub const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// 队列为空时检查目录的间隔 (兜底,正常由入队唤醒)
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 重试调度状态
+struct RetryState {
+    backoff: Duration,
+    next_retry_at: Option<DateTime<Utc>>,
+    last_flushed_at: Option<DateTime<Utc>>,
+}
+
+/// 本地暂存队列
+pub struct SpoolService {
+    dir: PathBuf,
+    cipher: XChaCha20Poly1305,
+    redis: Arc<RedisService>,
+    audit: Option<Arc<AuditLog>>,
+    /// 入队时唤醒后台任务
+    wake: Notify,
+    /// 后台重试与手动重试互斥,避免同一条目被保存两次
+    flush_lock: Mutex<()>,
+    retry: Mutex<RetryState>,
+}
+
+impl SpoolService {
+    /// 默认队列目录: 与日志同在应用配置目录下
+    pub fn default_dir() -> PathBuf {
+        dirs::config_dir()
+            .map(|p| p.join("微博登录助手"))
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("spool")
+    }
+
+    /// 打开队列目录,首次使用时生成密钥
+    ///
+    /// 无法解密的条目在此隔离,不会在之后的重试中反复出错。
+    ///
+    /// # 错误
+    /// - `SpoolError::Io`: 目录无法创建或密钥无法读写
+    /// - `SpoolError::Corrupted`: 密钥文件长度不对
+    pub fn open(dir: impl Into<PathBuf>, redis: Arc<RedisService>) -> Result<Self, SpoolError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+
+        let key = load_or_create_key(&dir.join(KEY_FILE))?;
+
+        tracing::info!(队列目录 = %dir.display(), "本地暂存队列已打开");
+        let spool = Self {
+            dir,
+            cipher: XChaCha20Poly1305::new(&key),
+            redis,
+            audit: None,
+            wake: Notify::new(),
+            flush_lock: Mutex::new(()),
+            retry: Mutex::new(RetryState {
+                backoff: INITIAL_BACKOFF,
+                next_retry_at: None,
+                last_flushed_at: None,
+            }),
+        };
+        spool.load_entries(true)?;
+        Ok(spool)
+    }
+
+    /// 写回Redis时记录审计日志 (构建器模式)
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 把保存失败的Cookies放入队列
+    ///
+    /// # 参数
+    /// - `error`: 保存失败的原因,展示给用户
+    pub fn enqueue(
+        &self,
+        cookies: CookiesData,
+        source: SnapshotSource,
+        validation: SnapshotValidation,
+        error: &str,
+    ) -> Result<SpoolEntrySummary, SpoolError> {
+        let entry = SpoolEntry::new(cookies, source, validation, error);
+        self.write_entry(&entry)?;
+        self.wake.notify_one();
+
+        tracing::warn!(
+            用户ID = %entry.cookies.uid,
+            条目ID = %entry.id,
+            Cookies样本 = %entry.cookies.sample_for_logging(),
+            "Cookies已暂存到本地队列"
+        );
+        Ok(entry.summary())
+    }
+
+    /// 队列状态
+    ///
+    /// 只读: 无法解密的条目不计入,留给 `flush` 隔离。
+    pub async fn status(&self) -> Result<SpoolStatus, SpoolError> {
+        let pending = self
+            .load_entries(false)?
+            .iter()
+            .map(SpoolEntry::summary)
+            .collect::<Vec<_>>();
+        let retry = self.retry.lock().await;
+
+        Ok(SpoolStatus {
+            next_retry_at: if pending.is_empty() {
+                None
+            } else {
+                retry.next_retry_at
+            },
+            last_flushed_at: retry.last_flushed_at,
+            pending,
+        })
+    }
+
+    /// 立即尝试把所有条目写回Redis
+    ///
+    /// 按进入队列的先后顺序保存 (`IfNewer`,期间重新登录得到的更新Cookies不会被覆盖)。
+    /// 遇到连接类错误即停止,其余条目留待下次重试。
+    /// 其他错误 (脚本执行失败、数据无法序列化等) 重试也不会成功,
+    /// 条目改名为 `.failed` 隔离留作排查;无法解密的条目改名为 `.corrupt`。
+    pub async fn flush(&self) -> Result<SpoolFlushReport, SpoolError> {
+        let _guard = self.flush_lock.lock().await;
+
+        let mut report = SpoolFlushReport::default();
+        for mut entry in self.load_entries(true)? {
+            let uid = entry.cookies.uid.clone();
+            let result = self
+                .redis
+                .save_cookies(
+                    &entry.cookies,
+                    entry.source,
+                    entry.validation,
+                    SaveMode::IfNewer,
+                )
+                .await;
+
+            let mut event = AuditEvent::new(AuditOperation::Save, "spool")
+                .with_uid(&uid)
+                .with_result(&result);
+
+            match result {
+                Ok(outcome) => {
+                    self.remove_entry(&entry.id)?;
+                    if outcome.winner == SaveWinner::Existing {
+                        event = event.with_detail("Redis中已有更新的Cookies,暂存条目已丢弃");
+                        report.superseded.push(uid);
+                    } else {
+                        report.saved.push(uid);
+                    }
+                }
+                Err(e) => {
+                    entry.attempts += 1;
+                    entry.last_error = Some(e.to_string());
+                    self.write_entry(&entry)?;
+
+                    if is_connection_error(&e) {
+                        report.failed += 1;
+                        self.record(event).await;
+                        break;
+                    }
+
+                    tracing::error!(用户ID = %uid, 条目ID = %entry.id, 错误 = %e, "Redis拒绝保存暂存条目,已隔离");
+                    self.quarantine(&self.entry_path(&entry.id), FAILED_EXT)?;
+                    event = event.with_detail("暂存条目已隔离,不再重试");
+                    report.quarantined.push(uid);
+                }
+            }
+            self.record(event).await;
+        }
+
+        report.remaining = self.entry_paths()?.len();
+        if report.remaining == 0 {
+            self.retry.lock().await.last_flushed_at = Some(Utc::now());
+        }
+
+        tracing::info!(
+            已保存 = %report.saved.len(),
+            已被取代 = %report.superseded.len(),
+            失败 = %report.failed,
+            已隔离 = %report.quarantined.len(),
+            剩余 = %report.remaining,
+            "本地暂存队列重试完成"
+        );
+        Ok(report)
+    }
+
+    /// 后台重试循环 (不会返回,随应用退出)
+    ///
+    /// 队列为空时等待入队唤醒;有条目时立即重试,仍有剩余则按指数退避等待
+    /// (5秒起,最长5分钟),全部写回后退避时间复位。
+    pub async fn run(self: Arc<Self>, app: AppHandle) {
+        tracing::info!(队列目录 = %self.dir.display(), "本地暂存队列后台重试已启动");
+
+        loop {
+            let pending = self.entry_paths().map(|paths| paths.len()).unwrap_or(0);
+            if pending == 0 {
+                self.retry.lock().await.next_retry_at = None;
+                let _ = tokio::time::timeout(IDLE_CHECK_INTERVAL, self.wake.notified()).await;
+                continue;
+            }
+
+            match self.flush().await {
+                Ok(report) => {
+                    if !report.saved.is_empty() || !report.superseded.is_empty() {
+                        let _ = app.emit_all("spool_flushed", report.clone());
+                    }
+                    if report.remaining == 0 {
+                        self.retry.lock().await.backoff = INITIAL_BACKOFF;
+                        continue;
+                    }
+                }
+                Err(e) => tracing::warn!(错误 = %e, "本地暂存队列重试失败"),
+            }
+
+            let backoff = {
+                let mut retry = self.retry.lock().await;
+                let backoff = retry.backoff;
+                retry.backoff = (backoff * 2).min(MAX_BACKOFF);
+                retry.next_retry_at = chrono::Duration::from_std(backoff)
+                    .ok()
+                    .map(|delay| Utc::now() + delay);
+                backoff
+            };
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    async fn record(&self, event: AuditEvent) {
+        if let Some(audit) = &self.audit {
+            audit.record(event).await;
+        }
+    }
+
+    /// 读取所有条目 (按ID即入队先后排序)
+    ///
+    /// 无法解密的文件跳过;`quarantine` 为true时改名为 `.corrupt` 留作排查,不再参与重试。
+    fn load_entries(&self, quarantine: bool) -> Result<Vec<SpoolEntry>, SpoolError> {
+        let mut entries = Vec::new();
+        for path in self.entry_paths()? {
+            let data = std::fs::read(&path)?;
+            match self.decrypt(&data) {
+                Ok(entry) => entries.push(entry),
+                Err(e) if quarantine => {
+                    tracing::error!(文件 = %path.display(), 错误 = %e, "暂存条目无法读取,已隔离");
+                    self.quarantine(&path, CORRUPT_EXT)?;
+                }
+                Err(e) => {
+                    tracing::debug!(文件 = %path.display(), 错误 = %e, "暂存条目无法读取,已跳过");
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 把条目文件改为其他扩展名,不再参与重试
+    fn quarantine(&self, path: &Path, ext: &str) -> Result<(), SpoolError> {
+        std::fs::rename(path, path.with_extension(ext))?;
+        Ok(())
+    }
+
+    fn entry_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, ENTRY_EXT))
+    }
+
+    /// 所有条目文件路径 (已排序)
+    fn entry_paths(&self) -> Result<Vec<PathBuf>, SpoolError> {
+        let mut paths = Vec::new();
+        for dir_entry in std::fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            if path.extension().is_some_and(|ext| ext == ENTRY_EXT) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// 加密写入条目 (临时文件 + fsync + 原子重命名)
+    fn write_entry(&self, entry: &SpoolEntry) -> Result<(), SpoolError> {
+        let data = self.encrypt(entry)?;
+        let path = self.entry_path(&entry.id);
+        let tmp = self.dir.join(format!("{}.tmp", entry.id));
+
+        let mut file = create_private(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn remove_entry(&self, id: &str) -> Result<(), SpoolError> {
+        match std::fs::remove_file(self.entry_path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn encrypt(&self, entry: &SpoolEntry) -> Result<Vec<u8>, SpoolError> {
+        let plaintext =
+            serde_json::to_vec(entry).map_err(|e| SpoolError::Encryption(e.to_string()))?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: MAGIC,
+                },
+            )
+            .map_err(|e| SpoolError::Encryption(e.to_string()))?;
+
+        let mut data = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<SpoolEntry, SpoolError> {
+        let body = data
+            .strip_prefix(MAGIC.as_slice())
+            .filter(|body| body.len() > NONCE_LEN)
+            .ok_or_else(|| SpoolError::Corrupted("文件头无效".to_string()))?;
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+        let plaintext = self
+            .cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: MAGIC,
+                },
+            )
+            .map_err(|_| SpoolError::Corrupted("认证失败".to_string()))?;
+
+        serde_json::from_slice(&plaintext).map_err(|e| SpoolError::Corrupted(e.to_string()))
+    }
+}
+
+/// Redis暂时不可达的错误 (值得等待后重试,不必继续尝试后面的条目)
+fn is_connection_error(error: &StorageError) -> bool {
+    matches!(
+        error,
+        StorageError::RedisConnectionFailed(_) | StorageError::OperationTimeout(_)
+    )
+}
+
+/// 读取密钥,不存在时生成
+fn load_or_create_key(path: &Path) -> Result<Key, SpoolError> {
+    match std::fs::read(path) {
+        Ok(bytes) if bytes.len() == KEY_LEN => Ok(*Key::from_slice(&bytes)),
+        Ok(_) => Err(SpoolError::Corrupted(format!(
+            "密钥文件长度错误: {}",
+            path.display()
+        ))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+            let mut file = create_private(path)?;
+            file.write_all(&key)?;
+            file.sync_all()?;
+            tracing::info!(密钥文件 = %path.display(), "已生成本地暂存队列密钥");
+            Ok(key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("weibo-spool-test-{}", uuid::Uuid::new_v4()))
+    }
+
+    /// 指向一个不会有Redis监听的端口,连接立即被拒绝
+    fn unreachable_redis() -> Arc<RedisService> {
+        Arc::new(RedisService::new("redis://127.0.0.1:1").unwrap())
+    }
+
+    fn cookies(uid: &str) -> CookiesData {
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "secret_sub_value".to_string());
+        cookies.insert("SUBP".to_string(), "secret_subp_value".to_string());
+        CookiesData::new(uid.to_string(), cookies)
+    }
+
+    #[test]
+    fn test_entries_are_encrypted_and_survive_reopen() {
+        let dir = temp_dir();
+        let spool = SpoolService::open(&dir, unreachable_redis()).unwrap();
+        let summary = spool
+            .enqueue(
+                cookies("123"),
+                SnapshotSource::QrLogin,
+                SnapshotValidation::TrustedServer,
+                "Redis连接失败",
+            )
+            .unwrap();
+
+        let raw = std::fs::read(dir.join(format!("{}.spool", summary.id))).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("secret_sub_value"));
+
+        let reopened = SpoolService::open(&dir, unreachable_redis()).unwrap();
+        let entries = reopened.load_entries(false).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].cookies.uid, "123");
+        assert_eq!(
+            entries[0].cookies.cookies.get("SUB").map(String::as_str),
+            Some("secret_sub_value")
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tampered_entry_is_quarantined() {
+        let dir = temp_dir();
+        let spool = SpoolService::open(&dir, unreachable_redis()).unwrap();
+        let summary = spool
+            .enqueue(
+                cookies("123"),
+                SnapshotSource::QrLogin,
+                SnapshotValidation::TrustedServer,
+                "Redis连接失败",
+            )
+            .unwrap();
+
+        let path = dir.join(format!("{}.spool", summary.id));
+        let mut raw = std::fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0x01;
+        std::fs::write(&path, raw).unwrap();
+
+        // 查询状态不改动文件
+        assert!(spool.status().await.unwrap().pending.is_empty());
+        assert!(path.exists());
+
+        let report = spool.flush().await.unwrap();
+        assert_eq!(report.remaining, 0);
+        assert!(!path.exists());
+        assert!(path.with_extension("corrupt").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_flush_keeps_entries_while_redis_is_down() {
+        let dir = temp_dir();
+        let spool = SpoolService::open(&dir, unreachable_redis()).unwrap();
+        for uid in ["1", "2"] {
+            spool
+                .enqueue(
+                    cookies(uid),
+                    SnapshotSource::QrLogin,
+                    SnapshotValidation::TrustedServer,
+                    "Redis连接失败",
+                )
+                .unwrap();
+        }
+
+        let report = spool.flush().await.unwrap();
+        assert!(report.saved.is_empty());
+        assert_eq!(report.failed, 1, "连接失败后应停止尝试后续条目");
+        assert_eq!(report.remaining, 2);
+
+        let status = spool.status().await.unwrap();
+        assert_eq!(status.pending.len(), 2);
+        assert_eq!(status.pending[0].uid, "1");
+        assert_eq!(status.pending[0].attempts, 1);
+        assert_eq!(status.pending[1].attempts, 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_only_connection_errors_are_retried() {
+        assert!(is_connection_error(&StorageError::RedisConnectionFailed("refused".to_string())));
+        assert!(is_connection_error(&StorageError::OperationTimeout("5s".to_string())));
+        assert!(!is_connection_error(&StorageError::CommandFailed("ERR script".to_string())));
+        assert!(!is_connection_error(&StorageError::SerializationError("bad".to_string())));
+    }
+}
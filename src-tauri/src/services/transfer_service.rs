@@ -0,0 +1,655 @@
+//! Cookies导入导出服务
+//!
+//! - 导出: 按UID、选择列表、标签或全部账户导出cookies到文件
+//! - 导入: 解析文件为账户分组,逐个经过格式校验与cookies验证后保存
+//! - 加密传输包: 口令保护的bundle,用于把账户交给队友
+//!
+//! 格式转换由 `utils::cookie_formats` 完成,本模块只负责范围解析、验证与文件读写。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::models::account_tags::normalize_tags;
+use crate::models::{
+    CookiesData, SnapshotSource, SnapshotValidation, StorageError, TagMatch, TransferError,
+};
+use crate::services::{CookieValidator, RedisService, SaveMode, SaveWinner};
+use crate::utils::bundle_crypto;
+use crate::utils::cookie_formats::{self, BundleAccount, CookieFormat, CookieGroup, CookiesBundle};
+use crate::utils::private_file::write_private;
+
+/// 导出范围
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportScope {
+    /// 单个账户
+    Uid { uid: String },
+
+    /// 前端勾选的多个账户
+    Selection { uids: Vec<String> },
+
+    /// 按标签筛选
+    Tags {
+        tags: Vec<String>,
+        #[serde(default = "default_tag_match")]
+        mode: TagMatch,
+    },
+
+    /// 所有账户
+    All,
+}
+
+fn default_tag_match() -> TagMatch {
+    TagMatch::All
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportReport {
+    /// 导出格式
+    pub format: CookieFormat,
+
+    /// 是否为口令加密的传输包
+    pub encrypted: bool,
+
+    /// 写入的文件 (storageState多账户时每个账户一个文件)
+    pub files: Vec<PathBuf>,
+
+    /// 导出的UID
+    pub exported: Vec<String>,
+
+    /// 范围内但已不存在的UID (导出期间过期或被删除)
+    pub missing: Vec<String>,
+}
+
+/// 单个账户的导入结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// 已保存到Redis
+    Imported,
+
+    /// 验证通过,但处于dry-run模式未写入
+    WouldImport,
+
+    /// 未导入但不算错误 (重复、Redis中已有更新的版本、没有微博cookie)
+    Skipped,
+
+    /// 格式校验或验证失败
+    Failed,
+}
+
+/// 导入报告中的一项 (对应文件中的一个账户分组)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportEntry {
+    /// 分组在文件中的序号 (从0开始)
+    pub index: usize,
+
+    /// UID (验证后得到;验证失败时为文件中声明的UID)
+    pub uid: Option<String>,
+
+    /// 用户昵称
+    pub screen_name: Option<String>,
+
+    /// 导入状态
+    pub status: ImportStatus,
+
+    /// 跳过或失败的原因
+    pub reason: Option<String>,
+
+    /// cookie名称 (不含值)
+    pub cookie_names: Vec<String>,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// 实际使用的格式 (未指定时为推断结果)
+    pub format: CookieFormat,
+
+    /// 是否来自口令加密的传输包
+    pub encrypted: bool,
+
+    /// 是否为dry-run
+    pub dry_run: bool,
+
+    /// 已导入 (dry-run时为可导入) 数量
+    pub imported: usize,
+
+    /// 跳过数量
+    pub skipped: usize,
+
+    /// 失败数量
+    pub failed: usize,
+
+    /// 逐个账户的结果
+    pub entries: Vec<ImportEntry>,
+}
+
+impl ImportReport {
+    fn new(format: CookieFormat, dry_run: bool, entries: Vec<ImportEntry>) -> Self {
+        let count = |status: &[ImportStatus]| {
+            entries
+                .iter()
+                .filter(|entry| status.contains(&entry.status))
+                .count()
+        };
+
+        Self {
+            format,
+            encrypted: false,
+            dry_run,
+            imported: count(&[ImportStatus::Imported, ImportStatus::WouldImport]),
+            skipped: count(&[ImportStatus::Skipped]),
+            failed: count(&[ImportStatus::Failed]),
+            entries,
+        }
+    }
+}
+
+/// Cookies导入导出服务
+pub struct TransferService {
+    redis: Arc<RedisService>,
+    validator: Arc<dyn CookieValidator>,
+}
+
+impl TransferService {
+    /// 创建服务
+    pub fn new(redis: Arc<RedisService>, validator: Arc<dyn CookieValidator>) -> Self {
+        Self { redis, validator }
+    }
+
+    /// 解析导出范围为UID列表 (已去重,保持顺序)
+    pub async fn resolve_scope(&self, scope: &ExportScope) -> Result<Vec<String>, StorageError> {
+        let mut uids = match scope {
+            ExportScope::Uid { uid } => vec![uid.clone()],
+            ExportScope::Selection { uids } => uids.clone(),
+            ExportScope::Tags { tags, mode } => self.redis.accounts_by_tags(tags, *mode).await?,
+            ExportScope::All => {
+                let mut uids = self.redis.list_all_uids().await?;
+                uids.sort();
+                uids
+            }
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        uids.retain(|uid| seen.insert(uid.clone()));
+        Ok(uids)
+    }
+
+    /// 导出cookies到文件
+    ///
+    /// # 参数
+    /// - `scope`: 导出范围
+    /// - `format`: 导出格式
+    /// - `path`: 目标文件路径,父目录不存在时自动创建
+    ///
+    /// storageState每个文件只能描述一个浏览器上下文,
+    /// 多个账户时写入 `{文件名}.{uid}.{扩展名}`。
+    ///
+    /// # 错误
+    /// - `TransferError::EmptySelection`: 范围内没有任何现存账户
+    /// - `TransferError::Storage`: Redis读取失败
+    /// - `TransferError::Io`: 文件写入失败
+    pub async fn export(
+        &self,
+        scope: &ExportScope,
+        format: CookieFormat,
+        path: &Path,
+    ) -> Result<ExportReport, TransferError> {
+        let (accounts, missing) = self.load_accounts(scope).await?;
+        create_parent_dir(path)?;
+
+        let files = match format {
+            CookieFormat::Netscape => {
+                write_private(path, cookie_formats::to_netscape(&accounts))?;
+                vec![path.to_path_buf()]
+            }
+            CookieFormat::CookieHeader => {
+                write_private(path, cookie_formats::to_cookie_header_lines(&accounts))?;
+                vec![path.to_path_buf()]
+            }
+            CookieFormat::PlaywrightStorageState => self.write_storage_states(&accounts, path)?,
+            CookieFormat::Bundle => {
+                let bundle = self.build_bundle(&accounts).await?;
+                let json = serde_json::to_string_pretty(&bundle)
+                    .map_err(|e| TransferError::InvalidFormat(e.to_string()))?;
+                write_private(path, json)?;
+                vec![path.to_path_buf()]
+            }
+        };
+
+        let exported: Vec<String> = accounts.into_iter().map(|c| c.uid).collect();
+
+        tracing::info!(
+            导出格式 = ?format,
+            账户数量 = %exported.len(),
+            缺失数量 = %missing.len(),
+            文件数量 = %files.len(),
+            "Cookies导出完成"
+        );
+
+        Ok(ExportReport {
+            format,
+            encrypted: false,
+            files,
+            exported,
+            missing,
+        })
+    }
+
+    /// 导出为口令加密的传输包
+    ///
+    /// 内容与 `bundle` 格式相同,整体用Argon2id + XChaCha20-Poly1305加密。
+    ///
+    /// # 错误
+    /// - `TransferError::Encryption`: 口令过短
+    /// - 其余同 `export`
+    pub async fn export_encrypted(
+        &self,
+        scope: &ExportScope,
+        path: &Path,
+        passphrase: &str,
+    ) -> Result<ExportReport, TransferError> {
+        // 先检查口令,避免读完Redis才发现口令不合格
+        bundle_crypto::check_passphrase(passphrase)?;
+
+        let (accounts, missing) = self.load_accounts(scope).await?;
+        let bundle = self.build_bundle(&accounts).await?;
+
+        let passphrase = passphrase.to_string();
+        let sealed = tokio::task::spawn_blocking(move || bundle_crypto::seal(&bundle, &passphrase))
+            .await
+            .map_err(|e| TransferError::Encryption(e.to_string()))??;
+
+        create_parent_dir(path)?;
+        write_private(path, sealed)?;
+
+        let exported: Vec<String> = accounts.into_iter().map(|c| c.uid).collect();
+        tracing::info!(
+            账户数量 = %exported.len(),
+            缺失数量 = %missing.len(),
+            "加密传输包导出完成"
+        );
+
+        Ok(ExportReport {
+            format: CookieFormat::Bundle,
+            encrypted: true,
+            files: vec![path.to_path_buf()],
+            exported,
+            missing,
+        })
+    }
+
+    /// 读取范围内现存的账户
+    ///
+    /// # 返回值
+    /// (现存账户, 已不存在的UID)
+    async fn load_accounts(
+        &self,
+        scope: &ExportScope,
+    ) -> Result<(Vec<CookiesData>, Vec<String>), TransferError> {
+        let uids = self.resolve_scope(scope).await?;
+
+        let mut accounts = Vec::with_capacity(uids.len());
+        let mut missing = Vec::new();
+        for uid in uids {
+            match self.redis.query_cookies(&uid).await {
+                Ok(cookies_data) => accounts.push(cookies_data),
+                Err(StorageError::NotFound(_)) => missing.push(uid),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if accounts.is_empty() {
+            return Err(TransferError::EmptySelection(format!("{:?}", scope)));
+        }
+
+        Ok((accounts, missing))
+    }
+
+    /// 组装bundle (附带各账户标签)
+    async fn build_bundle(&self, accounts: &[CookiesData]) -> Result<CookiesBundle, TransferError> {
+        let mut bundle_accounts = Vec::with_capacity(accounts.len());
+        for cookies_data in accounts {
+            let tags = self.redis.get_tags(&cookies_data.uid).await?;
+            bundle_accounts.push(BundleAccount::from_cookies_data(cookies_data, tags));
+        }
+        Ok(cookie_formats::to_bundle(bundle_accounts))
+    }
+
+    /// 从文件导入cookies
+    ///
+    /// # 参数
+    /// - `path`: 源文件路径
+    /// - `format`: 文件格式,None时根据内容推断
+    /// - `dry_run`: 为true时只解析和验证,不写入Redis
+    ///
+    /// # 错误
+    /// - `TransferError::Io`: 文件读取失败
+    /// - `TransferError::InvalidFormat`: 文件无法按格式解析
+    ///
+    /// 单个账户的失败不会中断导入,记录在报告中。
+    pub async fn import_file(
+        &self,
+        path: &Path,
+        format: Option<CookieFormat>,
+        dry_run: bool,
+    ) -> Result<ImportReport, TransferError> {
+        let content = std::fs::read_to_string(path)?;
+        self.import_str(&content, format, dry_run).await
+    }
+
+    /// 从文本导入cookies (用于粘贴的请求头等)
+    ///
+    /// 参数与错误同 `import_file`。
+    pub async fn import_str(
+        &self,
+        content: &str,
+        format: Option<CookieFormat>,
+        dry_run: bool,
+    ) -> Result<ImportReport, TransferError> {
+        let format = format.unwrap_or_else(|| cookie_formats::detect_format(content));
+        let groups = cookie_formats::parse_groups(content, format)
+            .map_err(|e| TransferError::InvalidFormat(e.to_string()))?;
+
+        Ok(self.import_groups(format, groups, dry_run).await)
+    }
+
+    /// 导入口令加密的传输包
+    ///
+    /// 先完成解密和认证标签校验,整个文件确认完整后才开始验证和写入Redis;
+    /// 口令错误或文件被篡改时不会有任何账户被写入。
+    ///
+    /// # 错误
+    /// - `TransferError::Io`: 文件读取失败
+    /// - `TransferError::InvalidFormat`: 不是加密传输包或版本不受支持
+    /// - `TransferError::Decryption`: 口令错误或文件被篡改
+    pub async fn import_encrypted(
+        &self,
+        path: &Path,
+        passphrase: &str,
+        dry_run: bool,
+    ) -> Result<ImportReport, TransferError> {
+        let data = std::fs::read(path)?;
+        let passphrase = passphrase.to_string();
+        let (header, bundle) =
+            tokio::task::spawn_blocking(move || bundle_crypto::open(&data, &passphrase))
+                .await
+                .map_err(|e| TransferError::Decryption(e.to_string()))??;
+
+        tracing::info!(
+            格式版本 = %header.version,
+            创建时间 = %header.created_at,
+            账户数量 = %bundle.accounts.len(),
+            "加密传输包校验通过"
+        );
+
+        let groups = bundle.accounts.into_iter().map(CookieGroup::from).collect();
+        let mut report = self
+            .import_groups(CookieFormat::Bundle, groups, dry_run)
+            .await;
+        report.encrypted = true;
+        Ok(report)
+    }
+
+    /// 逐个导入账户分组并汇总报告
+    async fn import_groups(
+        &self,
+        format: CookieFormat,
+        groups: Vec<CookieGroup>,
+        dry_run: bool,
+    ) -> ImportReport {
+        tracing::info!(
+            导入格式 = ?format,
+            分组数量 = %groups.len(),
+            dry_run = %dry_run,
+            "开始导入Cookies"
+        );
+
+        let mut seen_uids = HashSet::new();
+        let mut entries = Vec::with_capacity(groups.len());
+        for (index, group) in groups.into_iter().enumerate() {
+            let entry = self
+                .import_group(index, group, dry_run, &mut seen_uids)
+                .await;
+            tracing::info!(
+                序号 = %entry.index,
+                用户ID = ?entry.uid,
+                状态 = ?entry.status,
+                原因 = ?entry.reason,
+                "导入分组处理完成"
+            );
+            entries.push(entry);
+        }
+
+        let report = ImportReport::new(format, dry_run, entries);
+        tracing::info!(
+            导入数量 = %report.imported,
+            跳过数量 = %report.skipped,
+            失败数量 = %report.failed,
+            dry_run = %dry_run,
+            "Cookies导入完成"
+        );
+        report
+    }
+
+    /// 导入单个账户分组
+    ///
+    /// 流程: 格式校验 → cookies验证 → UID一致性检查 → 去重 → 保存 (IfNewer)
+    async fn import_group(
+        &self,
+        index: usize,
+        group: CookieGroup,
+        dry_run: bool,
+        seen_uids: &mut HashSet<String>,
+    ) -> ImportEntry {
+        let mut cookie_names: Vec<String> = group.cookies.keys().cloned().collect();
+        cookie_names.sort();
+
+        let mut entry = ImportEntry {
+            index,
+            uid: group.uid.clone(),
+            screen_name: group.screen_name.clone(),
+            status: ImportStatus::Failed,
+            reason: None,
+            cookie_names,
+        };
+
+        if group.cookies.is_empty() {
+            entry.status = ImportStatus::Skipped;
+            entry.reason = Some(format!(
+                "没有微博域名的cookie (忽略了{}个其他站点cookie)",
+                group.ignored_foreign
+            ));
+            return entry;
+        }
+
+        // 1. 格式校验 (UID尚未确定,先用声明值或占位)
+        let declared_uid = group.uid.clone();
+        let candidate = CookiesData::new(declared_uid.clone().unwrap_or_default(), group.cookies);
+        if let Err(e) = candidate.validate() {
+            entry.reason = Some(e.to_string());
+            return entry;
+        }
+
+        // 2. cookies验证,以验证得到的UID为准
+        let (uid, screen_name) = match self.validator.validate_cookies(&candidate.cookies).await {
+            Ok(result) => result,
+            Err(e) => {
+                entry.reason = Some(e.to_string());
+                return entry;
+            }
+        };
+        entry.uid = Some(uid.clone());
+        entry.screen_name = Some(screen_name.clone());
+
+        if let Some(declared) = declared_uid.filter(|declared| *declared != uid) {
+            entry.reason = Some(format!(
+                "文件声明的UID {} 与验证得到的UID {} 不一致",
+                declared, uid
+            ));
+            return entry;
+        }
+
+        // 3. 同一文件中的重复账户只导入第一个
+        if !seen_uids.insert(uid.clone()) {
+            entry.status = ImportStatus::Skipped;
+            entry.reason = Some("文件中重复的账户".to_string());
+            return entry;
+        }
+
+        if dry_run {
+            entry.status = ImportStatus::WouldImport;
+            return entry;
+        }
+
+        // 4. 保存,保留bundle中的原始获取时间以便与Redis中的版本比较新旧
+        let mut cookies_data = CookiesData::new(uid.clone(), candidate.cookies)
+            .with_screen_name(screen_name)
+            .with_namespace(self.redis.namespace());
+        if let Some(fetched_at) = group.fetched_at {
+            cookies_data.fetched_at = fetched_at;
+        }
+
+        match self
+            .redis
+            .save_cookies(
+                &cookies_data,
+                SnapshotSource::Import,
+                SnapshotValidation::Verified,
+                SaveMode::IfNewer,
+            )
+            .await
+        {
+            Ok(outcome) if outcome.winner == SaveWinner::Existing => {
+                entry.status = ImportStatus::Skipped;
+                entry.reason = Some("Redis中已有更新的Cookies".to_string());
+            }
+            Ok(_) => {
+                entry.status = ImportStatus::Imported;
+                if let Err(e) = self.restore_tags(&uid, &group.tags).await {
+                    entry.reason = Some(format!("Cookies已导入,但标签恢复失败: {}", e));
+                }
+            }
+            Err(e) => {
+                entry.reason = Some(e.to_string());
+            }
+        }
+
+        entry
+    }
+
+    /// 恢复bundle中携带的标签
+    async fn restore_tags(&self, uid: &str, tags: &[String]) -> Result<(), TransferError> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+        let tags = normalize_tags(tags).map_err(|e| TransferError::InvalidFormat(e.to_string()))?;
+        self.redis.add_tags(uid, &tags).await?;
+        Ok(())
+    }
+
+    /// 写入storageState文件
+    fn write_storage_states(
+        &self,
+        accounts: &[CookiesData],
+        path: &Path,
+    ) -> Result<Vec<PathBuf>, TransferError> {
+        let mut files = Vec::with_capacity(accounts.len());
+
+        for cookies_data in accounts {
+            let target = if accounts.len() == 1 {
+                path.to_path_buf()
+            } else {
+                per_account_path(path, &cookies_data.uid)
+            };
+            let json =
+                serde_json::to_string_pretty(&cookie_formats::to_storage_state(cookies_data))
+                    .map_err(|e| TransferError::InvalidFormat(e.to_string()))?;
+            write_private(&target, json)?;
+            files.push(target);
+        }
+
+        Ok(files)
+    }
+}
+
+/// 创建目标文件的父目录
+fn create_parent_dir(path: &Path) -> Result<(), TransferError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+/// 生成每个账户的文件路径: `state.json` → `state.{uid}.json`
+fn per_account_path(path: &Path, uid: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "storage-state".to_string());
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, uid, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, uid),
+    };
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_account_path() {
+        assert_eq!(
+            per_account_path(Path::new("/tmp/out/state.json"), "123"),
+            PathBuf::from("/tmp/out/state.123.json")
+        );
+        assert_eq!(
+            per_account_path(Path::new("state"), "123"),
+            PathBuf::from("state.123")
+        );
+    }
+
+    #[test]
+    fn test_import_report_counts() {
+        let entry = |status| ImportEntry {
+            index: 0,
+            uid: None,
+            screen_name: None,
+            status,
+            reason: None,
+            cookie_names: Vec::new(),
+        };
+        let report = ImportReport::new(
+            CookieFormat::Netscape,
+            true,
+            vec![
+                entry(ImportStatus::WouldImport),
+                entry(ImportStatus::Skipped),
+                entry(ImportStatus::Failed),
+                entry(ImportStatus::Failed),
+            ],
+        );
+        assert_eq!((report.imported, report.skipped, report.failed), (1, 1, 2));
+    }
+
+    #[test]
+    fn test_export_scope_deserialize() {
+        let scope: ExportScope =
+            serde_json::from_str(r#"{"type":"tags","tags":["项目A"]}"#).unwrap();
+        assert!(matches!(
+            scope,
+            ExportScope::Tags {
+                mode: TagMatch::All,
+                ..
+            }
+        ));
+
+        let scope: ExportScope = serde_json::from_str(r#"{"type":"all"}"#).unwrap();
+        assert!(matches!(scope, ExportScope::All));
+    }
+}
@@ -1,32 +1,48 @@
+use futures::future::BoxFuture;
 use serde::Deserialize;
 use std::collections::HashMap;
-use tokio::process::Command;
+use std::time::Duration;
 
-use crate::models::ValidationError;
+use crate::models::{ValidationError, ValidationOutcome};
+use crate::services::cookie_validator::CookieValidator;
+use crate::services::validation_worker::ValidationWorker;
 
 /// Cookies验证服务
 ///
 /// 职责:调用Playwright脚本验证cookies有效性。
 /// 通过访问微博个人资料API,确认cookies未过期且可用。
+/// 脚本作为常驻进程运行,见 [`ValidationWorker`]。
 pub struct ValidationService {
     playwright_script_path: String,
+    worker: ValidationWorker,
 }
 
 /// Playwright验证结果
 ///
 /// Node.js脚本返回的JSON结构
 #[derive(Debug, Deserialize)]
-struct PlaywrightValidationResult {
+pub(crate) struct PlaywrightValidationResult {
     /// 验证是否成功
-    valid: bool,
+    pub(crate) valid: bool,
     /// 用户ID (验证成功时返回)
-    uid: Option<String>,
+    pub(crate) uid: Option<String>,
     /// 用户昵称 (验证成功时返回)
-    screen_name: Option<String>,
+    pub(crate) screen_name: Option<String>,
     /// 错误信息 (验证失败时返回)
-    error: Option<String>,
+    pub(crate) error: Option<String>,
+    /// HTTP状态码 (收到响应时返回)
+    #[serde(default)]
+    pub(crate) status: Option<u16>,
+    /// 跟随重定向后的最终URL (收到响应时返回)
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    /// 未收到响应时的失败类型: network_error / timeout / validator_error
+    #[serde(default)]
+    pub(crate) outcome: Option<ValidationOutcome>,
 }
 
+/// 脚本中单个请求的超时时间 (秒),与 validate-cookies.ts 一致
+const SCRIPT_REQUEST_TIMEOUT_SECS: u64 = 10;
 
 impl ValidationService {
     /// 创建新的验证服务
@@ -47,80 +63,52 @@ impl ValidationService {
             "验证服务初始化完成"
         );
         Self {
+            worker: ValidationWorker::new(playwright_script_path.clone()),
             playwright_script_path,
         }
     }
 
-    /// 执行Playwright脚本
-    async fn execute_playwright_script(
-        &self,
-        input_json: &str,
-    ) -> Result<std::process::Output, ValidationError> {
-        tracing::debug!(
-            node命令 = "node",
-            脚本路径 = %self.playwright_script_path,
-            输入JSON = %input_json,
-            "执行Playwright脚本"
-        );
-
-        let output = Command::new("node")
-            .arg(&self.playwright_script_path)
-            .arg(input_json)
-            .output()
-            .await
-            .map_err(|e| {
-                tracing::error!(
-                    错误 = %e,
-                    脚本路径 = %self.playwright_script_path,
-                    "执行Playwright脚本失败"
-                );
-                ValidationError::PlaywrightFailed(format!("Failed to execute: {}", e))
-            })?;
-
-        if !output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            tracing::error!(
-                退出码 = ?output.status.code(),
-                标准输出 = %stdout,
-                错误输出 = %stderr,
-                "Playwright脚本执行出错"
-            );
-            return Err(ValidationError::PlaywrightFailed(format!(
-                "Script failed with exit code {:?}. stdout: {}. stderr: {}",
-                output.status.code(),
-                stdout,
-                stderr
-            )));
-        }
-
-        Ok(output)
+    /// 设置同时进行的验证数量上限 (构建器模式)
+    ///
+    /// 对应验证进程中BrowserContext的数量,默认4个。
+    pub fn with_max_contexts(mut self, max_contexts: usize) -> Self {
+        self.worker =
+            ValidationWorker::with_max_contexts(self.playwright_script_path.clone(), max_contexts)
+                .with_timeout(self.worker.timeout());
+        self
     }
 
-    /// 解析验证结果
-    fn parse_validation_result(
-        output: &[u8],
-    ) -> Result<PlaywrightValidationResult, ValidationError> {
-        serde_json::from_slice(output).map_err(|e| {
-            let stdout = String::from_utf8_lossy(output);
-            tracing::error!(错误 = %e, 标准输出 = %stdout, "解析Playwright输出失败");
-            ValidationError::PlaywrightFailed(format!(
-                "Failed to parse output: {}. Output: {}",
-                e, stdout
-            ))
-        })
+    /// 设置单次验证时限 (构建器模式)
+    ///
+    /// 超时后验证进程及其浏览器被终止,默认30秒。
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.worker = self.worker.with_timeout(timeout);
+        self
     }
 
     /// 提取用户信息
+    ///
+    /// 失败时按脚本给出的失败类型,或根据状态码、最终URL和错误信息分类。
     fn extract_user_info(
         result: PlaywrightValidationResult,
     ) -> Result<(String, String), ValidationError> {
         if !result.valid {
             let error_msg = result.error.unwrap_or_else(|| "Unknown error".to_string());
-            tracing::warn!(错误 = %error_msg, "Cookies验证失败");
-            return Err(ValidationError::ProfileApiFailed {
-                status: 401,
-                message: error_msg,
+            tracing::warn!(
+                错误 = %error_msg,
+                状态码 = ?result.status,
+                失败类型 = ?result.outcome,
+                "Cookies验证失败"
+            );
+            return Err(match result.outcome {
+                Some(ValidationOutcome::NetworkError) => ValidationError::RequestFailed(error_msg),
+                Some(ValidationOutcome::Timeout) => ValidationError::Timeout {
+                    seconds: SCRIPT_REQUEST_TIMEOUT_SECS,
+                },
+                Some(ValidationOutcome::ValidatorError) => {
+                    ValidationError::PlaywrightFailed(error_msg)
+                }
+                _ => ValidationError::rejected(result.status, result.url.as_deref(), error_msg),
             });
         }
 
@@ -137,7 +125,7 @@ impl ValidationService {
 
     /// 验证Cookies有效性
     ///
-    /// 调用Playwright脚本,使用cookies访问微博个人资料API。
+    /// 交给常驻的Playwright验证进程,使用cookies访问微博个人资料API。
     /// 如果返回成功,说明cookies有效。
     ///
     /// # 参数
@@ -147,38 +135,52 @@ impl ValidationService {
     /// - `Ok((uid, screen_name))`: 验证成功,返回用户ID和昵称
     ///
     /// # 错误
-    /// - `ValidationError::PlaywrightFailed`: Playwright脚本执行失败
-    /// - `ValidationError::ProfileApiFailed`: 个人资料API返回错误(cookies无效)
+    /// - `ValidationError::PlaywrightFailed`: 无法启动Playwright验证进程
+    /// - `ValidationError::WorkerCrashed`: 验证进程在返回结果前退出
+    /// - `ValidationError::Timeout`: 超过时限,验证进程已被终止
+    /// - `ValidationError::Rejected`: cookies被拒绝,附带原因 (过期、封禁、验证码等)
+    /// - `ValidationError::ProfileApiFailed`: 个人资料API返回错误且无法识别原因
+    /// - `ValidationError::RequestFailed`: 脚本无法连接微博
     ///
     /// # Playwright脚本约定
-    /// - 输入: JSON字符串作为第一个参数,格式 `{"SUB": "xxx", "SUBP": "yyy"}`
-    /// - 输出: JSON到stdout,格式 `{"valid": bool, "uid": string, "screen_name": string, "error": string}`
-    /// - 退出码: 0表示脚本执行成功(但valid可能为false),非0表示脚本崩溃
+    /// - 常驻进程,stdin/stdout每行一个JSON,cookies不经过命令行参数
+    /// - 输入: `{"id": 1, "cookies": {"SUB": "xxx", "SUBP": "yyy"}}`
+    /// - 输出: `{"id": 1, "valid": bool, "uid": string, "screen_name": string, "error": string,
+    ///   "status": number, "url": string, "outcome": string}`
     pub async fn validate_cookies(
         &self,
         cookies: &HashMap<String, String>,
     ) -> Result<(String, String), ValidationError> {
-        // 序列化输入：直接传递cookies键值对
-        let input_json = serde_json::to_string(cookies)
-            .map_err(|e| ValidationError::PlaywrightFailed(e.to_string()))?;
-
         tracing::debug!(
             脚本路径 = %self.playwright_script_path,
             cookies数量 = %cookies.len(),
             "开始Playwright验证"
         );
 
-        // 执行脚本
-        let output = self.execute_playwright_script(&input_json).await?;
-
-        // 解析结果
-        let result = Self::parse_validation_result(&output.stdout)?;
+        let result = self.worker.validate(cookies).await?;
 
         // 提取用户信息
         Self::extract_user_info(result)
     }
 }
 
+impl CookieValidator for ValidationService {
+    fn name(&self) -> &'static str {
+        "playwright"
+    }
+
+    fn validate_cookies<'a>(
+        &'a self,
+        cookies: &'a HashMap<String, String>,
+    ) -> BoxFuture<'a, Result<(String, String), ValidationError>> {
+        Box::pin(ValidationService::validate_cookies(self, cookies))
+    }
+
+    fn shutdown(&self) {
+        self.worker.shutdown();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +200,60 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn extract(output: serde_json::Value) -> Result<(String, String), ValidationError> {
+        ValidationService::extract_user_info(serde_json::from_value(output).unwrap())
+    }
+
+    #[test]
+    fn test_extract_user_info_outcomes() {
+        use serde_json::json;
+
+        assert_eq!(
+            extract(json!({"valid": true, "uid": "123", "screen_name": "张三"})).unwrap(),
+            ("123".to_string(), "张三".to_string())
+        );
+
+        let expired = extract(json!({
+            "valid": false,
+            "error": "VIP center returned non-JSON response",
+            "status": 200,
+            "url": "https://passport.weibo.com/sso/signin?entry=vip"
+        }));
+        assert!(matches!(
+            expired,
+            Err(ValidationError::Rejected {
+                outcome: ValidationOutcome::Expired,
+                ..
+            })
+        ));
+
+        let rate_limited = extract(json!({"valid": false, "error": "HTTP 429", "status": 429}));
+        assert!(matches!(
+            rate_limited,
+            Err(ValidationError::Rejected {
+                outcome: ValidationOutcome::RateLimited,
+                ..
+            })
+        ));
+
+        let network = extract(json!({
+            "valid": false,
+            "error": "net::ERR_NAME_NOT_RESOLVED",
+            "outcome": "network_error"
+        }));
+        assert!(matches!(network, Err(ValidationError::RequestFailed(_))));
+
+        let timeout = extract(json!({"valid": false, "error": "Timeout", "outcome": "timeout"}));
+        assert!(matches!(timeout, Err(ValidationError::Timeout { .. })));
+
+        // 无法识别原因时保持原有错误
+        let unknown = extract(json!({"valid": false, "error": "系统繁忙", "status": 200}));
+        assert!(matches!(
+            unknown,
+            Err(ValidationError::ProfileApiFailed { status: 200, .. })
+        ));
+    }
+
     #[test]
     fn test_service_creation() {
         let service = ValidationService::new("/path/to/script.js".to_string());
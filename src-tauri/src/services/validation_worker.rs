@@ -0,0 +1,661 @@
+//! Playwright验证Worker
+//!
+//! 常驻的 `node validate-cookies.js` 进程,通过stdin/stdout按行交换JSON:
+//! - 请求: `{"id": 1, "cookies": {...}}`
+//! - 响应: `{"id": 1, "valid": true, "uid": "...", "screen_name": "..."}`
+//!
+//! 浏览器在worker内复用,每个请求使用独立的BrowserContext,
+//! 同时进行的验证数量由 `max_contexts` 限制 (Rust侧与worker侧使用同一个上限)。
+//! cookies只写入stdin,不出现在命令行参数中。
+//!
+//! worker退出 (崩溃或被杀) 时,等待中的请求立即失败,下一个请求自动重启进程。
+//!
+//! 每个请求有时限: 超时的请求单独失败 (`ValidationError::Timeout`),worker随即退役:
+//! 关闭stdin不再接收新请求 (新请求由重启的进程处理),同一进程中其他进行中的验证照常完成,
+//! 全部结束后整个进程组 (node及其启动的浏览器,包括卡死的那个) 被杀掉。
+//! 应用退出时通过 [`ValidationWorker::shutdown`] 清理。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{oneshot, Mutex, Semaphore};
+
+use crate::models::ValidationError;
+use crate::services::validation_service::PlaywrightValidationResult;
+
+/// 默认的BrowserContext上限
+pub const DEFAULT_MAX_CONTEXTS: usize = 4;
+
+/// 默认的单次验证时限
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 写入请求失败时的最大尝试次数 (首次失败通常是worker刚退出,重启后再试一次)
+const MAX_SEND_ATTEMPTS: usize = 2;
+
+#[derive(Serialize)]
+struct WorkerRequest<'a> {
+    id: u64,
+    cookies: &'a HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct WorkerResponse {
+    /// 请求无法解析时为null
+    id: Option<u64>,
+    #[serde(flatten)]
+    result: PlaywrightValidationResult,
+}
+
+/// 等待worker响应的接收端
+type ReplyReceiver = oneshot::Receiver<PlaywrightValidationResult>;
+
+/// 一个worker进程,由发送请求、读取响应与超时处理共享
+///
+/// 读取任务持有到stdout关闭为止,进程退出前 `child` 不会被drop (`kill_on_drop`)。
+struct WorkerHandle {
+    /// 第几个启动的进程
+    generation: u64,
+    pid: Option<u32>,
+    /// 只在持锁确认进程尚未退出后才发送信号: 已回收的pid可能被其他进程复用
+    child: std::sync::Mutex<Child>,
+    pending: std::sync::Mutex<HashMap<u64, oneshot::Sender<PlaywrightValidationResult>>>,
+    /// stdout关闭 (进程退出) 或已被杀掉后置为false
+    alive: AtomicBool,
+    /// 有请求超时: 不再接收新请求,进行中的请求全部结束后杀掉进程组
+    retiring: AtomicBool,
+}
+
+/// 当前接收请求的进程
+struct WorkerProcess {
+    handle: Arc<WorkerHandle>,
+    /// drop即关闭stdin: worker处理完已收到的请求后自行退出
+    stdin: ChildStdin,
+}
+
+/// Playwright验证Worker
+pub struct ValidationWorker {
+    script_path: String,
+    max_contexts: usize,
+    timeout: Duration,
+    permits: Semaphore,
+    next_id: AtomicU64,
+    /// 已启动过的进程数,用于区分首次启动与崩溃重启
+    spawned: AtomicU64,
+    process: Mutex<Option<WorkerProcess>>,
+    /// 所有启动过的进程 (包括退役中的),应用退出时不经过异步锁即可全部杀掉
+    workers: std::sync::Mutex<Vec<Weak<WorkerHandle>>>,
+}
+
+impl ValidationWorker {
+    /// 创建worker (首次验证时才启动进程)
+    pub fn new(script_path: String) -> Self {
+        Self::with_max_contexts(script_path, DEFAULT_MAX_CONTEXTS)
+    }
+
+    /// 指定BrowserContext上限
+    pub fn with_max_contexts(script_path: String, max_contexts: usize) -> Self {
+        let max_contexts = max_contexts.max(1);
+        Self {
+            script_path,
+            max_contexts,
+            timeout: DEFAULT_TIMEOUT,
+            permits: Semaphore::new(max_contexts),
+            next_id: AtomicU64::new(1),
+            spawned: AtomicU64::new(0),
+            process: Mutex::new(None),
+            workers: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 设置单次验证时限 (构建器模式)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// BrowserContext上限
+    pub fn max_contexts(&self) -> usize {
+        self.max_contexts
+    }
+
+    /// 单次验证时限
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 发送验证请求并等待结果
+    ///
+    /// # 错误
+    /// - `ValidationError::PlaywrightFailed`: 无法启动worker或请求无法序列化
+    /// - `ValidationError::WorkerCrashed`: worker在返回结果前退出
+    /// - `ValidationError::Timeout`: 超过时限,只有本请求失败,worker在其他验证结束后重启
+    ///
+    /// 取消 (drop返回的future) 只放弃等待,worker中的请求由其自身的请求超时结束。
+    pub(crate) async fn validate(
+        &self,
+        cookies: &HashMap<String, String>,
+    ) -> Result<PlaywrightValidationResult, ValidationError> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| ValidationError::PlaywrightFailed(e.to_string()))?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut line = serde_json::to_string(&WorkerRequest { id, cookies })
+            .map_err(|e| ValidationError::PlaywrightFailed(e.to_string()))?;
+        line.push('\n');
+
+        let (reply, handle) = self.send(id, &line).await?;
+        match tokio::time::timeout(self.timeout, reply).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(_)) => Err(ValidationError::WorkerCrashed(
+                "验证进程在返回结果前退出".to_string(),
+            )),
+            Err(_) => {
+                tracing::error!(
+                    请求ID = %id,
+                    时限毫秒 = %self.timeout.as_millis(),
+                    "验证超时,其他验证结束后重启验证进程"
+                );
+                self.retire(&handle, id).await;
+                Err(ValidationError::timeout(self.timeout))
+            }
+        }
+    }
+
+    /// 终止所有worker进程组 (应用退出时调用)
+    ///
+    /// 同步执行,不等待进行中的验证;之后的验证会重新启动进程。
+    pub fn shutdown(&self) {
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+        let killed = workers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|handle| handle.kill())
+            .count();
+        if killed > 0 {
+            tracing::info!(进程数量 = %killed, "验证进程已随应用退出终止");
+        }
+    }
+
+    /// 放弃超时的请求并让进程退役
+    ///
+    /// 当前进程即该进程时关闭其stdin,下一个请求启动新进程。
+    async fn retire(&self, handle: &Arc<WorkerHandle>, id: u64) {
+        handle.abandon(id);
+
+        let mut process = self.process.lock().await;
+        if process
+            .as_ref()
+            .is_some_and(|process| Arc::ptr_eq(&process.handle, handle))
+        {
+            *process = None;
+        }
+    }
+
+    /// 写入请求,必要时启动或重启worker
+    async fn send(
+        &self,
+        id: u64,
+        line: &str,
+    ) -> Result<(ReplyReceiver, Arc<WorkerHandle>), ValidationError> {
+        let mut process = self.process.lock().await;
+        let mut last_error = String::new();
+
+        for _ in 0..MAX_SEND_ATTEMPTS {
+            if !process
+                .as_ref()
+                .is_some_and(|process| process.handle.accepts_requests())
+            {
+                *process = Some(self.spawn()?);
+            }
+            let worker = process.as_mut().expect("worker was just spawned");
+
+            let (tx, rx) = oneshot::channel();
+            if !worker.handle.register(id, tx) {
+                last_error = "验证进程已退出".to_string();
+                continue;
+            }
+
+            let written = async {
+                worker.stdin.write_all(line.as_bytes()).await?;
+                worker.stdin.flush().await
+            }
+            .await;
+
+            match written {
+                Ok(()) => return Ok((rx, worker.handle.clone())),
+                Err(e) => {
+                    // 管道已断开: 进程刚退出,标记后重启
+                    worker.handle.pending.lock().unwrap().remove(&id);
+                    worker.handle.alive.store(false, Ordering::SeqCst);
+                    tracing::warn!(错误 = %e, "写入验证进程失败,重启后重试");
+                    last_error = e.to_string();
+                }
+            }
+        }
+
+        Err(ValidationError::WorkerCrashed(format!(
+            "无法写入验证进程: {}",
+            last_error
+        )))
+    }
+
+    /// 启动worker进程与输出读取任务
+    ///
+    /// worker放在独立的进程组中,浏览器进程随之一起被终止。
+    fn spawn(&self) -> Result<WorkerProcess, ValidationError> {
+        let mut command = Command::new("node");
+        command
+            .arg(&self.script_path)
+            .env("VALIDATION_MAX_CONTEXTS", self.max_contexts.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let mut child = command.spawn().map_err(|e| {
+            tracing::error!(
+                错误 = %e,
+                脚本路径 = %self.script_path,
+                "启动验证进程失败"
+            );
+            ValidationError::PlaywrightFailed(format!("Failed to execute: {}", e))
+        })?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let previous = self.spawned.fetch_add(1, Ordering::Relaxed);
+        if previous == 0 {
+            tracing::info!(
+                进程ID = ?child.id(),
+                脚本路径 = %self.script_path,
+                上下文上限 = %self.max_contexts,
+                "验证进程已启动"
+            );
+        } else {
+            tracing::warn!(
+                进程ID = ?child.id(),
+                重启次数 = %previous,
+                "验证进程已重启"
+            );
+        }
+
+        let handle = Arc::new(WorkerHandle {
+            generation: previous + 1,
+            pid: child.id(),
+            child: std::sync::Mutex::new(child),
+            pending: std::sync::Mutex::default(),
+            alive: AtomicBool::new(true),
+            retiring: AtomicBool::new(false),
+        });
+
+        let mut workers = self.workers.lock().unwrap();
+        workers.retain(|worker| worker.strong_count() > 0);
+        workers.push(Arc::downgrade(&handle));
+        drop(workers);
+
+        tokio::spawn(read_responses(stdout, handle.clone()));
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::warn!(输出 = %line, "验证进程错误输出");
+            }
+        });
+
+        Ok(WorkerProcess { handle, stdin })
+    }
+}
+
+impl WorkerHandle {
+    /// 是否可以接收新请求
+    fn accepts_requests(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+            && !self.retiring.load(Ordering::SeqCst)
+            && matches!(self.child.lock().unwrap().try_wait(), Ok(None))
+    }
+
+    /// 登记等待中的请求
+    ///
+    /// 与读取任务退出时的清理、退役时的空闲检查持有同一把锁: 进程已退出或退役时返回false,
+    /// 避免请求登记在已清空的表中永远等不到结果。
+    fn register(&self, id: u64, reply: oneshot::Sender<PlaywrightValidationResult>) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        if !self.alive.load(Ordering::SeqCst) || self.retiring.load(Ordering::SeqCst) {
+            return false;
+        }
+        pending.insert(id, reply);
+        true
+    }
+
+    /// 放弃超时的请求,进程开始退役
+    fn abandon(&self, id: u64) {
+        self.pending.lock().unwrap().remove(&id);
+        self.retiring.store(true, Ordering::SeqCst);
+        self.kill_if_idle();
+    }
+
+    /// 退役且没有进行中的请求时杀掉进程组
+    fn kill_if_idle(&self) {
+        if !self.retiring.load(Ordering::SeqCst) || !self.pending.lock().unwrap().is_empty() {
+            return;
+        }
+        if self.kill() {
+            tracing::warn!(
+                进程ID = ?self.pid,
+                进程序号 = %self.generation,
+                "退役的验证进程已终止"
+            );
+        }
+    }
+
+    /// 杀掉进程组,进程已退出时什么都不做
+    fn kill(&self) -> bool {
+        // 先标记,下一个请求直接重启而不是写入正在退出的进程
+        self.alive.store(false, Ordering::SeqCst);
+        let mut child = self.child.lock().unwrap();
+        match (child.try_wait(), self.pid) {
+            (Ok(None), Some(pid)) => {
+                kill_process_group(pid);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 杀掉进程组 (worker及其启动的浏览器)
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // 进程组ID即worker的pid (spawn时 process_group(0))
+    // SAFETY: killpg只发送信号,不涉及内存
+    let result = unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+    if result != 0 {
+        tracing::warn!(
+            进程ID = %pid,
+            错误 = %std::io::Error::last_os_error(),
+            "终止验证进程组失败"
+        );
+    }
+}
+
+/// 杀掉进程树 (worker及其启动的浏览器)
+#[cfg(windows)]
+fn kill_process_group(pid: u32) {
+    let result = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .output();
+    if let Err(e) = result {
+        tracing::warn!(进程ID = %pid, 错误 = %e, "终止验证进程树失败");
+    }
+}
+
+/// 读取worker响应并按id交给等待中的请求
+///
+/// stdout关闭即视为进程退出: 丢弃所有等待中的请求,调用方收到 `WorkerCrashed`。
+async fn read_responses(stdout: tokio::process::ChildStdout, handle: Arc<WorkerHandle>) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        match serde_json::from_str::<WorkerResponse>(&line) {
+            Ok(WorkerResponse {
+                id: Some(id),
+                result,
+            }) => {
+                let reply = handle.pending.lock().unwrap().remove(&id);
+                match reply {
+                    Some(reply) => {
+                        let _ = reply.send(result);
+                        handle.kill_if_idle();
+                    }
+                    None => tracing::warn!(请求ID = %id, "收到未知或已超时请求的验证结果"),
+                }
+            }
+            Ok(WorkerResponse { id: None, result }) => {
+                tracing::error!(错误 = ?result.error, "验证进程无法解析请求");
+            }
+            // 输出内容可能包含用户信息,只记录长度
+            Err(e) => tracing::warn!(错误 = %e, 长度 = %line.len(), "验证进程输出了非协议内容"),
+        }
+    }
+
+    let orphaned = {
+        let mut pending = handle.pending.lock().unwrap();
+        handle.alive.store(false, Ordering::SeqCst);
+        pending.drain().count()
+    };
+    if orphaned > 0 {
+        tracing::error!(等待中请求 = %orphaned, "验证进程意外退出");
+    } else {
+        tracing::info!(进程序号 = %handle.generation, "验证进程已退出");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// 模拟worker: 按SUB返回uid,DELAY毫秒后响应,CRASH时直接退出,
+    /// HANG时启动一个常驻子进程 (模拟浏览器) 并把其pid写入HANG指定的文件,永不响应
+    const FAKE_WORKER: &str = r#"
+const readline = require('readline');
+const { spawn } = require('child_process');
+const fs = require('fs');
+const rl = readline.createInterface({ input: process.stdin });
+rl.on('line', (line) => {
+  const req = JSON.parse(line);
+  if (req.cookies.CRASH) process.exit(3);
+  if (req.cookies.HANG) {
+    const browser = spawn(process.execPath, ['-e', 'setInterval(() => {}, 1000)'], { stdio: 'ignore' });
+    fs.writeFileSync(req.cookies.HANG, String(browser.pid));
+    return;
+  }
+  setTimeout(() => {
+    process.stdout.write(JSON.stringify({
+      id: req.id,
+      valid: true,
+      uid: req.cookies.SUB,
+      screen_name: process.argv.join(' '),
+    }) + '\n');
+  }, Number(req.cookies.DELAY || 0));
+});
+"#;
+
+    fn fake_worker() -> (PathBuf, ValidationWorker) {
+        let dir = std::env::temp_dir().join(format!("weibo-worker-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("fake-worker.js");
+        std::fs::write(&script, FAKE_WORKER).unwrap();
+        let worker = ValidationWorker::new(script.to_string_lossy().to_string());
+        (dir, worker)
+    }
+
+    fn cookies(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_concurrent_requests_matched_by_id() {
+        let (dir, worker) = fake_worker();
+
+        let slow = cookies(&[("SUB", "slow"), ("DELAY", "200")]);
+        let fast = cookies(&[("SUB", "fast")]);
+        let (slow, fast) = tokio::join!(worker.validate(&slow), worker.validate(&fast));
+
+        assert_eq!(slow.unwrap().uid.as_deref(), Some("slow"));
+        assert_eq!(fast.unwrap().uid.as_deref(), Some("fast"));
+        assert_eq!(worker.spawned.load(Ordering::Relaxed), 1, "复用同一个进程");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_cookies_are_not_passed_in_argv() {
+        let (dir, worker) = fake_worker();
+        let result = worker
+            .validate(&cookies(&[("SUB", "secret-sub-value")]))
+            .await
+            .unwrap();
+        let argv = result.screen_name.unwrap();
+        assert!(argv.contains("fake-worker.js"));
+        assert!(!argv.contains("secret-sub-value"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_crash_fails_pending_requests_and_respawns() {
+        let (dir, worker) = fake_worker();
+
+        let pending = cookies(&[("SUB", "pending"), ("DELAY", "5000")]);
+        let crash = cookies(&[("CRASH", "1")]);
+        let (pending, crash) = tokio::join!(worker.validate(&pending), async {
+            // 确保pending请求先发出
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            worker.validate(&crash).await
+        });
+        assert!(matches!(pending, Err(ValidationError::WorkerCrashed(_))));
+        assert!(matches!(crash, Err(ValidationError::WorkerCrashed(_))));
+
+        let result = worker
+            .validate(&cookies(&[("SUB", "after")]))
+            .await
+            .unwrap();
+        assert_eq!(result.uid.as_deref(), Some("after"));
+        assert_eq!(worker.spawned.load(Ordering::Relaxed), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// 进程是否仍存在 (已退出但未回收的僵尸进程视为不存在)
+    #[cfg(unix)]
+    fn process_exists(pid: i32) -> bool {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid));
+        match stat {
+            Ok(stat) => !stat.contains(") Z "),
+            // 没有/proc的系统
+            Err(_) => unsafe { libc::kill(pid, 0) == 0 },
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_timeout_kills_process_group() {
+        let (dir, worker) = fake_worker();
+        let worker = worker.with_timeout(std::time::Duration::from_millis(500));
+        let pid_file = dir.join("browser.pid");
+
+        let result = worker
+            .validate(&cookies(&[("HANG", pid_file.to_str().unwrap())]))
+            .await;
+        assert!(matches!(result, Err(ValidationError::Timeout { .. })));
+
+        // 模拟的浏览器进程随worker一起被杀掉
+        let browser_pid: i32 = std::fs::read_to_string(&pid_file).unwrap().parse().unwrap();
+        let mut alive = true;
+        for _ in 0..50 {
+            alive = process_exists(browser_pid);
+            if !alive {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!alive, "浏览器子进程应被终止");
+
+        // 下一个请求使用重启后的进程
+        let result = worker
+            .validate(&cookies(&[("SUB", "after")]))
+            .await
+            .unwrap();
+        assert_eq!(result.uid.as_deref(), Some("after"));
+        assert_eq!(worker.spawned.load(Ordering::Relaxed), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_timeout_only_fails_timed_out_request() {
+        let (dir, worker) = fake_worker();
+        let worker = worker.with_timeout(std::time::Duration::from_secs(2));
+        let pid_file = dir.join("browser.pid");
+
+        let hang = cookies(&[("HANG", pid_file.to_str().unwrap())]);
+        let slow = cookies(&[("SUB", "slow"), ("DELAY", "1800")]);
+        let (hang, slow) = tokio::join!(worker.validate(&hang), async {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            // 在超时之后、慢请求完成之前发出的请求由新进程处理
+            let late = async {
+                tokio::time::sleep(std::time::Duration::from_millis(1600)).await;
+                worker.validate(&cookies(&[("SUB", "late")])).await
+            };
+            tokio::join!(worker.validate(&slow), late)
+        });
+        let (slow, late) = slow;
+
+        assert!(matches!(hang, Err(ValidationError::Timeout { seconds: 2 })));
+        assert_eq!(
+            slow.unwrap().uid.as_deref(),
+            Some("slow"),
+            "同一进程中的其他验证照常完成"
+        );
+        assert_eq!(late.unwrap().uid.as_deref(), Some("late"));
+        assert_eq!(worker.spawned.load(Ordering::Relaxed), 2);
+
+        // 进行中的验证结束后,退役进程连同卡死的浏览器一起被杀掉
+        let browser_pid: i32 = std::fs::read_to_string(&pid_file).unwrap().parse().unwrap();
+        let mut alive = true;
+        for _ in 0..50 {
+            alive = process_exists(browser_pid);
+            if !alive {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!alive, "浏览器子进程应被终止");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_shutdown_stops_worker() {
+        let (dir, worker) = fake_worker();
+        worker
+            .validate(&cookies(&[("SUB", "before")]))
+            .await
+            .unwrap();
+
+        worker.shutdown();
+        assert!(worker.workers.lock().unwrap().is_empty());
+        // 关闭后再次验证会重新启动进程
+        let result = worker
+            .validate(&cookies(&[("SUB", "after")]))
+            .await
+            .unwrap();
+        assert_eq!(result.uid.as_deref(), Some("after"));
+        assert_eq!(worker.spawned.load(Ordering::Relaxed), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要Node.js
+    async fn test_missing_script_reports_error() {
+        let worker = ValidationWorker::new("/nonexistent/validate-cookies.js".to_string());
+        let result = worker.validate(&cookies(&[("SUB", "x")])).await;
+        assert!(matches!(result, Err(ValidationError::WorkerCrashed(_))));
+    }
+}
@@ -1,10 +1,12 @@
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio_tungstenite::{connect_async, tungstenite::Message, WebSocketStream, MaybeTlsStream};
 use tokio::net::TcpStream;
 use futures_util::{StreamExt, SinkExt};
 
 use crate::models::{ApiError, LoginSession};
+use crate::services::rate_limiter::{RateLimiter, RateOperation};
 
 /// WebSocket Stream 类型别名
 pub type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
@@ -22,6 +24,9 @@ pub type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 pub struct WeiboApiClient {
     #[allow(dead_code)]
     server_url: String,
+
+    /// 全局限速器,生成二维码前取得令牌
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 /// WebSocket事件
@@ -71,7 +76,16 @@ impl WeiboApiClient {
             "微博API客户端已初始化 (WebSocket模式)"
         );
 
-        Self { server_url }
+        Self {
+            server_url,
+            rate_limiter: None,
+        }
+    }
+
+    /// 生成二维码前经过全局限速器
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
     }
 
     /// 生成二维码
@@ -87,9 +101,14 @@ impl WeiboApiClient {
     /// - `ApiError::NetworkFailed`: WebSocket连接失败
     /// - `ApiError::QrCodeGenerationFailed`: 二维码生成失败
     /// - `ApiError::JsonParseFailed`: 响应解析失败
+    /// - `ApiError::RateLimitExceeded`: 生成过于频繁,已被本地限速
     pub async fn generate_qrcode(&self) -> Result<(LoginSession, String, WsStream), ApiError> {
         tracing::info!("通过WebSocket生成二维码");
 
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(RateOperation::QrCode).await?;
+        }
+
         let ws_url = "ws://localhost:9223";
 
         // 连接WebSocket (带重试)
@@ -0,0 +1,724 @@
+//! 已登录的微博HTTP客户端
+//!
+//! 下游工具拿到账户后都要做同一件事: 把 `CookiesData` 变成能用的微博请求。
+//! [`WeiboHttpClient`] 统一处理:
+//! - cookie jar: cookies只写入 weibo.com (含子域名),不会发往其他站点
+//! - 与Playwright一致的User-Agent,不跟随重定向
+//! - 响应外层 (`ok` / `msg` / `data`,以及旧版接口的 `error_code`) 的解析,
+//!   登录失效、频率限制、封禁等情况映射为 [`WeiboHttpError`]
+//!
+//! 只提供只读接口的便捷方法,其余接口可用 [`WeiboHttpClient::get_data`] 直接请求。
+//! HTTP验证器 (`HttpValidator`) 与保活刷新 (`RefreshService`) 同样基于此客户端,
+//! 响应的解析与错误分类只在这里实现一次。
+//!
+//! # 示例
+//!
+//! ```no_run
+//! use weibo_login::services::{RedisService, WeiboHttpClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let redis = RedisService::new("redis://localhost:6379")?;
+//! let account = redis.query_cookies("1234567890").await?;
+//!
+//! let client = WeiboHttpClient::new(&account)?;
+//! let profile = client.my_profile().await?;
+//! println!("{} 有 {:?} 个粉丝", profile.screen_name, profile.followers_count);
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::cookie::Jar;
+use reqwest::header::{LOCATION, RETRY_AFTER, SET_COOKIE};
+use reqwest::redirect::Policy;
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::{CookiesData, ValidationOutcome, WeiboHttpError};
+
+/// 微博站点地址
+pub(crate) const DEFAULT_BASE_URL: &str = "https://weibo.com";
+
+/// 登录状态接口: 返回是否登录及当前uid
+pub(crate) const CONFIG_PATH: &str = "/ajax/config";
+
+/// 个人资料接口
+pub(crate) const PROFILE_PATH: &str = "/ajax/profile/info";
+
+/// 与Playwright验证脚本一致的User-Agent
+pub(crate) const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+
+/// 默认请求超时
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 用户微博列表接口
+const STATUSES_PATH: &str = "/ajax/statuses/mymblog";
+
+/// cookies所属的根域名: 保存的cookies来自 weibo.com 的登录,不写入其他站点
+const COOKIE_DOMAIN: &str = "weibo.com";
+
+/// 表示登录失效的错误码: `ok: -100` 及旧版接口的令牌失效
+const LOGIN_EXPIRED_CODES: [i64; 4] = [-100, 21301, 21327, 21332];
+
+/// 表示频率限制的错误码 (IP、用户、单接口)
+const RATE_LIMITED_CODES: [i64; 3] = [10022, 10023, 10024];
+
+/// 微博ajax接口的响应外层
+#[derive(Debug, Deserialize)]
+struct WeiboEnvelope {
+    #[serde(default)]
+    ok: Value,
+    #[serde(default)]
+    msg: Option<String>,
+    #[serde(default)]
+    data: Value,
+    /// 旧版接口的错误码 (如 10023 频率限制、21327 登录过期)
+    #[serde(default)]
+    error_code: Option<i64>,
+    /// 旧版接口的错误说明
+    #[serde(default)]
+    error: Option<String>,
+    /// 未登录时附带的跳转地址
+    #[serde(default)]
+    url: Option<String>,
+}
+
+impl WeiboEnvelope {
+    fn is_ok(&self) -> bool {
+        self.ok.as_i64() == Some(1) || self.ok.as_str() == Some("1")
+    }
+}
+
+/// uid可能是字符串或数字
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// 登录状态 (`/ajax/config`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoginStatus {
+    /// 当前登录的微博用户ID
+    pub uid: String,
+}
+
+/// 用户资料 (`/ajax/profile/info`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileInfo {
+    pub uid: String,
+    pub screen_name: String,
+    pub description: Option<String>,
+    pub avatar_url: Option<String>,
+    pub followers_count: Option<u64>,
+    pub friends_count: Option<u64>,
+    pub statuses_count: Option<u64>,
+    /// 是否认证用户
+    pub verified: bool,
+}
+
+impl ProfileInfo {
+    /// 从接口返回的 `data.user` 解析
+    fn from_user(user: &Value) -> Result<Self, WeiboHttpError> {
+        let uid = user
+            .get("idstr")
+            .or_else(|| user.get("id"))
+            .and_then(value_to_string)
+            .ok_or_else(|| WeiboHttpError::InvalidResponse("用户资料缺少用户ID".to_string()))?;
+        let text = |key: &str| {
+            user.get(key)
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        // 粉丝数过万时接口返回 "1.2万" 之类的字符串,只解析精确数字
+        let count = |key: &str| {
+            user.get(key).and_then(|value| match value {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => s.parse().ok(),
+                _ => None,
+            })
+        };
+
+        Ok(Self {
+            screen_name: text("screen_name").unwrap_or_default(),
+            description: text("description"),
+            avatar_url: text("avatar_hd").or_else(|| text("profile_image_url")),
+            followers_count: count("followers_count"),
+            friends_count: count("friends_count"),
+            statuses_count: count("statuses_count"),
+            verified: user.get("verified").and_then(Value::as_bool) == Some(true),
+            uid,
+        })
+    }
+}
+
+/// 已登录的微博HTTP客户端
+///
+/// 每个客户端只携带一个账户的cookies,不同账户互不影响。
+/// 客户端只读不写: 服务端轮换的cookies不会写回Redis (保活见 `RefreshService`)。
+pub struct WeiboHttpClient {
+    uid: String,
+    base_url: Url,
+    timeout: Duration,
+    client: Client,
+}
+
+impl WeiboHttpClient {
+    /// 用已保存的账户创建访问 weibo.com 的客户端
+    ///
+    /// # 错误
+    /// 账户缺少必需的cookie (SUB、SUBP) 时返回 `WeiboHttpError::InvalidRequest`
+    pub fn new(account: &CookiesData) -> Result<Self, WeiboHttpError> {
+        Self::with_site(account, DEFAULT_BASE_URL, DEFAULT_TIMEOUT)
+    }
+
+    /// 指定站点地址与单个请求的超时时间,用于测试时指向本地服务器
+    ///
+    /// 站点不是 weibo.com 时,cookies只写入该站点的主机名。
+    ///
+    /// # 错误
+    /// 地址无法解析或账户缺少必需的cookie时返回 `WeiboHttpError::InvalidRequest`
+    pub fn with_site(
+        account: &CookiesData,
+        base_url: &str,
+        timeout: Duration,
+    ) -> Result<Self, WeiboHttpError> {
+        account
+            .validate()
+            .map_err(|e| WeiboHttpError::InvalidRequest(e.to_string()))?;
+        let base_url = Url::parse(base_url)
+            .map_err(|e| WeiboHttpError::InvalidRequest(format!("无效的站点地址: {}", e)))?;
+
+        Self::for_cookies(&account.uid, &account.cookies, base_url, timeout)
+    }
+
+    /// 用一组尚未确认归属的cookies创建客户端 (验证器使用,不检查必需的cookie)
+    ///
+    /// `uid` 只用于日志与 [`my_profile`](Self::my_profile),未知时为空。
+    pub(crate) fn for_cookies(
+        uid: &str,
+        cookies: &HashMap<String, String>,
+        base_url: Url,
+        timeout: Duration,
+    ) -> Result<Self, WeiboHttpError> {
+        let client = Client::builder()
+            .cookie_provider(Arc::new(Self::cookie_jar(cookies, &base_url)))
+            .user_agent(USER_AGENT)
+            .redirect(Policy::none())
+            .timeout(timeout)
+            .build()
+            .map_err(|e| WeiboHttpError::RequestFailed(format!("无法创建HTTP客户端: {}", e)))?;
+
+        tracing::debug!(
+            用户ID = %uid,
+            站点 = %base_url,
+            cookies数量 = %cookies.len(),
+            "微博HTTP客户端已创建"
+        );
+
+        Ok(Self {
+            uid: uid.to_string(),
+            base_url,
+            timeout,
+            client,
+        })
+    }
+
+    /// 客户端所属账户的UID
+    pub fn uid(&self) -> &str {
+        &self.uid
+    }
+
+    /// 把cookies写入 weibo.com,子域名 (如 passport.weibo.com) 同样携带
+    ///
+    /// 账户只保存了cookie的名称和值,不知道其他站点 (weibo.cn、sina.com.cn) 是否认可,
+    /// 因此不写入其他根域名。
+    fn cookie_jar(cookies: &HashMap<String, String>, base_url: &Url) -> Jar {
+        let jar = Jar::default();
+        let host = base_url.host_str().unwrap_or_default();
+        let is_weibo = host == COOKIE_DOMAIN || host.ends_with(&format!(".{}", COOKIE_DOMAIN));
+
+        for (name, value) in cookies {
+            if is_weibo {
+                let url =
+                    Url::parse(&format!("https://{}/", COOKIE_DOMAIN)).expect("根域名地址合法");
+                jar.add_cookie_str(
+                    &format!("{}={}; Domain=.{}; Path=/", name, value, COOKIE_DOMAIN),
+                    &url,
+                );
+            } else {
+                jar.add_cookie_str(&format!("{}={}; Path=/", name, value), base_url);
+            }
+        }
+        jar
+    }
+
+    /// 请求接口并返回响应外层中的 `data`
+    ///
+    /// 重定向与HTTP错误状态只按状态码和 `Location` 分类: 错误页、维护页的HTML中
+    /// 常有登录链接,不能据此判断登录失效。
+    ///
+    /// # 错误
+    /// - `WeiboHttpError::LoginExpired`: 被重定向到登录页、HTTP 401或返回未登录
+    /// - `WeiboHttpError::RateLimited`: HTTP 429或频率限制错误码
+    /// - `WeiboHttpError::AccountRestricted`: 账户被封禁、锁定或需要验证码
+    /// - `WeiboHttpError::Api`: 接口返回其他错误
+    /// - `WeiboHttpError::HttpStatus`: 其他HTTP错误状态
+    /// - `WeiboHttpError::InvalidResponse`: 返回的不是JSON (如HTML页面)
+    /// - 其余为网络与超时错误
+    pub async fn get_data(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Value, WeiboHttpError> {
+        self.get(path, query).await.map(|(data, _)| data)
+    }
+
+    /// 请求接口,返回 `data` 与响应中的 `Set-Cookie` 原文
+    async fn get(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<(Value, Vec<String>), WeiboHttpError> {
+        let url = self
+            .base_url
+            .join(path)
+            .map_err(|e| WeiboHttpError::InvalidRequest(format!("无效的接口地址: {}", e)))?;
+
+        let response = self
+            .client
+            .get(url)
+            .query(query)
+            .header("Accept", "application/json, text/plain, */*")
+            .header("Referer", self.base_url.as_str())
+            .send()
+            .await
+            .map_err(|e| self.request_error(e))?;
+
+        let status = response.status();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let location = header(LOCATION).unwrap_or_default();
+        let retry_after = header(RETRY_AFTER).and_then(|s| s.trim().parse::<u64>().ok());
+        let set_cookies: Vec<String> = response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .map(str::to_string)
+            .collect();
+
+        // 重定向目标说明原因: 登录页 (过期)、安全验证页 (锁定)、验证码页
+        if status.is_redirection() || status == StatusCode::UNAUTHORIZED {
+            return Err(Self::failure(
+                Some(status.as_u16()),
+                None,
+                &location,
+                format!("{} 被重定向: {}", path, location),
+                retry_after,
+            ));
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(WeiboHttpError::RateLimited {
+                retry_after,
+                message: format!("{} 请求过于频繁 (HTTP 429)", path),
+            });
+        }
+        if !status.is_success() {
+            return Err(WeiboHttpError::HttpStatus {
+                status: status.as_u16(),
+                path: path.to_string(),
+            });
+        }
+
+        let body = response.text().await.map_err(|e| self.request_error(e))?;
+        let envelope: WeiboEnvelope = serde_json::from_str(&body)
+            .map_err(|_| WeiboHttpError::InvalidResponse(format!("{} 返回的不是JSON", path)))?;
+
+        // 新版ajax接口以 ok=1 表示成功;旧版接口出错时才带 error_code
+        let succeeded =
+            envelope.is_ok() || (envelope.ok.is_null() && envelope.error_code.is_none());
+        if !succeeded {
+            let code = envelope
+                .error_code
+                .or_else(|| envelope.ok.as_i64().filter(|code| *code != 0));
+            let message = envelope
+                .msg
+                .or(envelope.error)
+                .unwrap_or_else(|| format!("{} 返回错误", path));
+            tracing::warn!(用户ID = %self.uid, 接口 = %path, 错误码 = ?code, 错误 = %message, "微博接口返回错误");
+            return Err(Self::failure(
+                None,
+                code,
+                envelope.url.as_deref().unwrap_or_default(),
+                message,
+                retry_after,
+            ));
+        }
+
+        Ok((envelope.data, set_cookies))
+    }
+
+    /// 当前登录状态,确认cookies仍然有效
+    ///
+    /// # 错误
+    /// 未登录时返回 `WeiboHttpError::LoginExpired`,其余同 [`get_data`](Self::get_data)
+    /// (无法识别原因的接口错误仍为 `Api`,不视为登录失效)
+    pub async fn login_status(&self) -> Result<LoginStatus, WeiboHttpError> {
+        self.check_login().await.map(|(status, _)| status)
+    }
+
+    /// 登录状态,以及服务端随响应下发的 `Set-Cookie` 原文 (保活刷新据此收下轮换的cookies)
+    pub(crate) async fn check_login(&self) -> Result<(LoginStatus, Vec<String>), WeiboHttpError> {
+        let (data, set_cookies) = self.get(CONFIG_PATH, &[]).await?;
+        if data.get("login").and_then(Value::as_bool) != Some(true) {
+            return Err(WeiboHttpError::LoginExpired {
+                message: "未登录".to_string(),
+            });
+        }
+        let uid = data
+            .get("uid")
+            .and_then(value_to_string)
+            .ok_or_else(|| WeiboHttpError::InvalidResponse("登录状态缺少用户ID".to_string()))?;
+        Ok((LoginStatus { uid }, set_cookies))
+    }
+
+    /// 用户资料
+    pub async fn profile_info(&self, uid: &str) -> Result<ProfileInfo, WeiboHttpError> {
+        let data = self.get_data(PROFILE_PATH, &[("uid", uid)]).await?;
+        ProfileInfo::from_user(&data["user"])
+    }
+
+    /// 本账户的资料
+    pub async fn my_profile(&self) -> Result<ProfileInfo, WeiboHttpError> {
+        self.profile_info(&self.uid).await
+    }
+
+    /// 用户发布的微博 (按页,从1开始),返回接口原始的微博对象
+    pub async fn user_statuses(&self, uid: &str, page: u32) -> Result<Vec<Value>, WeiboHttpError> {
+        let page = page.max(1).to_string();
+        let data = self
+            .get_data(
+                STATUSES_PATH,
+                &[("uid", uid), ("page", page.as_str()), ("feature", "0")],
+            )
+            .await?;
+        match data.get("list") {
+            Some(Value::Array(list)) => Ok(list.clone()),
+            _ => Err(WeiboHttpError::InvalidResponse(
+                "微博列表缺少list字段".to_string(),
+            )),
+        }
+    }
+
+    /// 区分超时与其他网络错误
+    fn request_error(&self, error: reqwest::Error) -> WeiboHttpError {
+        if error.is_timeout() {
+            WeiboHttpError::timeout(self.timeout)
+        } else {
+            WeiboHttpError::RequestFailed(error.to_string())
+        }
+    }
+
+    /// 按状态码、错误码、重定向目标与消息分类失败
+    ///
+    /// 消息中的具体原因优先 (如 `ok: -100` 但提示"已被封禁"),
+    /// 无法识别时再看错误码,仍无法识别的为 `Api`。
+    fn failure(
+        status: Option<u16>,
+        code: Option<i64>,
+        hint: &str,
+        message: String,
+        retry_after: Option<u64>,
+    ) -> WeiboHttpError {
+        let outcome = match ValidationOutcome::classify(status, Some(hint), &message) {
+            ValidationOutcome::Unknown => match code {
+                Some(code) if LOGIN_EXPIRED_CODES.contains(&code) => ValidationOutcome::Expired,
+                Some(code) if RATE_LIMITED_CODES.contains(&code) => ValidationOutcome::RateLimited,
+                _ => ValidationOutcome::Unknown,
+            },
+            outcome => outcome,
+        };
+
+        match outcome {
+            ValidationOutcome::Expired => WeiboHttpError::LoginExpired { message },
+            ValidationOutcome::RateLimited => WeiboHttpError::RateLimited {
+                retry_after,
+                message,
+            },
+            ValidationOutcome::Banned
+            | ValidationOutcome::Locked
+            | ValidationOutcome::CaptchaRequired => {
+                WeiboHttpError::AccountRestricted { outcome, message }
+            }
+            _ => WeiboHttpError::Api { code, message },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mock_http::{MockResponse, MockServer};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn account() -> CookiesData {
+        CookiesData::new(
+            "123".to_string(),
+            HashMap::from([
+                ("SUB".to_string(), "sub_value".to_string()),
+                ("SUBP".to_string(), "subp_value".to_string()),
+            ]),
+        )
+    }
+
+    fn client(server: &MockServer) -> WeiboHttpClient {
+        WeiboHttpClient::with_site(&account(), &server.url(), Duration::from_secs(5)).unwrap()
+    }
+
+    #[test]
+    fn test_requires_login_cookies() {
+        let account = CookiesData::new(
+            "123".to_string(),
+            HashMap::from([("SUB".to_string(), "sub_value".to_string())]),
+        );
+        assert!(matches!(
+            WeiboHttpClient::new(&account),
+            Err(WeiboHttpError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_cookie_jar_scoped_to_weibo_com() {
+        use reqwest::cookie::CookieStore;
+
+        let jar =
+            WeiboHttpClient::cookie_jar(&account().cookies, &Url::parse(DEFAULT_BASE_URL).unwrap());
+        let header = |url: &str| {
+            jar.cookies(&Url::parse(url).unwrap())
+                .map(|value| value.to_str().unwrap().to_string())
+        };
+
+        for url in [
+            "https://weibo.com/ajax/config",
+            "https://passport.weibo.com/",
+        ] {
+            let cookie = header(url).unwrap_or_default();
+            assert!(cookie.contains("SUB=sub_value"), "{}", url);
+            assert!(cookie.contains("SUBP=subp_value"), "{}", url);
+        }
+        for url in [
+            "https://m.weibo.cn/api/config",
+            "https://login.sina.com.cn/",
+            "https://example.com/",
+        ] {
+            assert_eq!(header(url), None, "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profile_info() {
+        let server = MockServer::start(vec![(
+            PROFILE_PATH,
+            MockResponse::json(
+                200,
+                json!({"ok": 1, "data": {"user": {
+                    "id": 123,
+                    "idstr": "123",
+                    "screen_name": "测试账户",
+                    "description": "",
+                    "avatar_hd": "https://tvax1.sinaimg.cn/large/avatar.jpg",
+                    "followers_count": "1.2万",
+                    "friends_count": 88,
+                    "statuses_count": 1024,
+                    "verified": true
+                }}}),
+            ),
+        )])
+        .await;
+
+        let profile = client(&server).my_profile().await.unwrap();
+        assert_eq!(profile.uid, "123");
+        assert_eq!(profile.screen_name, "测试账户");
+        assert_eq!(profile.description, None);
+        assert_eq!(
+            profile.avatar_url.as_deref(),
+            Some("https://tvax1.sinaimg.cn/large/avatar.jpg")
+        );
+        assert_eq!(profile.followers_count, None);
+        assert_eq!(profile.friends_count, Some(88));
+        assert_eq!(profile.statuses_count, Some(1024));
+        assert!(profile.verified);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/ajax/profile/info?uid=123");
+        let cookie = &requests[0].headers["cookie"];
+        assert!(cookie.contains("SUB=sub_value"));
+        assert!(cookie.contains("SUBP=subp_value"));
+        assert_eq!(requests[0].headers["user-agent"], USER_AGENT);
+    }
+
+    #[tokio::test]
+    async fn test_login_status_and_statuses() {
+        let server = MockServer::start(vec![
+            (
+                CONFIG_PATH,
+                MockResponse::json(200, json!({"ok": 1, "data": {"login": true, "uid": 123}})),
+            ),
+            (
+                STATUSES_PATH,
+                MockResponse::json(
+                    200,
+                    json!({"ok": 1, "data": {"list": [{"idstr": "1"}, {"idstr": "2"}], "total": 2}}),
+                ),
+            ),
+        ])
+        .await;
+        let client = client(&server);
+
+        assert_eq!(
+            client.login_status().await.unwrap(),
+            LoginStatus {
+                uid: "123".to_string()
+            }
+        );
+        let statuses = client.user_statuses("123", 0).await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(
+            server.requests()[1].path,
+            "/ajax/statuses/mymblog?uid=123&page=1&feature=0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_mapping() {
+        type Expected = fn(&WeiboHttpError) -> bool;
+        let cases: Vec<(MockResponse, Expected)> = vec![
+            (
+                MockResponse::text(302, "")
+                    .with_header("Location", "https://passport.weibo.com/sso/signin"),
+                |e| matches!(e, WeiboHttpError::LoginExpired { .. }),
+            ),
+            (
+                MockResponse::json(
+                    200,
+                    json!({"ok": -100, "url": "https://weibo.com/login.php"}),
+                ),
+                |e| matches!(e, WeiboHttpError::LoginExpired { .. }),
+            ),
+            (
+                MockResponse::json(200, json!({"error_code": 21327, "error": "expired_token"})),
+                |e| matches!(e, WeiboHttpError::LoginExpired { .. }),
+            ),
+            (
+                MockResponse::text(429, "").with_header("Retry-After", "30"),
+                |e| {
+                    matches!(
+                        e,
+                        WeiboHttpError::RateLimited {
+                            retry_after: Some(30),
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                MockResponse::json(
+                    200,
+                    json!({"error_code": 10023, "error": "User requests out of limit!"}),
+                ),
+                |e| {
+                    matches!(
+                        e,
+                        WeiboHttpError::RateLimited {
+                            retry_after: None,
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                MockResponse::json(200, json!({"ok": -100, "msg": "该账号已被封禁"})),
+                |e| {
+                    matches!(
+                        e,
+                        WeiboHttpError::AccountRestricted {
+                            outcome: ValidationOutcome::Banned,
+                            ..
+                        }
+                    )
+                },
+            ),
+            (
+                MockResponse::json(200, json!({"ok": 0, "msg": "用户不存在"})),
+                |e| matches!(e, WeiboHttpError::Api { code: None, .. }),
+            ),
+            (
+                MockResponse::json(200, json!({"ok": 0, "msg": "系统繁忙"})),
+                |e| matches!(e, WeiboHttpError::Api { .. }),
+            ),
+            (MockResponse::text(500, "Internal Server Error"), |e| {
+                matches!(e, WeiboHttpError::HttpStatus { status: 500, .. })
+            }),
+            (MockResponse::text(200, "<html>维护中</html>"), |e| {
+                matches!(e, WeiboHttpError::InvalidResponse(_))
+            }),
+            // 错误页中的登录链接不代表登录失效
+            (
+                MockResponse::text(
+                    500,
+                    r#"<html><a href="https://passport.weibo.com/sso/signin">login</a></html>"#,
+                ),
+                |e| matches!(e, WeiboHttpError::HttpStatus { status: 500, .. }),
+            ),
+            (
+                MockResponse::text(200, r#"<html><a href="/login.php">登录</a></html>"#),
+                |e| matches!(e, WeiboHttpError::InvalidResponse(_)),
+            ),
+        ];
+
+        for (response, expected) in cases {
+            let server = MockServer::start(vec![(PROFILE_PATH, response)]).await;
+            let err = client(&server).profile_info("123").await.unwrap_err();
+            assert!(expected(&err), "实际: {:?}", err);
+        }
+
+        // 登录状态接口的无法识别错误同样保留为Api,不视为登录失效
+        let server = MockServer::start(vec![(
+            CONFIG_PATH,
+            MockResponse::json(200, json!({"ok": 0, "msg": "系统繁忙"})),
+        )])
+        .await;
+        let err = client(&server).login_status().await.unwrap_err();
+        assert!(matches!(err, WeiboHttpError::Api { .. }), "实际: {:?}", err);
+    }
+
+    #[test]
+    fn test_error_outcome() {
+        let err = WeiboHttpError::RateLimited {
+            retry_after: None,
+            message: "频繁".to_string(),
+        };
+        assert_eq!(err.outcome(), ValidationOutcome::RateLimited);
+        assert_eq!(
+            WeiboHttpError::Timeout { seconds: 5 }.outcome(),
+            ValidationOutcome::Timeout
+        );
+        assert!(matches!(
+            WeiboHttpError::timeout(Duration::from_millis(300)),
+            WeiboHttpError::Timeout { seconds: 1 }
+        ));
+    }
+}
@@ -1,5 +1,12 @@
-use crate::services::{RedisService, SessionManager, ValidationService, WeiboApiClient};
+use crate::models::RedisConfig;
+use crate::services::{
+    AccountWatcher, AuditLog, CookieValidator, LeaseService, LoginValidationPolicy,
+    RateLimitedValidator, RateLimiter, RedisService, RefreshService, RevalidationConfig,
+    RevalidationService, SessionManager, SpoolService, TransferService, ValidationCancellations,
+    ValidatorConfig, WeiboApiClient,
+};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// 应用全局状态
 ///
@@ -7,7 +14,17 @@ use std::sync::Arc;
 /// - redis: 数据持久化
 /// - weibo_api: 微博平台交互 (Playwright自动化)
 /// - validator: Cookies可信度保障
+/// - login_policy: 扫码登录后的验证策略
+/// - validations: 前端可取消的进行中验证
+/// - revalidation: 已保存账户的定期重新验证
+/// - refresh: Cookies保活刷新
+/// - rate_limiter: 面向微博请求的全局限速
 /// - session_manager: 二维码会话生命周期管理
+/// - transfer: Cookies导入导出
+/// - leases: 下游worker的账户租约
+/// - audit: cookies读写审计日志
+/// - watcher: 账户变化监听与账户列表缓存
+/// - spool: Redis不可用时的本地暂存队列 (无法打开时为None)
 pub struct AppState {
     /// Redis服务: 唯一的数据存储入口
     pub redis: Arc<RedisService>,
@@ -15,41 +32,108 @@ pub struct AppState {
     /// 微博API客户端: 唯一的微博平台通信渠道 (Playwright实现)
     pub weibo_api: Arc<WeiboApiClient>,
 
-    /// Cookies验证服务: 唯一的可信度检验机制
-    pub validator: Arc<ValidationService>,
+    /// Cookies验证器: 唯一的可信度检验机制 (Playwright或HTTP,由配置选择)
+    pub validator: Arc<dyn CookieValidator>,
+
+    /// 扫码登录验证策略: 信任服务端、保存前验证或保存后验证
+    pub login_policy: LoginValidationPolicy,
+
+    /// 进行中的验证: 前端取消验证的唯一入口
+    pub validations: Arc<ValidationCancellations>,
+
+    /// 重新验证服务: 已保存账户是否仍然可用的定期检查
+    pub revalidation: Arc<RevalidationService>,
+
+    /// 刷新服务: 让账户保持活跃、收下微博轮换的cookies
+    pub refresh: Arc<RefreshService>,
+
+    /// 全局限速器: 所有面向微博的请求共用的令牌桶
+    pub rate_limiter: Arc<RateLimiter>,
 
     /// 会话管理器: 防止资源泄露的看守者
     pub session_manager: Arc<SessionManager>,
+
+    /// 导入导出服务: 与外部工具交换cookies的唯一通道
+    pub transfer: Arc<TransferService>,
+
+    /// 租约服务: 保证同一账户同一时间只被一个worker使用
+    pub leases: Arc<LeaseService>,
+
+    /// 审计日志: 谁在何时读写了哪个账户
+    pub audit: Arc<AuditLog>,
+
+    /// 账户变化监听器: 外部修改、删除与过期的唯一发现者
+    pub watcher: Arc<AccountWatcher>,
+
+    /// 本地暂存队列: Redis故障期间扫码结果的避难所
+    ///
+    /// 队列目录或密钥无法打开时为None,应用照常运行,只是无法暂存
+    pub spool: Option<Arc<SpoolService>>,
 }
 
 impl AppState {
     /// 初始化应用状态
     ///
-    /// 三个参数,三个核心能力,缺一不可:
-    /// - redis_url: 数据根基
+    /// 六个参数,六个核心能力,缺一不可:
+    /// - redis_config: 数据根基 (连接地址与key命名空间)
     /// - playwright_server_url: Playwright WebSocket server地址
-    /// - playwright_validation_script: 验证工具
+    /// - validator_config: 验证工具 (实现选择、脚本路径、时限与扫码登录验证策略)
+    /// - revalidation_config: 定期重新验证 (间隔、并发数与随机延迟)
+    /// - refresh_interval: 后台保活刷新间隔 (None表示只手动刷新)
+    /// - rate_limiter: 全局限速器 (二维码、验证与刷新共用)
     ///
     /// # 错误处理
-    /// 任何服务初始化失败都将导致整个应用无法启动 - 这是必然,因为不完整的状态等同于无用
+    /// 任何核心服务初始化失败都将导致整个应用无法启动 - 这是必然,因为不完整的状态等同于无用。
+    /// 本地暂存队列是兜底能力,打不开时只记录错误,不阻止启动
     pub fn new(
-        redis_url: &str,
+        redis_config: &RedisConfig,
         playwright_server_url: &str,
-        playwright_validation_script: &str,
+        validator_config: &ValidatorConfig,
+        revalidation_config: &RevalidationConfig,
+        refresh_interval: Option<Duration>,
+        rate_limiter: Arc<RateLimiter>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let redis = Arc::new(RedisService::new(redis_url)?);
-        let weibo_api = Arc::new(WeiboApiClient::new(
-            playwright_server_url.to_string(),
+        let redis = Arc::new(RedisService::from_config(redis_config)?);
+        let weibo_api = Arc::new(
+            WeiboApiClient::new(playwright_server_url.to_string())
+                .with_rate_limiter(rate_limiter.clone()),
+        );
+        let validator: Arc<dyn CookieValidator> = Arc::new(RateLimitedValidator::new(
+            validator_config.build(),
+            rate_limiter.clone(),
         ));
-        let validator = Arc::new(ValidationService::new(
-            playwright_validation_script.to_string(),
+        let validations = Arc::new(ValidationCancellations::new());
+        let revalidation = Arc::new(RevalidationService::new(
+            redis.clone(),
+            validator.clone(),
+            revalidation_config.clone(),
         ));
+        let audit = Arc::new(AuditLog::new(redis.clone()));
+        let refresh = Arc::new(
+            RefreshService::new(redis.clone())
+                .with_interval(refresh_interval)
+                .with_rate_limiter(rate_limiter.clone())
+                .with_audit(audit.clone()),
+        );
         let session_manager = Arc::new(SessionManager::new());
+        let transfer = Arc::new(TransferService::new(redis.clone(), validator.clone()));
+        let leases = Arc::new(LeaseService::new(redis.clone()).with_audit(audit.clone()));
+        let watcher = Arc::new(AccountWatcher::new(redis.clone()));
+        let spool = match SpoolService::open(SpoolService::default_dir(), redis.clone()) {
+            Ok(spool) => Some(Arc::new(spool.with_audit(audit.clone()))),
+            Err(e) => {
+                tracing::error!(错误 = %e, "本地暂存队列无法打开,Redis故障期间的扫码结果将无法暂存");
+                None
+            }
+        };
 
         tracing::info!(
-            redis_url = %redis_url,
+            redis_config = %redis_config.summary_for_logging(),
             playwright_server = %playwright_server_url,
-            playwright_validation = %playwright_validation_script,
+            validator = %validator.name(),
+            playwright_validation = %validator_config.playwright_script,
+            validation_timeout_secs = %validator_config.timeout.as_secs(),
+            qr_login_validation = %validator_config.login_policy.as_str(),
             "AppState initialized with session manager"
         );
 
@@ -57,7 +141,17 @@ impl AppState {
             redis,
             weibo_api,
             validator,
+            login_policy: validator_config.login_policy,
+            validations,
+            revalidation,
+            refresh,
+            rate_limiter,
             session_manager,
+            transfer,
+            leases,
+            audit,
+            watcher,
+            spool,
         })
     }
 }
@@ -0,0 +1,387 @@
+//! 加密传输包
+//!
+//! 把多账户bundle用口令加密,便于把账户交给队友而不在聊天工具里传明文cookies。
+//!
+//! 文件布局:
+//! ```text
+//! ┌──────────┬──────────────┬──────────────────┬────────────────────────┐
+//! │ magic(8) │ 头部长度(u32) │ 头部JSON (明文)   │ 密文 + Poly1305标签     │
+//! └──────────┴──────────────┴──────────────────┴────────────────────────┘
+//! ```
+//!
+//! - 密钥: Argon2id(口令, salt),参数记录在头部
+//! - 加密: XChaCha20-Poly1305,magic + 头部长度 + 头部整体作为附加认证数据,
+//!   头部任何字节被改动都会导致解密失败
+//! - 明文: `cookie_formats::CookiesBundle` 的JSON
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::TransferError;
+use crate::utils::cookie_formats::{self, CookiesBundle};
+
+/// 文件魔数
+const MAGIC: &[u8; 8] = b"WBCKENC\0";
+
+/// 加密包格式版本
+pub const ENCRYPTED_BUNDLE_VERSION: u32 = 1;
+
+/// 口令最小长度 (字符数)
+pub const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// 头部JSON最大长度,防止恶意文件声明超大头部
+const MAX_HEADER_LEN: usize = 4096;
+
+/// 导入时接受的Argon2最大内存 (KiB),防止恶意参数耗尽内存
+const MAX_M_COST_KIB: u32 = 1024 * 1024;
+
+/// 导入时接受的Argon2最大迭代次数
+const MAX_T_COST: u32 = 16;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Argon2id参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// 内存开销 (KiB)
+    pub m_cost_kib: u32,
+
+    /// 迭代次数
+    pub t_cost: u32,
+
+    /// 并行度
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB / 3次迭代 / 单线程 (OWASP推荐的Argon2id配置之一)
+    fn default() -> Self {
+        Self {
+            m_cost_kib: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+}
+
+/// 加密包头部 (明文,但受AEAD认证保护)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedBundleHeader {
+    /// 格式版本
+    pub version: u32,
+
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+
+    /// 密钥派生算法 (固定为 `argon2id`)
+    pub kdf: String,
+
+    /// 密钥派生参数
+    pub kdf_params: KdfParams,
+
+    /// salt (十六进制)
+    pub salt: String,
+
+    /// 加密算法 (固定为 `xchacha20poly1305`)
+    pub cipher: String,
+
+    /// nonce (十六进制)
+    pub nonce: String,
+}
+
+/// 用默认参数加密bundle
+///
+/// # 错误
+/// 返回 `TransferError::Encryption` 如果口令过短
+pub fn seal(bundle: &CookiesBundle, passphrase: &str) -> Result<Vec<u8>, TransferError> {
+    seal_with_params(bundle, passphrase, KdfParams::default())
+}
+
+/// 用指定Argon2参数加密bundle
+pub fn seal_with_params(
+    bundle: &CookiesBundle,
+    passphrase: &str,
+    kdf_params: KdfParams,
+) -> Result<Vec<u8>, TransferError> {
+    check_passphrase(passphrase)?;
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let header = EncryptedBundleHeader {
+        version: ENCRYPTED_BUNDLE_VERSION,
+        created_at: Utc::now(),
+        kdf: "argon2id".to_string(),
+        kdf_params,
+        salt: to_hex(&salt),
+        cipher: "xchacha20poly1305".to_string(),
+        nonce: to_hex(&nonce),
+    };
+    let header_json =
+        serde_json::to_vec(&header).map_err(|e| TransferError::Encryption(e.to_string()))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + 4 + header_json.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&(header_json.len() as u32).to_be_bytes());
+    out.extend_from_slice(&header_json);
+
+    let key = derive_key(passphrase, &salt, &kdf_params).map_err(TransferError::Encryption)?;
+    let plaintext =
+        serde_json::to_vec(bundle).map_err(|e| TransferError::Encryption(e.to_string()))?;
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &out,
+            },
+        )
+        .map_err(|_| TransferError::Encryption("AEAD加密失败".to_string()))?;
+
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// 检查口令是否满足最小长度
+///
+/// # 错误
+/// 返回 `TransferError::Encryption` 如果口令过短
+pub fn check_passphrase(passphrase: &str) -> Result<(), TransferError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(TransferError::Encryption(format!(
+            "口令至少需要{}个字符",
+            MIN_PASSPHRASE_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// 读取头部 (不需要口令,用于在输入口令前展示创建时间等信息)
+///
+/// # 错误
+/// 返回 `TransferError::InvalidFormat` 如果不是加密包或版本不受支持
+pub fn read_header(data: &[u8]) -> Result<EncryptedBundleHeader, TransferError> {
+    split(data).map(|(header, _, _)| header)
+}
+
+/// 解密并校验bundle
+///
+/// 认证标签校验通过后才解析明文,调用方拿到结果时数据必然完整。
+///
+/// # 错误
+/// - `TransferError::InvalidFormat`: 不是加密包、版本或参数不受支持
+/// - `TransferError::Decryption`: 口令错误或内容被篡改
+pub fn open(
+    data: &[u8],
+    passphrase: &str,
+) -> Result<(EncryptedBundleHeader, CookiesBundle), TransferError> {
+    let (header, aad, ciphertext) = split(data)?;
+
+    let salt = from_hex(&header.salt)
+        .ok_or_else(|| TransferError::InvalidFormat("salt不是有效的十六进制".to_string()))?;
+    let nonce = from_hex(&header.nonce)
+        .filter(|nonce| nonce.len() == 24)
+        .ok_or_else(|| TransferError::InvalidFormat("nonce长度无效".to_string()))?;
+
+    let key =
+        derive_key(passphrase, &salt, &header.kdf_params).map_err(TransferError::InvalidFormat)?;
+    let plaintext = XChaCha20Poly1305::new(&key)
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| TransferError::Decryption("口令错误或文件已被篡改".to_string()))?;
+
+    let content =
+        String::from_utf8(plaintext).map_err(|e| TransferError::InvalidFormat(e.to_string()))?;
+    let bundle = cookie_formats::parse_bundle(&content)
+        .map_err(|e| TransferError::InvalidFormat(e.to_string()))?;
+
+    Ok((header, bundle))
+}
+
+/// 内容是否为加密包
+pub fn is_encrypted_bundle(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// 拆分为 (头部, 附加认证数据, 密文)
+fn split(data: &[u8]) -> Result<(EncryptedBundleHeader, &[u8], &[u8]), TransferError> {
+    if !is_encrypted_bundle(data) || data.len() < MAGIC.len() + 4 {
+        return Err(TransferError::InvalidFormat(
+            "不是加密的cookies传输包".to_string(),
+        ));
+    }
+
+    let len_bytes: [u8; 4] = data[MAGIC.len()..MAGIC.len() + 4]
+        .try_into()
+        .expect("长度已检查");
+    let header_len = u32::from_be_bytes(len_bytes) as usize;
+    let header_end = MAGIC.len() + 4 + header_len;
+    if header_len > MAX_HEADER_LEN || data.len() < header_end {
+        return Err(TransferError::InvalidFormat("头部长度无效".to_string()));
+    }
+
+    let header: EncryptedBundleHeader = serde_json::from_slice(&data[MAGIC.len() + 4..header_end])
+        .map_err(|e| TransferError::InvalidFormat(format!("头部解析失败: {}", e)))?;
+
+    if header.version > ENCRYPTED_BUNDLE_VERSION {
+        return Err(TransferError::InvalidFormat(format!(
+            "不支持的加密包版本: {} (最高支持 {})",
+            header.version, ENCRYPTED_BUNDLE_VERSION
+        )));
+    }
+    if header.kdf != "argon2id" || header.cipher != "xchacha20poly1305" {
+        return Err(TransferError::InvalidFormat(format!(
+            "不支持的算法: {} / {}",
+            header.kdf, header.cipher
+        )));
+    }
+    if header.kdf_params.m_cost_kib > MAX_M_COST_KIB || header.kdf_params.t_cost > MAX_T_COST {
+        return Err(TransferError::InvalidFormat(
+            "密钥派生参数超出允许范围".to_string(),
+        ));
+    }
+
+    Ok((header, &data[..header_end], &data[header_end..]))
+}
+
+/// Argon2id派生密钥
+fn derive_key(passphrase: &str, salt: &[u8], params: &KdfParams) -> Result<Key, String> {
+    let params = Params::new(
+        params.m_cost_kib,
+        params.t_cost,
+        params.p_cost,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| format!("Argon2参数无效: {}", e))?;
+
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("密钥派生失败: {}", e))?;
+    Ok(key)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CookiesData;
+    use crate::utils::cookie_formats::BundleAccount;
+    use std::collections::HashMap;
+
+    /// 测试用低开销参数
+    const FAST: KdfParams = KdfParams {
+        m_cost_kib: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    fn bundle() -> CookiesBundle {
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), "secret_sub".to_string());
+        cookies.insert("SUBP".to_string(), "secret_subp".to_string());
+        let data = CookiesData::new("123".to_string(), cookies);
+        cookie_formats::to_bundle(vec![BundleAccount::from_cookies_data(&data, Vec::new())])
+    }
+
+    #[test]
+    fn test_seal_open_round_trip() {
+        let original = bundle();
+        let sealed = seal_with_params(&original, "correct horse", FAST).unwrap();
+        assert!(is_encrypted_bundle(&sealed));
+        assert!(!String::from_utf8_lossy(&sealed).contains("secret_sub"));
+
+        let (header, opened) = open(&sealed, "correct horse").unwrap();
+        assert_eq!(header.version, ENCRYPTED_BUNDLE_VERSION);
+        assert_eq!(header.kdf_params, FAST);
+        assert_eq!(opened, original);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_rejected() {
+        let sealed = seal_with_params(&bundle(), "correct horse", FAST).unwrap();
+        assert!(matches!(
+            open(&sealed, "wrong horse"),
+            Err(TransferError::Decryption(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_rejected() {
+        let mut sealed = seal_with_params(&bundle(), "correct horse", FAST).unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+        assert!(matches!(
+            open(&sealed, "correct horse"),
+            Err(TransferError::Decryption(_))
+        ));
+    }
+
+    /// 用新头部替换原头部,保留原密文
+    fn with_header(sealed: &[u8], header: &EncryptedBundleHeader) -> Vec<u8> {
+        let (_, _, ciphertext) = split(sealed).unwrap();
+        let header_json = serde_json::to_vec(header).unwrap();
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&(header_json.len() as u32).to_be_bytes());
+        out.extend_from_slice(&header_json);
+        out.extend_from_slice(ciphertext);
+        out
+    }
+
+    #[test]
+    fn test_tampered_header_rejected() {
+        let sealed = seal_with_params(&bundle(), "correct horse", FAST).unwrap();
+        let mut header = read_header(&sealed).unwrap();
+        header.created_at -= chrono::Duration::days(365);
+
+        assert!(matches!(
+            open(&with_header(&sealed, &header), "correct horse"),
+            Err(TransferError::Decryption(_))
+        ));
+    }
+
+    #[test]
+    fn test_excessive_kdf_params_rejected() {
+        let sealed = seal_with_params(&bundle(), "correct horse", FAST).unwrap();
+        let mut header = read_header(&sealed).unwrap();
+        header.kdf_params.m_cost_kib = MAX_M_COST_KIB + 1;
+
+        assert!(matches!(
+            open(&with_header(&sealed, &header), "correct horse"),
+            Err(TransferError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_plain_file_is_not_encrypted_bundle() {
+        assert!(!is_encrypted_bundle(b"# Netscape HTTP Cookie File"));
+        assert!(matches!(
+            read_header(b"{}"),
+            Err(TransferError::InvalidFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,658 @@
+//! Cookies格式转换工具
+//!
+//! 在 `CookiesData` 与外部工具常用的格式之间互相转换:
+//! - Netscape `cookies.txt` (curl, wget, yt-dlp 等)
+//! - Playwright `storageState` JSON
+//! - `Cookie:` 请求头字符串
+//! - 本应用的多账户JSON bundle
+//!
+//! 多账户的Netscape与请求头文本使用 `# weibo-uid: {uid}` 注释行分隔账户,
+//! 这些注释对其他工具无害,解析时据此还原分组。
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::{CookiesData, ValidationError};
+
+/// 导出cookie的默认域名
+pub const WEIBO_COOKIE_DOMAIN: &str = ".weibo.com";
+
+/// 导出cookie的有效期 (与Redis TTL一致)
+const EXPORT_EXPIRY_DAYS: i64 = 30;
+
+/// 账户分隔注释前缀
+const UID_MARKER: &str = "# weibo-uid:";
+
+/// Netscape文件头
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+
+/// bundle格式标识
+pub const BUNDLE_FORMAT: &str = "weibo-cookies-bundle";
+
+/// bundle格式版本
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieFormat {
+    /// Netscape cookies.txt
+    Netscape,
+
+    /// Playwright storageState JSON
+    PlaywrightStorageState,
+
+    /// `Cookie:` 请求头
+    CookieHeader,
+
+    /// 多账户JSON bundle
+    Bundle,
+}
+
+/// 从外部格式解析出的一组cookies (对应一个账户)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieGroup {
+    /// 账户UID (来自分隔注释或bundle,浏览器导出通常没有)
+    pub uid: Option<String>,
+
+    /// 用户昵称 (仅bundle携带)
+    pub screen_name: Option<String>,
+
+    /// 原始获取时间 (仅bundle携带,用于保存时的新旧比较)
+    pub fetched_at: Option<DateTime<Utc>>,
+
+    /// 账户标签 (仅bundle携带)
+    pub tags: Vec<String>,
+
+    /// Cookie键值对
+    pub cookies: HashMap<String, String>,
+
+    /// 因域名不属于微博而忽略的cookie数量
+    pub ignored_foreign: usize,
+}
+
+impl CookieGroup {
+    fn new(uid: Option<String>) -> Self {
+        Self {
+            uid,
+            screen_name: None,
+            fetched_at: None,
+            tags: Vec::new(),
+            cookies: HashMap::new(),
+            ignored_foreign: 0,
+        }
+    }
+}
+
+impl From<BundleAccount> for CookieGroup {
+    fn from(account: BundleAccount) -> Self {
+        Self {
+            uid: Some(account.uid),
+            screen_name: account.screen_name,
+            fetched_at: Some(account.fetched_at),
+            tags: account.tags,
+            cookies: account.cookies,
+            ignored_foreign: 0,
+        }
+    }
+}
+
+/// bundle中的单个账户
+///
+/// 不包含 `redis_key`,导入时由目标环境的命名空间决定。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleAccount {
+    pub uid: String,
+    pub screen_name: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub validated_at: DateTime<Utc>,
+    pub cookies: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl BundleAccount {
+    /// 从CookiesData和标签构建
+    pub fn from_cookies_data(cookies_data: &CookiesData, tags: Vec<String>) -> Self {
+        Self {
+            uid: cookies_data.uid.clone(),
+            screen_name: cookies_data.screen_name.clone(),
+            fetched_at: cookies_data.fetched_at,
+            validated_at: cookies_data.validated_at,
+            cookies: cookies_data.cookies.clone(),
+            tags,
+        }
+    }
+}
+
+/// 多账户JSON bundle
+///
+/// 格式:
+/// ```json
+/// {
+///   "format": "weibo-cookies-bundle",
+///   "version": 1,
+///   "exported_at": "2025-10-05T10:30:45Z",
+///   "accounts": [
+///     {
+///       "uid": "1234567890",
+///       "screen_name": "张三",
+///       "fetched_at": "2025-10-01T08:00:00Z",
+///       "validated_at": "2025-10-01T08:00:00Z",
+///       "cookies": { "SUB": "...", "SUBP": "..." },
+///       "tags": ["项目A"]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookiesBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub accounts: Vec<BundleAccount>,
+}
+
+impl CookiesBundle {
+    /// 创建bundle
+    pub fn new(accounts: Vec<BundleAccount>) -> Self {
+        Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            accounts,
+        }
+    }
+}
+
+/// Playwright storageState
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageState {
+    pub cookies: Vec<StorageStateCookie>,
+    #[serde(default)]
+    pub origins: Vec<serde_json::Value>,
+}
+
+/// storageState中的cookie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStateCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Unix秒,-1表示会话cookie
+    pub expires: f64,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default = "default_same_site")]
+    pub same_site: String,
+}
+
+fn default_same_site() -> String {
+    "Lax".to_string()
+}
+
+/// 域名是否属于微博
+///
+/// 浏览器导出往往包含大量无关站点的cookie,只保留微博相关域名。
+pub fn is_weibo_domain(domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+    ["weibo.com", "weibo.cn", "sina.com.cn"]
+        .iter()
+        .any(|root| domain == *root || domain.ends_with(&format!(".{}", root)))
+}
+
+/// 导出cookie的过期时间戳
+fn export_expiry(cookies_data: &CookiesData) -> i64 {
+    (cookies_data.fetched_at + Duration::days(EXPORT_EXPIRY_DAYS)).timestamp()
+}
+
+/// 按名称排序的cookie,保证输出稳定
+fn sorted_cookies(cookies: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut pairs: Vec<_> = cookies.iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(b.0));
+    pairs
+}
+
+/// 导出为Netscape cookies.txt
+///
+/// 多个账户时每个账户前加 `# weibo-uid: {uid}` 注释。
+pub fn to_netscape(accounts: &[CookiesData]) -> String {
+    let mut out = String::new();
+    out.push_str(NETSCAPE_HEADER);
+    out.push('\n');
+
+    for cookies_data in accounts {
+        out.push('\n');
+        out.push_str(&format!("{} {}\n", UID_MARKER, cookies_data.uid));
+        let expiry = export_expiry(cookies_data);
+        for (name, value) in sorted_cookies(&cookies_data.cookies) {
+            out.push_str(&format!(
+                "{}\tTRUE\t/\tTRUE\t{}\t{}\t{}\n",
+                WEIBO_COOKIE_DOMAIN, expiry, name, value
+            ));
+        }
+    }
+
+    out
+}
+
+/// 解析Netscape cookies.txt
+///
+/// - 支持 `#HttpOnly_` 前缀 (curl及部分浏览器扩展的写法)
+/// - 非微博域名的cookie被忽略并计数
+/// - 无分隔注释时整个文件视为一个账户
+///
+/// # 错误
+/// 返回 `ValidationError::InvalidFormat` 如果数据行字段数不足7个
+pub fn parse_netscape(content: &str) -> Result<Vec<CookieGroup>, ValidationError> {
+    let mut groups: Vec<CookieGroup> = Vec::new();
+    let mut current = CookieGroup::new(None);
+
+    for (line_no, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim_end_matches('\r');
+        let trimmed = line.trim();
+
+        if let Some(uid) = trimmed.strip_prefix(UID_MARKER) {
+            push_group(
+                &mut groups,
+                std::mem::replace(&mut current, CookieGroup::new(None)),
+            );
+            current.uid = Some(uid.trim().to_string());
+            continue;
+        }
+
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if trimmed.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 7 {
+            return Err(ValidationError::InvalidFormat(format!(
+                "cookies.txt 第{}行字段不足: 期望7个,实际{}个",
+                line_no + 1,
+                fields.len()
+            )));
+        }
+
+        if !is_weibo_domain(fields[0]) {
+            current.ignored_foreign += 1;
+            continue;
+        }
+
+        current
+            .cookies
+            .insert(fields[5].to_string(), fields[6..].join("\t"));
+    }
+
+    push_group(&mut groups, current);
+    Ok(groups)
+}
+
+/// 导出为Playwright storageState (单个账户)
+pub fn to_storage_state(cookies_data: &CookiesData) -> StorageState {
+    let expiry = export_expiry(cookies_data) as f64;
+    let cookies = sorted_cookies(&cookies_data.cookies)
+        .into_iter()
+        .map(|(name, value)| StorageStateCookie {
+            name: name.clone(),
+            value: value.clone(),
+            domain: WEIBO_COOKIE_DOMAIN.to_string(),
+            path: "/".to_string(),
+            expires: expiry,
+            http_only: false,
+            secure: true,
+            same_site: default_same_site(),
+        })
+        .collect();
+
+    StorageState {
+        cookies,
+        origins: Vec::new(),
+    }
+}
+
+/// 解析Playwright storageState
+///
+/// storageState对应一个浏览器上下文,因此总是返回一个分组。
+///
+/// # 错误
+/// 返回 `ValidationError::InvalidFormat` 如果JSON结构不符合storageState
+pub fn parse_storage_state(content: &str) -> Result<CookieGroup, ValidationError> {
+    let state: StorageState = serde_json::from_str(content)
+        .map_err(|e| ValidationError::InvalidFormat(format!("storageState解析失败: {}", e)))?;
+
+    let mut group = CookieGroup::new(None);
+    for cookie in state.cookies {
+        if is_weibo_domain(&cookie.domain) {
+            group.cookies.insert(cookie.name, cookie.value);
+        } else {
+            group.ignored_foreign += 1;
+        }
+    }
+    Ok(group)
+}
+
+/// 导出为 `Cookie:` 请求头值 (单个账户,不含 `Cookie:` 前缀)
+///
+/// # 示例
+/// ```
+/// use weibo_login::models::CookiesData;
+/// use weibo_login::utils::cookie_formats::to_cookie_header;
+/// use std::collections::HashMap;
+///
+/// let mut cookies = HashMap::new();
+/// cookies.insert("SUBP".to_string(), "yyy".to_string());
+/// cookies.insert("SUB".to_string(), "xxx".to_string());
+/// let data = CookiesData::new("1".to_string(), cookies);
+/// assert_eq!(to_cookie_header(&data), "SUB=xxx; SUBP=yyy");
+/// ```
+pub fn to_cookie_header(cookies_data: &CookiesData) -> String {
+    sorted_cookies(&cookies_data.cookies)
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// 导出为请求头文本文件
+///
+/// 每个账户一行 `Cookie: ...`,前面是 `# weibo-uid: {uid}` 注释。
+pub fn to_cookie_header_lines(accounts: &[CookiesData]) -> String {
+    accounts
+        .iter()
+        .map(|cookies_data| {
+            format!(
+                "{} {}\nCookie: {}\n",
+                UID_MARKER,
+                cookies_data.uid,
+                to_cookie_header(cookies_data)
+            )
+        })
+        .collect()
+}
+
+/// 解析请求头文本
+///
+/// 每个非空、非注释行是一个账户,可带或不带 `Cookie:` 前缀 (大小写不敏感)。
+/// 紧邻的 `# weibo-uid:` 注释为该行指定UID。
+///
+/// # 错误
+/// 返回 `ValidationError::InvalidFormat` 如果某个片段不是 `name=value`
+pub fn parse_cookie_header(content: &str) -> Result<Vec<CookieGroup>, ValidationError> {
+    let mut groups = Vec::new();
+    let mut pending_uid: Option<String> = None;
+
+    for (line_no, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim();
+
+        if let Some(uid) = line.strip_prefix(UID_MARKER) {
+            pending_uid = Some(uid.trim().to_string());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let value = match line.split_once(':') {
+            Some((name, rest)) if name.trim().eq_ignore_ascii_case("cookie") => rest,
+            _ => line,
+        };
+
+        let mut group = CookieGroup::new(pending_uid.take());
+        for pair in value.split(';') {
+            let pair = pair.trim();
+            if pair.is_empty() {
+                continue;
+            }
+            let (name, value) = pair.split_once('=').ok_or_else(|| {
+                ValidationError::InvalidFormat(format!(
+                    "第{}行不是有效的cookie: {}",
+                    line_no + 1,
+                    pair.split('=').next().unwrap_or_default()
+                ))
+            })?;
+            group
+                .cookies
+                .insert(name.trim().to_string(), value.trim().to_string());
+        }
+        push_group(&mut groups, group);
+    }
+
+    Ok(groups)
+}
+
+/// 导出为bundle
+pub fn to_bundle(accounts: Vec<BundleAccount>) -> CookiesBundle {
+    CookiesBundle::new(accounts)
+}
+
+/// 解析bundle
+///
+/// # 错误
+/// 返回 `ValidationError::InvalidFormat` 如果格式标识或版本不受支持
+pub fn parse_bundle(content: &str) -> Result<CookiesBundle, ValidationError> {
+    let bundle: CookiesBundle = serde_json::from_str(content)
+        .map_err(|e| ValidationError::InvalidFormat(format!("bundle解析失败: {}", e)))?;
+
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(ValidationError::InvalidFormat(format!(
+            "未知的bundle格式: {}",
+            bundle.format
+        )));
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(ValidationError::InvalidFormat(format!(
+            "不支持的bundle版本: {} (最高支持 {})",
+            bundle.version, BUNDLE_VERSION
+        )));
+    }
+
+    Ok(bundle)
+}
+
+/// 根据内容推断格式
+///
+/// - JSON且带 `format` 字段: bundle
+/// - JSON且带 `cookies` 数组: storageState
+/// - 有Netscape文件头或制表符分隔的7列数据行: Netscape
+/// - 其余: 请求头文本
+pub fn detect_format(content: &str) -> CookieFormat {
+    let trimmed = content.trim_start();
+
+    if trimmed.starts_with('{') {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) {
+            if value.get("format").is_some() {
+                return CookieFormat::Bundle;
+            }
+            if value.get("cookies").is_some_and(|c| c.is_array()) {
+                return CookieFormat::PlaywrightStorageState;
+            }
+        }
+    }
+
+    let is_netscape = trimmed.starts_with("# Netscape")
+        || trimmed.starts_with("# HTTP Cookie File")
+        || trimmed.lines().any(|line| {
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            !line.starts_with('#') && line.split('\t').count() >= 7
+        });
+    if is_netscape {
+        return CookieFormat::Netscape;
+    }
+
+    CookieFormat::CookieHeader
+}
+
+/// 按指定格式解析为账户分组
+///
+/// # 错误
+/// 返回 `ValidationError::InvalidFormat` 如果内容不符合该格式
+pub fn parse_groups(
+    content: &str,
+    format: CookieFormat,
+) -> Result<Vec<CookieGroup>, ValidationError> {
+    match format {
+        CookieFormat::Netscape => parse_netscape(content),
+        CookieFormat::PlaywrightStorageState => Ok(vec![parse_storage_state(content)?]),
+        CookieFormat::CookieHeader => parse_cookie_header(content),
+        CookieFormat::Bundle => Ok(parse_bundle(content)?
+            .accounts
+            .into_iter()
+            .map(CookieGroup::from)
+            .collect()),
+    }
+}
+
+/// 追加分组,跳过既无cookie又无UID的空分组
+fn push_group(groups: &mut Vec<CookieGroup>, group: CookieGroup) {
+    if !group.cookies.is_empty() || group.uid.is_some() || group.ignored_foreign > 0 {
+        groups.push(group);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(uid: &str, sub: &str) -> CookiesData {
+        let mut cookies = HashMap::new();
+        cookies.insert("SUB".to_string(), sub.to_string());
+        cookies.insert("SUBP".to_string(), format!("{}_p", sub));
+        CookiesData::new(uid.to_string(), cookies).with_screen_name(format!("用户{}", uid))
+    }
+
+    #[test]
+    fn test_netscape_round_trip_multiple_accounts() {
+        let accounts = vec![account("111", "a"), account("222", "b")];
+        let text = to_netscape(&accounts);
+        assert!(text.starts_with(NETSCAPE_HEADER));
+
+        let groups = parse_netscape(&text).unwrap();
+        assert_eq!(groups.len(), 2);
+        for (group, original) in groups.iter().zip(&accounts) {
+            assert_eq!(group.uid.as_deref(), Some(original.uid.as_str()));
+            assert_eq!(group.cookies, original.cookies);
+        }
+    }
+
+    #[test]
+    fn test_netscape_browser_export_filters_foreign_domains() {
+        let text = "# Netscape HTTP Cookie File\n\
+                    #HttpOnly_.weibo.com\tTRUE\t/\tTRUE\t0\tSUB\tx\n\
+                    .weibo.com\tTRUE\t/\tFALSE\t0\tSUBP\ty\n\
+                    .example.com\tTRUE\t/\tFALSE\t0\tsid\tz\n";
+        let groups = parse_netscape(text).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].uid, None);
+        assert_eq!(groups[0].cookies.len(), 2);
+        assert_eq!(groups[0].ignored_foreign, 1);
+    }
+
+    #[test]
+    fn test_netscape_rejects_short_line() {
+        assert!(parse_netscape(".weibo.com\tTRUE\t/\n").is_err());
+    }
+
+    #[test]
+    fn test_storage_state_round_trip() {
+        let original = account("111", "a");
+        let json = serde_json::to_string(&to_storage_state(&original)).unwrap();
+        assert!(json.contains("\"httpOnly\""));
+
+        let group = parse_storage_state(&json).unwrap();
+        assert_eq!(group.cookies, original.cookies);
+    }
+
+    #[test]
+    fn test_cookie_header_round_trip() {
+        let accounts = vec![account("111", "a"), account("222", "b")];
+        let text = to_cookie_header_lines(&accounts);
+
+        let groups = parse_cookie_header(&text).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].uid.as_deref(), Some("222"));
+        assert_eq!(groups[1].cookies, accounts[1].cookies);
+    }
+
+    #[test]
+    fn test_cookie_header_accepts_raw_value() {
+        let groups = parse_cookie_header("SUB=x; SUBP=y=z").unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].cookies.get("SUBP"), Some(&"y=z".to_string()));
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let accounts = vec![
+            BundleAccount::from_cookies_data(&account("111", "a"), vec!["t1".to_string()]),
+            BundleAccount::from_cookies_data(&account("222", "b"), Vec::new()),
+        ];
+        let bundle = to_bundle(accounts.clone());
+        let json = serde_json::to_string_pretty(&bundle).unwrap();
+
+        let parsed = parse_bundle(&json).unwrap();
+        assert_eq!(parsed.version, BUNDLE_VERSION);
+        assert_eq!(parsed.accounts, accounts);
+    }
+
+    #[test]
+    fn test_bundle_rejects_unknown_format() {
+        let json =
+            r#"{"format":"other","version":1,"exported_at":"2025-01-01T00:00:00Z","accounts":[]}"#;
+        assert!(parse_bundle(json).is_err());
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let accounts = vec![account("111", "a")];
+        assert_eq!(
+            detect_format(&to_netscape(&accounts)),
+            CookieFormat::Netscape
+        );
+        assert_eq!(
+            detect_format(&serde_json::to_string(&to_storage_state(&accounts[0])).unwrap()),
+            CookieFormat::PlaywrightStorageState
+        );
+        assert_eq!(
+            detect_format(&to_cookie_header_lines(&accounts)),
+            CookieFormat::CookieHeader
+        );
+        let bundle = to_bundle(vec![BundleAccount::from_cookies_data(
+            &accounts[0],
+            Vec::new(),
+        )]);
+        assert_eq!(
+            detect_format(&serde_json::to_string(&bundle).unwrap()),
+            CookieFormat::Bundle
+        );
+    }
+
+    #[test]
+    fn test_parse_groups_keeps_bundle_metadata() {
+        let original = account("111", "a");
+        let bundle = to_bundle(vec![BundleAccount::from_cookies_data(
+            &original,
+            vec!["t1".to_string()],
+        )]);
+        let json = serde_json::to_string(&bundle).unwrap();
+
+        let groups = parse_groups(&json, CookieFormat::Bundle).unwrap();
+        assert_eq!(groups[0].fetched_at, Some(original.fetched_at));
+        assert_eq!(groups[0].tags, vec!["t1"]);
+        assert_eq!(groups[0].screen_name, original.screen_name);
+    }
+
+    #[test]
+    fn test_is_weibo_domain() {
+        assert!(is_weibo_domain(".weibo.com"));
+        assert!(is_weibo_domain("passport.weibo.cn"));
+        assert!(is_weibo_domain("login.sina.com.cn"));
+        assert!(!is_weibo_domain("notweibo.com"));
+    }
+}
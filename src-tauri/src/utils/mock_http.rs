@@ -0,0 +1,160 @@
+//! 测试用本地HTTP服务器
+//!
+//! 只处理无请求体的GET请求: 按路径 (不含查询参数) 返回预设响应,
+//! 并记录收到的请求,用于在不访问微博的情况下测试HTTP客户端。
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// 预设响应
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl MockResponse {
+    /// JSON响应
+    pub(crate) fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.to_string(),
+        }
+    }
+
+    /// 纯文本/HTML响应
+    pub(crate) fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".into(), "text/html; charset=utf-8".into())],
+            body: body.to_string(),
+        }
+    }
+
+    /// 追加响应头,同名头可重复 (如多个Set-Cookie)
+    pub(crate) fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// 收到的请求
+#[derive(Debug, Clone)]
+pub(crate) struct RecordedRequest {
+    /// 路径,含查询参数
+    pub(crate) path: String,
+    /// 请求头,名称为小写
+    pub(crate) headers: HashMap<String, String>,
+}
+
+/// 本地HTTP服务器,drop时停止
+pub(crate) struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// 在随机端口启动,`routes` 为 (路径, 响应),未匹配的路径返回404
+    pub(crate) async fn start(routes: Vec<(&str, MockResponse)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes: Arc<HashMap<String, MockResponse>> = Arc::new(
+            routes
+                .into_iter()
+                .map(|(path, response)| (path.to_string(), response))
+                .collect(),
+        );
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let routes = routes.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = Self::serve(stream, &routes, &recorded).await;
+                });
+            }
+        });
+
+        Self {
+            addr,
+            requests,
+            task,
+        }
+    }
+
+    /// 服务器地址,如 `http://127.0.0.1:34567`
+    pub(crate) fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 到目前为止收到的请求
+    pub(crate) fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    async fn serve(
+        mut stream: TcpStream,
+        routes: &HashMap<String, MockResponse>,
+        recorded: &Mutex<Vec<RecordedRequest>>,
+    ) -> std::io::Result<()> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let head = String::from_utf8_lossy(&buf);
+        let mut lines = head.split("\r\n");
+        let path = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/")
+            .to_string();
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        recorded.lock().unwrap().push(RecordedRequest {
+            path: path.clone(),
+            headers,
+        });
+
+        let route = path.split('?').next().unwrap_or_default();
+        let response = routes
+            .get(route)
+            .cloned()
+            .unwrap_or_else(|| MockResponse::text(404, "not found"));
+
+        let mut out = format!("HTTP/1.1 {} MOCK\r\n", response.status);
+        for (name, value) in &response.headers {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        out.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.body.len(),
+            response.body
+        ));
+        stream.write_all(out.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -1,3 +1,8 @@
 // 工具函数模块
+pub mod bundle_crypto;
+pub mod cookie_formats;
 pub mod logger;
+#[cfg(test)]
+pub(crate) mod mock_http;
+pub mod private_file;
 pub mod version;
@@ -0,0 +1,54 @@
+//! 仅当前用户可读写的文件
+//!
+//! 暂存队列、导出的cookies等含登录凭证的文件都通过这里创建,
+//! 避免按默认权限 (通常为0644) 落盘后被本机其他用户读取。
+
+use std::io::Write;
+use std::path::Path;
+
+/// 创建 (或截断) 仅当前用户可读写的文件
+///
+/// 文件已存在时同样收紧为0600,覆盖旧的导出文件不会沿用宽松的权限。
+pub fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/// 以0600权限写入整个文件,用法同 `std::fs::write`
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    create_private(path)?.write_all(contents.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_restricts_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("weibo-private-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "SUB=secret").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "SUB=secret");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}